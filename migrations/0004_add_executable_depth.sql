@@ -0,0 +1,5 @@
+-- Depth-aware sizing: largest profitable paired size, its VWAP combined cost and dollar profit.
+ALTER TABLE windows ADD COLUMN executable_size REAL;
+ALTER TABLE windows ADD COLUMN executable_cost REAL;
+ALTER TABLE windows ADD COLUMN expected_profit REAL;
+CREATE INDEX IF NOT EXISTS idx_windows_expected_profit ON windows(expected_profit);
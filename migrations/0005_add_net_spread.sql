@@ -0,0 +1,3 @@
+-- Fee- and cost-adjusted values stored alongside the gross spread_size / expected_profit.
+ALTER TABLE windows ADD COLUMN net_spread REAL;
+ALTER TABLE windows ADD COLUMN net_profit REAL;
@@ -0,0 +1,3 @@
+-- Basket type: binary (yes+no of one market) or neg-risk event baskets.
+-- For neg-risk windows market_id holds the Gamma event id and yes_ask/no_ask hold summed member asks.
+ALTER TABLE windows ADD COLUMN window_kind TEXT NOT NULL DEFAULT 'binary';
@@ -0,0 +1,5 @@
+-- Which side of the book the window trades: buy (yes_ask + no_ask < 1) or sell (yes_bid + no_bid > 1).
+-- For sell windows combined_cost holds the combined bid proceeds.
+ALTER TABLE windows ADD COLUMN side TEXT NOT NULL DEFAULT 'buy';
+ALTER TABLE windows ADD COLUMN yes_bid REAL;
+ALTER TABLE windows ADD COLUMN no_bid REAL;
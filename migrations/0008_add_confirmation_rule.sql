@@ -0,0 +1,5 @@
+-- Confirmation rule in force when the window opened (see config::ConfirmationRules).
+-- confirm_rule_source is `default`, `category:<name>` or `pinned:<prefix>`.
+ALTER TABLE windows ADD COLUMN confirm_min_ticks INTEGER;
+ALTER TABLE windows ADD COLUMN confirm_min_elapsed_ms INTEGER;
+ALTER TABLE windows ADD COLUMN confirm_rule_source TEXT;
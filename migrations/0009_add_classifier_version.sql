@@ -0,0 +1,5 @@
+-- Which WindowClassifier (name@vN) produced open_duration_class / close_reason / opportunity_class.
+ALTER TABLE windows ADD COLUMN classifier_version TEXT;
+
+-- Rows classified before versioning used the fixed MIN_ARB_TICKS classifier.
+UPDATE windows SET classifier_version = 'tick_count@v1' WHERE open_duration_class IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Per-window tick trace: every price tick (and trade) observed while a window was active.
+-- Neg-risk windows store summed member prices and NULL sizes.
+CREATE TABLE IF NOT EXISTS window_ticks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    window_id INTEGER NOT NULL REFERENCES windows(id),
+    seq INTEGER NOT NULL,
+    at_ns INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    yes_ask REAL NOT NULL,
+    no_ask REAL NOT NULL,
+    yes_bid REAL NOT NULL,
+    no_bid REAL NOT NULL,
+    yes_ask_size REAL,
+    no_ask_size REAL,
+    yes_bid_size REAL,
+    no_bid_size REAL,
+    spread REAL NOT NULL,
+    trade_asset_id TEXT,
+    trade_price REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_window_ticks_window_id ON window_ticks(window_id, seq);
@@ -0,0 +1,10 @@
+-- Peak / closing / time-weighted spread and per-leg ask range over a window's arb ticks.
+-- Pre-existing rows keep NULLs; the scorer falls back to spread_size for them.
+ALTER TABLE windows ADD COLUMN peak_spread REAL;
+ALTER TABLE windows ADD COLUMN peak_spread_at INTEGER;
+ALTER TABLE windows ADD COLUMN closing_spread REAL;
+ALTER TABLE windows ADD COLUMN twa_spread REAL;
+ALTER TABLE windows ADD COLUMN min_yes_ask REAL;
+ALTER TABLE windows ADD COLUMN max_yes_ask REAL;
+ALTER TABLE windows ADD COLUMN min_no_ask REAL;
+ALTER TABLE windows ADD COLUMN max_no_ask REAL;
@@ -0,0 +1,4 @@
+-- Server-side (exchange) timestamps, in ms, of the messages that opened and closed
+-- a window. NULL when the feed message carried no timestamp.
+ALTER TABLE windows ADD COLUMN exchange_opened_at INTEGER;
+ALTER TABLE windows ADD COLUMN exchange_closed_at INTEGER;
@@ -0,0 +1,14 @@
+-- Paired book depth on the window's side (thinner leg's size within the widest
+-- BOOK_DEPTH_BANDS band) at the first arb tick and at close. NULL for neg-risk
+-- windows and for rows written before book metrics existed.
+ALTER TABLE windows ADD COLUMN open_book_depth REAL;
+ALTER TABLE windows ADD COLUMN close_book_depth REAL;
+
+-- Scorer inputs: 24h average of open_book_depth, plus the live books sampled on
+-- each scoring pass.
+ALTER TABLE market_stats ADD COLUMN avg_open_book_depth REAL;
+ALTER TABLE market_stats ADD COLUMN book_ask_depth REAL;
+ALTER TABLE market_stats ADD COLUMN book_bid_depth REAL;
+ALTER TABLE market_stats ADD COLUMN book_imbalance REAL;
+ALTER TABLE market_stats ADD COLUMN book_levels INTEGER;
+ALTER TABLE market_stats ADD COLUMN book_update_rate REAL;
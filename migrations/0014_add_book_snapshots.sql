@@ -0,0 +1,17 @@
+-- Top-of-book archive: one row per token per snapshot. `asks` / `bids` are JSON
+-- arrays of [price, size] pairs, best first, cut at BOOK_SNAPSHOT_DEPTH levels.
+-- `reason` is `periodic` or `window_open`.
+CREATE TABLE IF NOT EXISTS book_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    market_id TEXT NOT NULL,
+    asset_id TEXT NOT NULL,
+    taken_at INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    best_ask REAL,
+    best_bid REAL,
+    asks TEXT NOT NULL,
+    bids TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_snapshots_market_taken_at ON book_snapshots(market_id, taken_at);
+CREATE INDEX IF NOT EXISTS idx_book_snapshots_taken_at ON book_snapshots(taken_at);
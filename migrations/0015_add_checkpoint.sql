@@ -0,0 +1,41 @@
+-- Warm-restart checkpoint: the tracked market set with last-known prices, rewritten
+-- every CHECKPOINT_INTERVAL_SECS so a restart can resubscribe before Gamma answers.
+-- Neg-risk members carry their `event_id`; the event itself is in `checkpoint_events`.
+CREATE TABLE IF NOT EXISTS checkpoint_markets (
+    market_id TEXT PRIMARY KEY,
+    event_id TEXT,
+    question TEXT NOT NULL,
+    category TEXT NOT NULL,
+    end_date_iso TEXT,
+    total_volume REAL,
+    yes_token_id TEXT NOT NULL,
+    no_token_id TEXT NOT NULL,
+    yes_ask REAL,
+    yes_bid REAL,
+    no_ask REAL,
+    no_bid REAL,
+    saved_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS checkpoint_events (
+    event_id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    category TEXT NOT NULL,
+    end_date_iso TEXT,
+    saved_at INTEGER NOT NULL
+);
+
+-- Every series market the PinnedMarketWatcher last fetched for a PINNED_SLUGS
+-- prefix, current and upcoming, so it can keep handing off while Gamma is down.
+CREATE TABLE IF NOT EXISTS pinned_markets (
+    market_id TEXT PRIMARY KEY,
+    prefix TEXT NOT NULL,
+    end_ts INTEGER NOT NULL,
+    question TEXT NOT NULL,
+    category TEXT NOT NULL,
+    end_date_iso TEXT,
+    total_volume REAL,
+    yes_token_id TEXT NOT NULL,
+    no_token_id TEXT NOT NULL,
+    saved_at INTEGER NOT NULL
+);
@@ -0,0 +1,470 @@
+//! Local stand-in for Polymarket's market-channel WebSocket
+//! (`wss://ws-subscriptions-clob.polymarket.com/ws/market`), so the scanner can be
+//! driven end to end offline by pointing `WS_URL` at it.
+//!
+//! The server tracks `{"assets_ids": [...], "type": "market"}` subscribe and
+//! `"operation": "unsubscribe"` frames per client, answers a subscribe with the
+//! `book` snapshots set through `set_book` (as one array frame, like the real
+//! server), and sends scripted events only to clients subscribed to their assets.
+//! Faults: `disconnect_all` (close frame), `drop_all` (TCP drop without a close
+//! handshake), `send_raw` (malformed frames) and slow consumers — a client whose
+//! outbound queue fills up is closed with code 1008, as the real server does.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+/// Close code sent to a client that fell behind.
+pub const SLOW_CONSUMER_CLOSE_CODE: u16 = 1008;
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// Frames queued per client before it counts as a slow consumer and is closed.
+    pub client_queue: usize,
+    /// Answer a subscribe with the `book` of every newly subscribed asset that has one.
+    pub snapshot_on_subscribe: bool,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self { client_queue: 4096, snapshot_on_subscribe: true }
+    }
+}
+
+/// One step of a scripted session, see `MockServer::run_script`. In JSON (one
+/// step per line for the `mock-ws` binary): `{"wait_subscribed": 2}`,
+/// `{"sleep_ms": 100}`, `{"send": {...}}`, `{"raw": "not json"}`,
+/// `{"flood": {"frame": {...}, "count": 1000}}`,
+/// `{"disconnect": {"code": 1001, "reason": "restart"}}` or `"drop"`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Wait until at least this many distinct assets are subscribed.
+    WaitSubscribed(usize),
+    SleepMs(u64),
+    /// Send a frame to the clients subscribed to its assets.
+    Send(Value),
+    /// Send text as-is to every client.
+    Raw(String),
+    /// Send `frame` `count` times back to back.
+    Flood { frame: Value, count: usize },
+    /// Close every client with a close frame.
+    Disconnect { code: u16, reason: String },
+    /// Drop every client's socket without a close handshake.
+    Drop,
+}
+
+enum Outbound {
+    Text(String),
+    Close(u16, String),
+    Drop,
+}
+
+struct Client {
+    tx: mpsc::Sender<Outbound>,
+    assets: HashSet<String>,
+    /// Woken when the client's queue overflows.
+    evict: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct Shared {
+    config: MockConfig,
+    clients: Mutex<HashMap<u64, Client>>,
+    /// asset_id → `book` message sent on subscribe.
+    books: Mutex<HashMap<String, Value>>,
+    next_client: AtomicU64,
+    connections_accepted: AtomicU64,
+    slow_consumers: AtomicU64,
+}
+
+/// A running mock server; stops accepting when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts on an ephemeral localhost port.
+    pub async fn start(config: MockConfig) -> std::io::Result<Self> {
+        Self::start_on(SocketAddr::from(([127, 0, 0, 1], 0)), config).await
+    }
+
+    pub async fn start_on(addr: SocketAddr, config: MockConfig) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared { config, ..Shared::default() });
+        let accept_shared = Arc::clone(&shared);
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, peer)) = listener.accept().await {
+                debug!("[MOCK WS] connection from {peer}");
+                tokio::spawn(serve(Arc::clone(&accept_shared), stream));
+            }
+        });
+        Ok(Self { addr, shared, accept_task })
+    }
+
+    /// `ws://` URL to hand the scanner as `WS_URL`.
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Sets the book sent to clients when they subscribe to `asset_id`. Levels are
+    /// `(price, size)` strings, as on the wire.
+    pub fn set_book(&self, asset_id: &str, asks: &[(&str, &str)], bids: &[(&str, &str)]) {
+        self.shared.books.lock().unwrap().insert(asset_id.to_string(), frames::book(asset_id, asks, bids));
+    }
+
+    /// Uses the `book` messages in `frame` (object or array) as subscribe snapshots.
+    pub fn register_books(&self, frame: &Value) {
+        let mut books = self.shared.books.lock().unwrap();
+        let msgs = match frame {
+            Value::Array(msgs) => msgs.iter().collect(),
+            msg => vec![msg],
+        };
+        for msg in msgs.into_iter().filter(|m| m["event_type"] == "book") {
+            if let Some(asset_id) = msg["asset_id"].as_str() {
+                books.insert(asset_id.to_string(), msg.clone());
+            }
+        }
+    }
+
+    /// Sends a frame (object or array) to every client subscribed to any asset it
+    /// mentions, or to every client if it mentions none. Returns the clients reached.
+    pub fn send(&self, frame: &Value) -> usize {
+        let assets = frame_assets(frame);
+        self.shared.broadcast(&frame.to_string(), |client| {
+            assets.is_empty() || assets.iter().any(|a| client.assets.contains(a))
+        })
+    }
+
+    /// Sends text as-is (e.g. a malformed frame) to every client.
+    pub fn send_raw(&self, text: &str) -> usize {
+        self.shared.broadcast(text, |_| true)
+    }
+
+    /// Closes every client with a close frame.
+    pub fn disconnect_all(&self, code: u16, reason: &str) {
+        for client in self.shared.clients.lock().unwrap().values() {
+            let _ = client.tx.try_send(Outbound::Close(code, reason.to_string()));
+        }
+    }
+
+    /// Drops every client's socket without a close handshake.
+    pub fn drop_all(&self) {
+        for client in self.shared.clients.lock().unwrap().values() {
+            let _ = client.tx.try_send(Outbound::Drop);
+        }
+    }
+
+    /// Union of the assets currently subscribed by all clients.
+    pub fn subscribed(&self) -> HashSet<String> {
+        let clients = self.shared.clients.lock().unwrap();
+        clients.values().flat_map(|c| c.assets.iter().cloned()).collect()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.shared.clients.lock().unwrap().len()
+    }
+
+    /// WebSocket handshakes completed since start.
+    pub fn connections_accepted(&self) -> u64 {
+        self.shared.connections_accepted.load(Ordering::Relaxed)
+    }
+
+    /// Clients closed for falling behind.
+    pub fn slow_consumers(&self) -> u64 {
+        self.shared.slow_consumers.load(Ordering::Relaxed)
+    }
+
+    /// Waits until every asset in `asset_ids` is subscribed. False on timeout.
+    pub async fn wait_for_subscribed(&self, asset_ids: &[&str], timeout: Duration) -> bool {
+        self.wait_until(timeout, || {
+            let subscribed = self.subscribed();
+            asset_ids.iter().all(|a| subscribed.contains(*a))
+        })
+        .await
+    }
+
+    /// Waits until at least `n` handshakes have completed since start. False on timeout.
+    pub async fn wait_for_connections(&self, n: u64, timeout: Duration) -> bool {
+        self.wait_until(timeout, || self.connections_accepted() >= n).await
+    }
+
+    async fn wait_until(&self, timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !done() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        true
+    }
+
+    /// Runs `steps` in order.
+    pub async fn run_script(&self, steps: &[Step]) {
+        for step in steps {
+            match step {
+                Step::WaitSubscribed(n) => {
+                    while self.subscribed().len() < *n {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+                }
+                Step::SleepMs(ms) => tokio::time::sleep(Duration::from_millis(*ms)).await,
+                Step::Send(frame) => {
+                    self.send(frame);
+                }
+                Step::Raw(text) => {
+                    self.send_raw(text);
+                }
+                Step::Flood { frame, count } => {
+                    for _ in 0..*count {
+                        self.send(frame);
+                    }
+                }
+                Step::Disconnect { code, reason } => self.disconnect_all(*code, reason),
+                Step::Drop => self.drop_all(),
+            }
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+        self.drop_all();
+    }
+}
+
+impl Shared {
+    /// Queues `text` for every client matching `filter`; clients whose queue is
+    /// full are evicted as slow consumers. Returns the clients the frame was queued for.
+    fn broadcast(&self, text: &str, filter: impl Fn(&Client) -> bool) -> usize {
+        let clients = self.clients.lock().unwrap();
+        let mut sent = 0;
+        for client in clients.values().filter(|c| filter(c)) {
+            match client.tx.try_send(Outbound::Text(text.to_string())) {
+                Ok(()) => sent += 1,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    self.slow_consumers.fetch_add(1, Ordering::Relaxed);
+                    client.evict.notify_one();
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {}
+            }
+        }
+        sent
+    }
+
+    /// Applies a client's text frame; returns the frames to answer with.
+    fn handle_client_text(&self, client_id: u64, text: &str) -> Vec<String> {
+        if text == "PING" {
+            return vec!["PONG".to_string()];
+        }
+        let Ok(msg) = serde_json::from_str::<Value>(text) else {
+            warn!("[MOCK WS] client {client_id} sent non-JSON text: {text}");
+            return vec![];
+        };
+        let Some(ids) = msg.get("assets_ids").and_then(Value::as_array) else {
+            return vec![];
+        };
+        let ids = ids.iter().filter_map(Value::as_str);
+        let mut clients = self.clients.lock().unwrap();
+        let Some(client) = clients.get_mut(&client_id) else {
+            return vec![];
+        };
+        if msg.get("operation").and_then(Value::as_str) == Some("unsubscribe") {
+            for id in ids {
+                client.assets.remove(id);
+            }
+            return vec![];
+        }
+        let added: Vec<String> = ids.filter(|id| client.assets.insert(id.to_string())).map(str::to_string).collect();
+        drop(clients);
+        debug!("[MOCK WS] client {client_id} subscribed to {} new asset(s)", added.len());
+        if !self.config.snapshot_on_subscribe {
+            return vec![];
+        }
+        let books = self.books.lock().unwrap();
+        let snapshots: Vec<Value> = added.iter().filter_map(|id| books.get(id).cloned()).collect();
+        if snapshots.is_empty() {
+            vec![]
+        } else {
+            vec![Value::Array(snapshots).to_string()]
+        }
+    }
+}
+
+async fn serve(shared: Arc<Shared>, stream: TcpStream) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    shared.connections_accepted.fetch_add(1, Ordering::Relaxed);
+    let (mut write, mut read) = ws.split();
+    let (tx, mut rx) = mpsc::channel(shared.config.client_queue.max(1));
+    let evict = Arc::new(Notify::new());
+    let id = shared.next_client.fetch_add(1, Ordering::Relaxed);
+    shared.clients.lock().unwrap().insert(id, Client { tx, assets: HashSet::new(), evict: Arc::clone(&evict) });
+
+    let close = |code: u16, reason: &str| {
+        Message::Close(Some(CloseFrame { code: CloseCode::from(code), reason: reason.to_string().into() }))
+    };
+    loop {
+        tokio::select! {
+            msg = read.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    for reply in shared.handle_client_text(id, &text) {
+                        if write.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            out = rx.recv() => match out {
+                Some(Outbound::Text(text)) => {
+                    if write.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Outbound::Close(code, reason)) => {
+                    let _ = write.send(close(code, &reason)).await;
+                    break;
+                }
+                Some(Outbound::Drop) | None => break,
+            },
+            _ = evict.notified() => {
+                info!("[MOCK WS] client {id} fell behind; closing as a slow consumer");
+                let _ = write.send(close(SLOW_CONSUMER_CLOSE_CODE, "slow consumer")).await;
+                break;
+            }
+        }
+    }
+    shared.clients.lock().unwrap().remove(&id);
+}
+
+/// Asset ids a market-channel frame is about.
+fn frame_assets(frame: &Value) -> HashSet<String> {
+    let mut assets = HashSet::new();
+    let mut add = |msg: &Value| {
+        if let Some(id) = msg.get("asset_id").and_then(Value::as_str) {
+            assets.insert(id.to_string());
+        }
+        for change in msg.get("price_changes").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(id) = change.get("asset_id").and_then(Value::as_str) {
+                assets.insert(id.to_string());
+            }
+        }
+    };
+    match frame {
+        Value::Array(msgs) => msgs.iter().for_each(&mut add),
+        msg => add(msg),
+    }
+    assets
+}
+
+/// Builders for market-channel messages, stamped with the current time.
+pub mod frames {
+    use super::*;
+
+    fn now_ms() -> String {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis().to_string()
+    }
+
+    fn levels(levels: &[(&str, &str)]) -> Value {
+        levels.iter().map(|(price, size)| json!({ "price": price, "size": size })).collect()
+    }
+
+    pub fn book(asset_id: &str, asks: &[(&str, &str)], bids: &[(&str, &str)]) -> Value {
+        json!({
+            "event_type": "book",
+            "asset_id": asset_id,
+            "market": "mock",
+            "asks": levels(asks),
+            "bids": levels(bids),
+            "timestamp": now_ms(),
+        })
+    }
+
+    /// One `price_change` message; `changes` are `(asset_id, price, size, side)`
+    /// with side `"BUY"` or `"SELL"`.
+    pub fn price_change(market: &str, changes: &[(&str, &str, &str, &str)]) -> Value {
+        let entries: Vec<Value> = changes
+            .iter()
+            .map(|(asset_id, price, size, side)| json!({ "asset_id": asset_id, "price": price, "size": size, "side": side }))
+            .collect();
+        json!({
+            "event_type": "price_change",
+            "market": market,
+            "price_changes": entries,
+            "timestamp": now_ms(),
+        })
+    }
+
+    pub fn last_trade_price(asset_id: &str, price: &str) -> Value {
+        json!({
+            "event_type": "last_trade_price",
+            "asset_id": asset_id,
+            "market": "mock",
+            "price": price,
+            "timestamp": now_ms(),
+        })
+    }
+
+    pub fn tick_size_change(asset_id: &str, old_tick_size: &str, new_tick_size: &str) -> Value {
+        json!({
+            "event_type": "tick_size_change",
+            "asset_id": asset_id,
+            "market": "mock",
+            "old_tick_size": old_tick_size,
+            "new_tick_size": new_tick_size,
+            "timestamp": now_ms(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::connect_async;
+
+    #[tokio::test]
+    async fn subscribe_gets_snapshots_and_only_subscribed_events() {
+        let server = MockServer::start(MockConfig::default()).await.unwrap();
+        server.set_book("a1", &[("0.55", "10")], &[("0.50", "5")]);
+        let (mut ws, _) = connect_async(server.url()).await.unwrap();
+
+        ws.send(Message::Text(r#"{"assets_ids":["a1","a2"],"type":"market"}"#.to_string())).await.unwrap();
+        let Some(Ok(Message::Text(snapshot))) = ws.next().await else { panic!("expected snapshot") };
+        let snapshot: Value = serde_json::from_str(&snapshot).unwrap();
+        assert_eq!(snapshot[0]["event_type"], "book");
+        assert_eq!(snapshot[0]["asset_id"], "a1");
+        assert!(server.wait_for_subscribed(&["a1", "a2"], Duration::from_secs(1)).await);
+
+        assert_eq!(server.send(&frames::last_trade_price("other", "0.5")), 0);
+        assert_eq!(server.send(&frames::price_change("m", &[("a2", "0.4", "1", "BUY")])), 1);
+        let Some(Ok(Message::Text(change))) = ws.next().await else { panic!("expected price_change") };
+        assert!(change.contains("price_change"));
+
+        server.disconnect_all(1001, "restart");
+        match ws.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => assert_eq!(u16::from(frame.code), 1001),
+            other => panic!("expected close, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! `mock-ws [--port N] [--script FILE] [--loop] [--queue N] [--no-snapshots]`
+//!
+//! Serves the mock market channel on `127.0.0.1:N` (default 8765). `--script`
+//! runs a JSON-lines `Step` file once the server is up (blank lines and lines
+//! starting with `#` are skipped), repeating it with `--loop`. `book` frames
+//! in the script also become the snapshots sent on subscribe.
+
+use std::net::SocketAddr;
+
+use polymarket_mock_ws::{MockConfig, MockServer, Step};
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string())))
+        .init();
+    if let Err(e) = run().await {
+        eprintln!("mock-ws: {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> Result<(), String> {
+    let mut port = 8765u16;
+    let mut script = None;
+    let mut repeat = false;
+    let mut config = MockConfig::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => port = args.next().and_then(|v| v.parse().ok()).ok_or("--port needs a port number")?,
+            "--script" => script = Some(args.next().ok_or("--script needs a file")?),
+            "--loop" => repeat = true,
+            "--queue" => {
+                config.client_queue = args.next().and_then(|v| v.parse().ok()).ok_or("--queue needs a frame count")?
+            }
+            "--no-snapshots" => config.snapshot_on_subscribe = false,
+            other => return Err(format!("unrecognised argument '{other}'")),
+        }
+    }
+
+    let steps = match &script {
+        Some(path) => parse_script(&std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?)?,
+        None => Vec::new(),
+    };
+    let server = MockServer::start_on(SocketAddr::from(([127, 0, 0, 1], port)), config)
+        .await
+        .map_err(|e| format!("bind 127.0.0.1:{port}: {e}"))?;
+    for step in &steps {
+        if let Step::Send(frame) = step {
+            server.register_books(frame);
+        }
+    }
+    info!("[MOCK WS] listening on {} ({} script step(s))", server.url(), steps.len());
+
+    loop {
+        server.run_script(&steps).await;
+        if !repeat || steps.is_empty() {
+            break;
+        }
+    }
+    // Keep serving subscriptions after the script ends.
+    std::future::pending::<()>().await;
+    Ok(())
+}
+
+fn parse_script(text: &str) -> Result<Vec<Step>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("script line {}: {e}", i + 1)))
+        .collect()
+}
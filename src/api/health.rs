@@ -1,17 +1,157 @@
 //! Shared health state for the /health endpoint.
-//! Updated by WsManager, window_consumer, and DbWriter.
+//! Updated by WsManager, the detector shards, window_consumer, and DbWriter.
 
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+
+use crate::config::WS_HISTORY_LEN;
+use crate::ws::backoff::BreakerState;
+
+/// Counters for one detector shard, shared by its router link and its task.
+#[derive(Debug, Default)]
+pub struct DetectorShardHealth {
+    pub index: usize,
+    /// Price and trade messages the shard has handled.
+    pub msgs_processed: AtomicU64,
+    /// Messages routed to the shard but not yet handled.
+    pub queue_depth: AtomicU64,
+    /// Messages dropped because the shard's channel was full.
+    pub dropped: AtomicU64,
+    /// Messages handled during the last full second.
+    pub msgs_per_sec: AtomicU64,
+}
+
+impl DetectorShardHealth {
+    pub fn new(index: usize) -> Self {
+        Self { index, ..Self::default() }
+    }
+}
+
+/// One connection attempt of a pooled WS connection, for `/health/ws`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectionAttempt {
+    pub started_at_ns: u64,
+    /// When the socket came up (None = the connect itself failed).
+    pub connected_at_ns: Option<u64>,
+    /// Time from connect to close, or from start to failure if it never connected.
+    pub duration_ms: u64,
+    /// Close frame sent by the server, if any.
+    pub close_code: Option<u16>,
+    pub close_reason: Option<String>,
+    /// Error that ended the attempt; None for a close or a clean shutdown.
+    pub error: Option<String>,
+}
+
+/// Health of one pooled market-channel WebSocket connection.
+#[derive(Debug, Default)]
+pub struct WsConnectionHealth {
+    pub index: usize,
+    /// True when the socket is connected and in its main loop.
+    pub connected: AtomicBool,
+    /// Asset ids in this connection's partition.
+    pub subscribed_assets: AtomicU64,
+    /// Frames received on this connection since process start.
+    pub frames_received: AtomicU64,
+    /// Reconnect attempts after the first connect.
+    pub reconnects: AtomicU64,
+    /// Nanosecond timestamp of the last frame (0 = none).
+    pub last_frame_at_ns: AtomicU64,
+    /// When the current outage began (0 = connected, or never connected yet).
+    pub disconnected_since_ns: AtomicU64,
+    /// Total time spent disconnected, excluding the current outage.
+    pub disconnected_ns_total: AtomicU64,
+    /// Length of the most recent completed outage.
+    pub last_outage_ns: AtomicU64,
+    /// Markets whose books were invalidated by this connection's disconnects.
+    pub books_invalidated: AtomicU64,
+    /// Failed sessions in a row; reset by one that stays up.
+    pub consecutive_failures: AtomicU64,
+    /// `BreakerState` as u8.
+    pub breaker: AtomicU8,
+    /// Times the circuit breaker has opened.
+    pub breaker_opens: AtomicU64,
+    /// The last `WS_HISTORY_LEN` attempts, oldest first.
+    pub history: Mutex<VecDeque<ConnectionAttempt>>,
+}
+
+impl WsConnectionHealth {
+    pub fn new(index: usize) -> Self {
+        Self { index, ..Self::default() }
+    }
+
+    pub fn set_connected(&self, v: bool) {
+        self.connected.store(v, Ordering::Relaxed);
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Starts an outage at `now_ns` unless one is already running. True if newly started.
+    pub fn mark_disconnected(&self, now_ns: u64) -> bool {
+        self.disconnected_since_ns
+            .compare_exchange(0, now_ns.max(1), Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    pub fn breaker(&self) -> BreakerState {
+        BreakerState::from_u8(self.breaker.load(Ordering::Relaxed))
+    }
+
+    pub fn set_breaker(&self, state: BreakerState) {
+        self.breaker.store(state as u8, Ordering::Relaxed);
+    }
+
+    /// Appends a finished attempt, dropping the oldest beyond `WS_HISTORY_LEN`.
+    pub fn record_attempt(&self, attempt: ConnectionAttempt) {
+        let Ok(mut history) = self.history.lock() else {
+            return;
+        };
+        if history.len() >= WS_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(attempt);
+    }
+
+    pub fn history(&self) -> Vec<ConnectionAttempt> {
+        self.history.lock().map(|h| h.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Ends the running outage, if any, and returns its length.
+    pub fn mark_reconnected(&self, now_ns: u64) -> Option<u64> {
+        let since = self.disconnected_since_ns.swap(0, Ordering::Relaxed);
+        if since == 0 {
+            return None;
+        }
+        let gap = now_ns.saturating_sub(since);
+        self.disconnected_ns_total.fetch_add(gap, Ordering::Relaxed);
+        self.last_outage_ns.store(gap, Ordering::Relaxed);
+        Some(gap)
+    }
+}
 
 /// Shared health metrics. Updated by scanner components, read by API.
 #[derive(Default)]
 pub struct HealthState {
-    /// True when WS is connected and in the main loop.
-    pub ws_connected: AtomicBool,
+    /// One entry per pooled WS connection, in connection order.
+    pub ws_connections: RwLock<Vec<Arc<WsConnectionHealth>>>,
     /// Nanosecond timestamp of last window close event (0 = none).
     pub last_window_at_ns: AtomicU64,
     /// Approximate count of window close events queued for DB write.
     pub write_queue_pending: AtomicU64,
+    /// Tokens flagged silent (open window, no update) on the last silence sweep.
+    pub silent_tokens: AtomicU64,
+    /// Tokens still without a `book` snapshot past the silence threshold on the last sweep.
+    pub unsnapshotted_tokens: AtomicU64,
+    /// Markets currently marked stale.
+    pub stale_markets: AtomicU64,
+    /// Automatic resubscribes sent since start.
+    pub resubscribes: AtomicU64,
+    /// One entry per spread detector shard, in shard order.
+    pub detector_shards: RwLock<Vec<Arc<DetectorShardHealth>>>,
 }
 
 impl HealthState {
@@ -19,8 +159,12 @@ impl HealthState {
         Self::default()
     }
 
-    pub fn set_ws_connected(&self, v: bool) {
-        self.ws_connected.store(v, Ordering::Relaxed);
+    pub fn add_ws_connection(&self, conn: Arc<WsConnectionHealth>) {
+        self.ws_connections.write().unwrap().push(conn);
+    }
+
+    pub fn ws_connections(&self) -> Vec<Arc<WsConnectionHealth>> {
+        self.ws_connections.read().unwrap().clone()
     }
 
     pub fn set_last_window_at_ns(&self, ns: u64) {
@@ -35,8 +179,10 @@ impl HealthState {
         self.write_queue_pending.fetch_sub(1, Ordering::Relaxed);
     }
 
+    /// True when the pool has connections and every one of them is connected.
     pub fn ws_connected(&self) -> bool {
-        self.ws_connected.load(Ordering::Relaxed)
+        let conns = self.ws_connections.read().unwrap();
+        !conns.is_empty() && conns.iter().all(|c| c.is_connected())
     }
 
     pub fn last_window_at_ns(&self) -> u64 {
@@ -46,4 +192,12 @@ impl HealthState {
     pub fn write_queue_pending(&self) -> u64 {
         self.write_queue_pending.load(Ordering::Relaxed)
     }
+
+    pub fn add_detector_shard(&self, shard: Arc<DetectorShardHealth>) {
+        self.detector_shards.write().unwrap().push(shard);
+    }
+
+    pub fn detector_shards(&self) -> Vec<Arc<DetectorShardHealth>> {
+        self.detector_shards.read().unwrap().clone()
+    }
 }
@@ -1,9 +1,14 @@
-//! In-memory latency histogram for detection pipeline instrumentation.
-//! Records time from WS receive to spread computation in the detector.
+//! In-memory latency histograms for pipeline instrumentation.
+//! `LatencyStats` records WS receive → spread computation in the detector;
+//! `FeedLatency` records exchange timestamp → WS receive; `ParseLatency` records
+//! the time spent parsing each frame.
 
 use std::sync::Mutex;
 use std::time::Duration;
 
+/// Length of each min-delta bucket used for the clock-offset estimate.
+const OFFSET_WINDOW_NS: u64 = 300 * 1_000_000_000;
+
 /// Shared latency stats. Detector records, API reads.
 /// Values stored in microseconds.
 pub struct LatencyStats {
@@ -38,7 +43,7 @@ impl LatencyStats {
         let Ok(h) = self.inner.lock() else {
             return (None, None, None);
         };
-        if h.len() == 0 {
+        if h.is_empty() {
             return (None, None, None);
         }
         let p50 = h.value_at_quantile(0.5);
@@ -58,3 +63,190 @@ impl Default for LatencyStats {
         Self::new()
     }
 }
+
+/// Exchange → local receive latency, from the `timestamp` on feed messages.
+///
+/// Exchange timestamps come from a clock we don't control, so the raw delta is
+/// network latency plus clock offset. The offset is estimated as the smallest
+/// delta seen over the current and previous `OFFSET_WINDOW_NS` buckets (the fastest
+/// messages approximate zero queueing); the corrected latency is delta − offset.
+/// Values stored in microseconds.
+pub struct FeedLatency {
+    inner: Mutex<FeedLatencyInner>,
+}
+
+struct FeedLatencyInner {
+    /// Raw receive − exchange deltas; negative deltas are only counted.
+    raw: hdrhistogram::Histogram<u64>,
+    /// Deltas less the offset estimate at the time they were recorded.
+    corrected: hdrhistogram::Histogram<u64>,
+    negative_samples: u64,
+    bucket_started_ns: u64,
+    bucket_min_us: Option<i64>,
+    prev_bucket_min_us: Option<i64>,
+}
+
+impl FeedLatencyInner {
+    fn offset_us(&self) -> Option<i64> {
+        match (self.bucket_min_us, self.prev_bucket_min_us) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Snapshot of `FeedLatency` for the API.
+#[derive(Debug, Clone, Default)]
+pub struct FeedLatencySummary {
+    pub sample_count: u64,
+    pub negative_samples: u64,
+    /// Estimated local − exchange clock offset plus floor latency.
+    pub clock_offset_us: Option<i64>,
+    pub raw_p50_us: Option<u64>,
+    pub raw_p99_us: Option<u64>,
+    pub p50_us: Option<u64>,
+    pub p95_us: Option<u64>,
+    pub p99_us: Option<u64>,
+}
+
+impl FeedLatency {
+    /// Tracks 1us to 100s, 3 significant figures.
+    pub fn new() -> Self {
+        let histogram = || {
+            hdrhistogram::Histogram::new_with_bounds(1, 100_000_000, 3).expect("valid histogram bounds")
+        };
+        Self {
+            inner: Mutex::new(FeedLatencyInner {
+                raw: histogram(),
+                corrected: histogram(),
+                negative_samples: 0,
+                bucket_started_ns: 0,
+                bucket_min_us: None,
+                prev_bucket_min_us: None,
+            }),
+        }
+    }
+
+    /// Records one message received at `received_at_ns` carrying exchange time `exchange_ts_ms`.
+    pub fn record(&self, received_at_ns: u64, exchange_ts_ms: u64) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let delta_us = (received_at_ns / 1_000) as i64 - (exchange_ts_ms as i64) * 1_000;
+
+        if received_at_ns.saturating_sub(inner.bucket_started_ns) >= OFFSET_WINDOW_NS {
+            inner.prev_bucket_min_us = inner.bucket_min_us.take();
+            inner.bucket_started_ns = received_at_ns;
+        }
+        inner.bucket_min_us = Some(inner.bucket_min_us.map_or(delta_us, |m| m.min(delta_us)));
+
+        if delta_us >= 0 {
+            let _ = inner.raw.record(delta_us.max(1) as u64);
+        } else {
+            inner.negative_samples += 1;
+        }
+        let offset = inner.offset_us().unwrap_or(delta_us);
+        let _ = inner.corrected.record((delta_us - offset).max(1) as u64);
+    }
+
+    pub fn summary(&self) -> FeedLatencySummary {
+        let Ok(inner) = self.inner.lock() else {
+            return FeedLatencySummary::default();
+        };
+        let q = |h: &hdrhistogram::Histogram<u64>, quantile: f64| (!h.is_empty()).then(|| h.value_at_quantile(quantile));
+        FeedLatencySummary {
+            sample_count: inner.corrected.len(),
+            negative_samples: inner.negative_samples,
+            clock_offset_us: inner.offset_us(),
+            raw_p50_us: q(&inner.raw, 0.5),
+            raw_p99_us: q(&inner.raw, 0.99),
+            p50_us: q(&inner.corrected, 0.5),
+            p95_us: q(&inner.corrected, 0.95),
+            p99_us: q(&inner.corrected, 0.99),
+        }
+    }
+}
+
+impl Default for FeedLatency {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Time spent in `parse_ws_frame` per frame. Values stored in nanoseconds: small
+/// frames parse in a few microseconds, too close to a microsecond histogram's floor.
+pub struct ParseLatency {
+    inner: Mutex<hdrhistogram::Histogram<u64>>,
+}
+
+/// Snapshot of `ParseLatency` for the API.
+#[derive(Debug, Clone, Default)]
+pub struct ParseLatencySummary {
+    pub sample_count: u64,
+    pub p50_ns: Option<u64>,
+    pub p95_ns: Option<u64>,
+    pub p99_ns: Option<u64>,
+    pub max_ns: Option<u64>,
+}
+
+impl ParseLatency {
+    /// Tracks 1ns to 10s, 3 significant figures.
+    pub fn new() -> Self {
+        let histogram = hdrhistogram::Histogram::new_with_bounds(1, 10_000_000_000, 3)
+            .expect("valid histogram bounds");
+        Self {
+            inner: Mutex::new(histogram),
+        }
+    }
+
+    pub fn record(&self, d: Duration) {
+        let ns = d.as_nanos().min(u128::from(u64::MAX)) as u64;
+        if let Ok(mut h) = self.inner.lock() {
+            let _ = h.record(ns.max(1));
+        }
+    }
+
+    pub fn summary(&self) -> ParseLatencySummary {
+        let Ok(h) = self.inner.lock() else {
+            return ParseLatencySummary::default();
+        };
+        let q = |quantile: f64| (!h.is_empty()).then(|| h.value_at_quantile(quantile));
+        ParseLatencySummary {
+            sample_count: h.len(),
+            p50_ns: q(0.5),
+            p95_ns: q(0.95),
+            p99_ns: q(0.99),
+            max_ns: (!h.is_empty()).then(|| h.max()),
+        }
+    }
+}
+
+impl Default for ParseLatency {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_latency_subtracts_estimated_clock_offset() {
+        let feed = FeedLatency::new();
+        let exchange_ms = 1_700_000_000_000u64;
+        // Local clock 2s behind the exchange: raw deltas are negative, but the
+        // offset estimate (min delta) recovers the relative latency.
+        for extra_ms in [5u64, 10, 15, 20, 50] {
+            let received_ns = (exchange_ms - 2_000 + extra_ms) * 1_000_000;
+            feed.record(received_ns, exchange_ms);
+        }
+        let summary = feed.summary();
+        assert_eq!(summary.sample_count, 5);
+        assert_eq!(summary.negative_samples, 5);
+        assert_eq!(summary.clock_offset_us, Some(-1_995_000));
+        assert!(summary.raw_p50_us.is_none());
+        let p99 = summary.p99_us.unwrap();
+        assert!((44_000..=46_000).contains(&p99), "p99 {p99}");
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use axum::{
@@ -13,15 +14,18 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 
 use crate::api::health::HealthState;
-use crate::api::latency::LatencyStats;
+use crate::api::latency::{FeedLatency, LatencyStats, ParseLatency};
+use crate::detector::depth::{paired_levels, PairedLevel};
 use crate::error::AppError;
 use crate::state::MarketStore;
-use crate::types::WindowEvent;
+use crate::types::{Price, WindowEvent};
 
 #[derive(Clone)]
 pub struct ApiState {
     pub pool: sqlx::SqlitePool,
     pub latency_stats: Arc<LatencyStats>,
+    pub feed_latency: Arc<FeedLatency>,
+    pub parse_latency: Arc<ParseLatency>,
     pub health: Arc<HealthState>,
     pub store: Arc<MarketStore>,
     pub window_broadcast_tx: broadcast::Sender<WindowEvent>,
@@ -31,11 +35,16 @@ pub fn router(state: ApiState) -> Router {
     Router::new()
         .route("/markets", get(get_markets))
         .route("/markets/:id/windows", get(get_market_windows))
+        .route("/markets/:id/book", get(get_market_book))
+        .route("/markets/:id/book/snapshots", get(get_book_snapshots))
         .route("/windows/recent", get(get_recent_windows))
         .route("/windows/open", get(get_open_windows))
+        .route("/windows/top", get(get_top_windows))
+        .route("/windows/:id/ticks", get(get_window_ticks))
         .route("/stats/summary", get(get_stats_summary))
         .route("/stats/latency", get(get_stats_latency))
         .route("/health", get(get_health))
+        .route("/health/ws", get(get_health_ws))
         .route("/ws/events", get(ws_events_handler))
         .with_state(state)
 }
@@ -56,12 +65,30 @@ pub struct MarketWindowsQuery {
     pub since: Option<i64>,
 }
 
+#[derive(Deserialize)]
+pub struct BookQuery {
+    pub depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct BookSnapshotsQuery {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Deserialize)]
 pub struct RecentWindowsQuery {
     pub min_spread: Option<f64>,
     pub limit: Option<i64>,
 }
 
+#[derive(Deserialize)]
+pub struct TopWindowsQuery {
+    pub since: Option<i64>,
+    pub limit: Option<i64>,
+}
+
 // ---------------------------------------------------------------------------
 // Response types
 // ---------------------------------------------------------------------------
@@ -78,6 +105,15 @@ pub struct MarketResponse {
     pub avg_spread_size: Option<f64>,
     pub noise_ratio: Option<f64>,
     pub opportunity_score: Option<f64>,
+    /// 24h average paired book depth when windows opened.
+    pub avg_open_book_depth: Option<f64>,
+    /// Live books as of the last scoring pass: paired depth within the widest
+    /// band per side, YES-leg imbalance, level count and updates/s over both tokens.
+    pub book_ask_depth: Option<f64>,
+    pub book_bid_depth: Option<f64>,
+    pub book_imbalance: Option<f64>,
+    pub book_levels: Option<i64>,
+    pub book_update_rate: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -93,6 +129,78 @@ pub struct WindowResponse {
     pub close_reason: Option<String>,
     pub opportunity_class: Option<i64>,
     pub detection_latency_us: Option<i64>,
+    /// Largest profitable paired size (shares) from walking both ask ladders.
+    pub executable_size: Option<f64>,
+    /// VWAP combined cost per pair for `executable_size`.
+    pub executable_cost: Option<f64>,
+    /// Expected dollar profit for `executable_size`.
+    pub expected_profit: Option<f64>,
+    /// Spread after taker fees and fixed costs.
+    pub net_spread: Option<f64>,
+    /// `expected_profit` after taker fees and fixed costs.
+    pub net_profit: Option<f64>,
+    /// `binary`, `neg_risk_yes` or `neg_risk_no`.
+    pub window_kind: String,
+    /// `buy` (asks sum below $1) or `sell` (bids sum above $1).
+    pub side: String,
+    /// Confirmation rule the window was held to (ticks and elapsed ms) and where it came from.
+    pub confirm_min_ticks: Option<i64>,
+    pub confirm_min_elapsed_ms: Option<i64>,
+    pub confirm_rule_source: Option<String>,
+    /// Classifier (`name@vN`) that produced `open_duration_class` / `close_reason` / `opportunity_class`.
+    pub classifier_version: Option<String>,
+    /// Widest spread on any arb tick, and when it was seen (ns).
+    pub peak_spread: Option<f64>,
+    pub peak_spread_at: Option<i64>,
+    /// Spread on the last arb tick before close.
+    pub closing_spread: Option<f64>,
+    /// Time-weighted average spread over the window.
+    pub twa_spread: Option<f64>,
+    /// Per-leg ask range over the window's arb ticks.
+    pub min_yes_ask: Option<f64>,
+    pub max_yes_ask: Option<f64>,
+    pub min_no_ask: Option<f64>,
+    pub max_no_ask: Option<f64>,
+    /// Exchange (server) timestamps, in ms, of the messages that opened and closed the window.
+    pub exchange_opened_at: Option<i64>,
+    pub exchange_closed_at: Option<i64>,
+    /// Paired book depth on the window's side at the first arb tick and at close (binary windows only).
+    pub open_book_depth: Option<f64>,
+    pub close_book_depth: Option<f64>,
+}
+
+/// One entry of a window's tick trace (`window_ticks`).
+#[derive(Serialize)]
+pub struct WindowTickResponse {
+    pub seq: i64,
+    pub at_ns: i64,
+    /// `price` or `trade`.
+    pub kind: String,
+    pub yes_ask: f64,
+    pub no_ask: f64,
+    pub yes_bid: f64,
+    pub no_bid: f64,
+    pub yes_ask_size: Option<f64>,
+    pub no_ask_size: Option<f64>,
+    pub yes_bid_size: Option<f64>,
+    pub no_bid_size: Option<f64>,
+    pub spread: f64,
+    pub trade_asset_id: Option<String>,
+    pub trade_price: Option<f64>,
+}
+
+/// One token's archived book (`book_snapshots`).
+#[derive(Serialize)]
+pub struct BookSnapshotResponse {
+    pub asset_id: String,
+    pub taken_at: i64,
+    /// `periodic` or `window_open`.
+    pub reason: String,
+    pub best_ask: Option<f64>,
+    pub best_bid: Option<f64>,
+    /// `[price, size]` pairs, best first.
+    pub asks: serde_json::Value,
+    pub bids: serde_json::Value,
 }
 
 #[derive(Serialize)]
@@ -118,7 +226,8 @@ async fn get_markets(
         SELECT m.id, m.question, m.category,
                ms.windows_24h, ms.p1_windows_24h, ms.p2_windows_24h,
                ms.avg_window_duration_ms, ms.avg_spread_size,
-               ms.noise_ratio, ms.opportunity_score
+               ms.noise_ratio, ms.opportunity_score, ms.avg_open_book_depth,
+               ms.book_ask_depth, ms.book_bid_depth, ms.book_imbalance, ms.book_levels, ms.book_update_rate
         FROM markets m
         LEFT JOIN market_stats ms ON m.id = ms.market_id
         WHERE ms.opportunity_score IS NULL OR ms.opportunity_score >= ?
@@ -132,8 +241,8 @@ async fn get_markets(
     let markets: Vec<MarketResponse> = rows
         .into_iter()
         .filter(|r| {
-            params.category.as_ref().map_or(true, |c| {
-                r.category.as_deref().map_or(false, |cat| cat == c.as_str())
+            params.category.as_ref().is_none_or(|c| {
+                r.category.as_deref() == Some(c.as_str())
             })
         })
         .map(|r| MarketResponse {
@@ -147,6 +256,12 @@ async fn get_markets(
             avg_spread_size: r.avg_spread_size,
             noise_ratio: r.noise_ratio,
             opportunity_score: r.opportunity_score,
+            avg_open_book_depth: r.avg_open_book_depth,
+            book_ask_depth: r.book_ask_depth,
+            book_bid_depth: r.book_bid_depth,
+            book_imbalance: r.book_imbalance,
+            book_levels: r.book_levels,
+            book_update_rate: r.book_update_rate,
         })
         .collect();
 
@@ -165,7 +280,12 @@ async fn get_market_windows(
         r#"
         SELECT id, market_id, opened_at, closed_at, duration_ms,
                spread_size, spread_category, open_duration_class, close_reason, opportunity_class,
-               detection_latency_us
+               detection_latency_us, executable_size, executable_cost, expected_profit,
+               net_spread, net_profit, window_kind, side,
+               confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, classifier_version,
+               peak_spread, peak_spread_at, closing_spread, twa_spread,
+               min_yes_ask, max_yes_ask, min_no_ask, max_no_ask,
+               exchange_opened_at, exchange_closed_at, open_book_depth, close_book_depth
         FROM windows
         WHERE market_id = ? AND opened_at > ?
         ORDER BY opened_at DESC
@@ -192,12 +312,163 @@ async fn get_market_windows(
             close_reason: r.close_reason,
             opportunity_class: r.opportunity_class,
             detection_latency_us: r.detection_latency_us,
+            executable_size: r.executable_size,
+            executable_cost: r.executable_cost,
+            expected_profit: r.expected_profit,
+            net_spread: r.net_spread,
+            net_profit: r.net_profit,
+            window_kind: r.window_kind,
+            side: r.side,
+            confirm_min_ticks: r.confirm_min_ticks,
+            confirm_min_elapsed_ms: r.confirm_min_elapsed_ms,
+            confirm_rule_source: r.confirm_rule_source,
+            classifier_version: r.classifier_version,
+            peak_spread: r.peak_spread,
+            peak_spread_at: r.peak_spread_at,
+            closing_spread: r.closing_spread,
+            twa_spread: r.twa_spread,
+            min_yes_ask: r.min_yes_ask,
+            max_yes_ask: r.max_yes_ask,
+            min_no_ask: r.min_no_ask,
+            max_no_ask: r.max_no_ask,
+            exchange_opened_at: r.exchange_opened_at,
+            exchange_closed_at: r.exchange_closed_at,
+            open_book_depth: r.open_book_depth,
+            close_book_depth: r.close_book_depth,
         })
         .collect();
 
     Ok(Json(windows))
 }
 
+/// Live order books of a market's two tokens, straight from `MarketStore`, plus
+/// the arb ladder: both books paired level by level, as the depth walk sees them.
+async fn get_market_book(
+    State(state): State<ApiState>,
+    Path(market_id): Path<String>,
+    Query(params): Query<BookQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let depth = params.depth.unwrap_or(10).max(1);
+    let (yes_token, no_token) = state
+        .store
+        .market_tokens(&market_id)
+        .ok_or_else(|| AppError::NotFound(format!("market {market_id} is not subscribed")))?;
+    let (yes_asks, yes_bids) = state.store.book_depth(&yes_token, depth).unwrap_or_default();
+    let (no_asks, no_bids) = state.store.book_depth(&no_token, depth).unwrap_or_default();
+
+    let levels = |levels: &[(Price, f64)]| -> Vec<serde_json::Value> {
+        levels.iter().map(|&(price, size)| serde_json::json!({ "price": price, "size": size })).collect()
+    };
+    let token = |asset_id: &str, asks: &[(Price, f64)], bids: &[(Price, f64)]| {
+        let best = state.store.best_prices(asset_id);
+        let activity = state.store.token_activity(asset_id);
+        let nonzero = |ns: u64| (ns != 0).then_some(ns);
+        serde_json::json!({
+            "asset_id": asset_id,
+            "best_ask": best.map(|(ask, _)| ask),
+            "best_bid": best.map(|(_, bid)| bid),
+            "last_update_ns": activity.and_then(|a| nonzero(a.last_update_ns)),
+            "last_snapshot_ns": activity.and_then(|a| nonzero(a.last_snapshot_ns)),
+            "corrupt": state.store.is_book_corrupt(asset_id),
+            "integrity": state.store.book_integrity(asset_id).map(|i| serde_json::json!({
+                "checks": i.checks,
+                "divergences": i.divergences,
+                "hash_mismatches": i.hash_mismatches,
+            })),
+            "metrics": state.store.book_metrics(asset_id),
+            "asks": levels(asks),
+            "bids": levels(bids),
+        })
+    };
+
+    // A ladder of `depth` levels per side pairs into at most `depth` steps
+    // without running past the levels fetched.
+    let buy = arb_ladder(paired_levels(&yes_asks, &no_asks).take(depth), |combined| Price::ONE - combined);
+    let sell = arb_ladder(paired_levels(&yes_bids, &no_bids).take(depth), |combined| combined - Price::ONE);
+
+    Ok(Json(serde_json::json!({
+        "market_id": market_id,
+        "depth": depth,
+        "tick_size": state.store.tick_size(&market_id),
+        "hydrated": state.store.is_hydrated(&market_id),
+        "stale": state.store.is_stale(&market_id),
+        "interrupted": state.store.is_interrupted(&market_id),
+        "yes": token(&yes_token, &yes_asks, &yes_bids),
+        "no": token(&no_token, &no_asks, &no_bids),
+        "arb_ladder": { "buy": buy, "sell": sell },
+    })))
+}
+
+/// Cumulative paired size against combined price, one row per paired level.
+/// `edge` is the per-pair profit at a combined price (negative past the arb).
+fn arb_ladder(levels: impl Iterator<Item = PairedLevel>, edge: impl Fn(Price) -> Price) -> Vec<serde_json::Value> {
+    let mut cumulative_size = 0.0;
+    let mut cumulative_cost = 0.0;
+    let mut cumulative_profit = 0.0;
+    levels
+        .map(|level| {
+            let combined = level.combined();
+            cumulative_size += level.size;
+            cumulative_cost += level.size * combined.to_f64();
+            cumulative_profit += level.size * edge(combined).to_f64();
+            serde_json::json!({
+                "yes_price": level.yes_price,
+                "no_price": level.no_price,
+                "combined": combined,
+                "edge": edge(combined),
+                "size": level.size,
+                "cumulative_size": cumulative_size,
+                "vwap_combined": cumulative_cost / cumulative_size,
+                "cumulative_profit": cumulative_profit,
+            })
+        })
+        .collect()
+}
+
+/// Archived book snapshots of a market's tokens in `[since, until]` (ns), oldest first.
+async fn get_book_snapshots(
+    State(state): State<ApiState>,
+    Path(market_id): Path<String>,
+    Query(params): Query<BookSnapshotsQuery>,
+) -> Result<Json<Vec<BookSnapshotResponse>>, AppError> {
+    let since = params.since.unwrap_or(0);
+    let until = params.until.unwrap_or(i64::MAX);
+    let limit = params.limit.unwrap_or(1000);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT asset_id, taken_at, reason, best_ask, best_bid, asks, bids
+        FROM book_snapshots
+        WHERE market_id = ? AND taken_at >= ? AND taken_at <= ?
+        ORDER BY taken_at, id
+        LIMIT ?
+        "#,
+        market_id,
+        since,
+        until,
+        limit
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let snapshots = rows
+        .into_iter()
+        .map(|r| {
+            Ok(BookSnapshotResponse {
+                asset_id: r.asset_id,
+                taken_at: r.taken_at,
+                reason: r.reason,
+                best_ask: r.best_ask,
+                best_bid: r.best_bid,
+                asks: serde_json::from_str(&r.asks)?,
+                bids: serde_json::from_str(&r.bids)?,
+            })
+        })
+        .collect::<Result<_, AppError>>()?;
+
+    Ok(Json(snapshots))
+}
+
 async fn get_recent_windows(
     State(state): State<ApiState>,
     Query(params): Query<RecentWindowsQuery>,
@@ -209,7 +480,12 @@ async fn get_recent_windows(
         r#"
         SELECT id, market_id, opened_at, closed_at, duration_ms,
                spread_size, spread_category, open_duration_class, close_reason, opportunity_class,
-               detection_latency_us
+               detection_latency_us, executable_size, executable_cost, expected_profit,
+               net_spread, net_profit, window_kind, side,
+               confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, classifier_version,
+               peak_spread, peak_spread_at, closing_spread, twa_spread,
+               min_yes_ask, max_yes_ask, min_no_ask, max_no_ask,
+               exchange_opened_at, exchange_closed_at, open_book_depth, close_book_depth
         FROM windows
         WHERE spread_size >= ?
         ORDER BY opened_at DESC
@@ -235,6 +511,29 @@ async fn get_recent_windows(
             close_reason: r.close_reason,
             opportunity_class: r.opportunity_class,
             detection_latency_us: r.detection_latency_us,
+            executable_size: r.executable_size,
+            executable_cost: r.executable_cost,
+            expected_profit: r.expected_profit,
+            net_spread: r.net_spread,
+            net_profit: r.net_profit,
+            window_kind: r.window_kind,
+            side: r.side,
+            confirm_min_ticks: r.confirm_min_ticks,
+            confirm_min_elapsed_ms: r.confirm_min_elapsed_ms,
+            confirm_rule_source: r.confirm_rule_source,
+            classifier_version: r.classifier_version,
+            peak_spread: r.peak_spread,
+            peak_spread_at: r.peak_spread_at,
+            closing_spread: r.closing_spread,
+            twa_spread: r.twa_spread,
+            min_yes_ask: r.min_yes_ask,
+            max_yes_ask: r.max_yes_ask,
+            min_no_ask: r.min_no_ask,
+            max_no_ask: r.max_no_ask,
+            exchange_opened_at: r.exchange_opened_at,
+            exchange_closed_at: r.exchange_closed_at,
+            open_book_depth: r.open_book_depth,
+            close_book_depth: r.close_book_depth,
         })
         .collect();
 
@@ -246,7 +545,12 @@ async fn get_open_windows(State(state): State<ApiState>) -> Result<Json<Vec<Wind
         r#"
         SELECT id, market_id, opened_at, closed_at, duration_ms,
                spread_size, spread_category, open_duration_class, close_reason, opportunity_class,
-               detection_latency_us
+               detection_latency_us, executable_size, executable_cost, expected_profit,
+               net_spread, net_profit, window_kind, side,
+               confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, classifier_version,
+               peak_spread, peak_spread_at, closing_spread, twa_spread,
+               min_yes_ask, max_yes_ask, min_no_ask, max_no_ask,
+               exchange_opened_at, exchange_closed_at, open_book_depth, close_book_depth
         FROM windows
         WHERE closed_at IS NULL
         ORDER BY opened_at DESC
@@ -269,12 +573,148 @@ async fn get_open_windows(State(state): State<ApiState>) -> Result<Json<Vec<Wind
             close_reason: r.close_reason,
             opportunity_class: r.opportunity_class,
             detection_latency_us: r.detection_latency_us,
+            executable_size: r.executable_size,
+            executable_cost: r.executable_cost,
+            expected_profit: r.expected_profit,
+            net_spread: r.net_spread,
+            net_profit: r.net_profit,
+            window_kind: r.window_kind,
+            side: r.side,
+            confirm_min_ticks: r.confirm_min_ticks,
+            confirm_min_elapsed_ms: r.confirm_min_elapsed_ms,
+            confirm_rule_source: r.confirm_rule_source,
+            classifier_version: r.classifier_version,
+            peak_spread: r.peak_spread,
+            peak_spread_at: r.peak_spread_at,
+            closing_spread: r.closing_spread,
+            twa_spread: r.twa_spread,
+            min_yes_ask: r.min_yes_ask,
+            max_yes_ask: r.max_yes_ask,
+            min_no_ask: r.min_no_ask,
+            max_no_ask: r.max_no_ask,
+            exchange_opened_at: r.exchange_opened_at,
+            exchange_closed_at: r.exchange_closed_at,
+            open_book_depth: r.open_book_depth,
+            close_book_depth: r.close_book_depth,
         })
         .collect();
 
     Ok(Json(windows))
 }
 
+/// Windows ranked by expected dollar profit (not cents per share).
+async fn get_top_windows(
+    State(state): State<ApiState>,
+    Query(params): Query<TopWindowsQuery>,
+) -> Result<Json<Vec<WindowResponse>>, AppError> {
+    let limit = params.limit.unwrap_or(50);
+    let since = params.since.unwrap_or(0);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, market_id, opened_at, closed_at, duration_ms,
+               spread_size, spread_category, open_duration_class, close_reason, opportunity_class,
+               detection_latency_us, executable_size, executable_cost, expected_profit,
+               net_spread, net_profit, window_kind, side,
+               confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, classifier_version,
+               peak_spread, peak_spread_at, closing_spread, twa_spread,
+               min_yes_ask, max_yes_ask, min_no_ask, max_no_ask,
+               exchange_opened_at, exchange_closed_at, open_book_depth, close_book_depth
+        FROM windows
+        WHERE opened_at > ? AND expected_profit IS NOT NULL
+        ORDER BY expected_profit DESC
+        LIMIT ?
+        "#,
+        since,
+        limit
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let windows = rows
+        .into_iter()
+        .map(|r| WindowResponse {
+            id: r.id.unwrap_or(0),
+            market_id: r.market_id,
+            opened_at: r.opened_at,
+            closed_at: r.closed_at,
+            duration_ms: r.duration_ms,
+            spread_size: r.spread_size,
+            spread_category: r.spread_category,
+            open_duration_class: r.open_duration_class,
+            close_reason: r.close_reason,
+            opportunity_class: r.opportunity_class,
+            detection_latency_us: r.detection_latency_us,
+            executable_size: r.executable_size,
+            executable_cost: r.executable_cost,
+            expected_profit: r.expected_profit,
+            net_spread: r.net_spread,
+            net_profit: r.net_profit,
+            window_kind: r.window_kind,
+            side: r.side,
+            confirm_min_ticks: r.confirm_min_ticks,
+            confirm_min_elapsed_ms: r.confirm_min_elapsed_ms,
+            confirm_rule_source: r.confirm_rule_source,
+            classifier_version: r.classifier_version,
+            peak_spread: r.peak_spread,
+            peak_spread_at: r.peak_spread_at,
+            closing_spread: r.closing_spread,
+            twa_spread: r.twa_spread,
+            min_yes_ask: r.min_yes_ask,
+            max_yes_ask: r.max_yes_ask,
+            min_no_ask: r.min_no_ask,
+            max_no_ask: r.max_no_ask,
+            exchange_opened_at: r.exchange_opened_at,
+            exchange_closed_at: r.exchange_closed_at,
+            open_book_depth: r.open_book_depth,
+            close_book_depth: r.close_book_depth,
+        })
+        .collect();
+
+    Ok(Json(windows))
+}
+
+async fn get_window_ticks(
+    State(state): State<ApiState>,
+    Path(window_id): Path<i64>,
+) -> Result<Json<Vec<WindowTickResponse>>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT seq, at_ns, kind, yes_ask, no_ask, yes_bid, no_bid,
+               yes_ask_size, no_ask_size, yes_bid_size, no_bid_size,
+               spread, trade_asset_id, trade_price
+        FROM window_ticks
+        WHERE window_id = ?
+        ORDER BY seq
+        "#,
+        window_id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let ticks = rows
+        .into_iter()
+        .map(|r| WindowTickResponse {
+            seq: r.seq,
+            at_ns: r.at_ns,
+            kind: r.kind,
+            yes_ask: r.yes_ask,
+            no_ask: r.no_ask,
+            yes_bid: r.yes_bid,
+            no_bid: r.no_bid,
+            yes_ask_size: r.yes_ask_size,
+            no_ask_size: r.no_ask_size,
+            yes_bid_size: r.yes_bid_size,
+            no_bid_size: r.no_bid_size,
+            spread: r.spread,
+            trade_asset_id: r.trade_asset_id,
+            trade_price: r.trade_price,
+        })
+        .collect();
+
+    Ok(Json(ticks))
+}
+
 async fn get_stats_summary(
     State(state): State<ApiState>,
 ) -> Result<Json<SummaryResponse>, AppError> {
@@ -307,7 +747,8 @@ async fn get_stats_summary(
         SELECT m.id, m.question, m.category,
                ms.windows_24h, ms.p1_windows_24h, ms.p2_windows_24h,
                ms.avg_window_duration_ms, ms.avg_spread_size,
-               ms.noise_ratio, ms.opportunity_score
+               ms.noise_ratio, ms.opportunity_score, ms.avg_open_book_depth,
+               ms.book_ask_depth, ms.book_bid_depth, ms.book_imbalance, ms.book_levels, ms.book_update_rate
         FROM markets m
         LEFT JOIN market_stats ms ON m.id = ms.market_id
         ORDER BY ms.opportunity_score DESC NULLS LAST
@@ -330,6 +771,12 @@ async fn get_stats_summary(
             avg_spread_size: r.avg_spread_size,
             noise_ratio: r.noise_ratio,
             opportunity_score: r.opportunity_score,
+            avg_open_book_depth: r.avg_open_book_depth,
+            book_ask_depth: r.book_ask_depth,
+            book_bid_depth: r.book_bid_depth,
+            book_imbalance: r.book_imbalance,
+            book_levels: r.book_levels,
+            book_update_rate: r.book_update_rate,
         })
         .collect();
 
@@ -346,11 +793,31 @@ async fn get_stats_latency(
 ) -> Json<serde_json::Value> {
     let (p50, p95, p99) = state.latency_stats.percentiles();
     let to_ms = |us: Option<u64>| us.map(|u| (u as f64) / 1000.0);
+    let feed = state.feed_latency.summary();
+    let parse = state.parse_latency.summary();
+    let ns_to_us = |ns: Option<u64>| ns.map(|n| (n as f64) / 1000.0);
     Json(serde_json::json!({
         "p50_ms": to_ms(p50),
         "p95_ms": to_ms(p95),
         "p99_ms": to_ms(p99),
-        "sample_count": state.latency_stats.len()
+        "sample_count": state.latency_stats.len(),
+        "feed": {
+            "p50_ms": to_ms(feed.p50_us),
+            "p95_ms": to_ms(feed.p95_us),
+            "p99_ms": to_ms(feed.p99_us),
+            "raw_p50_ms": to_ms(feed.raw_p50_us),
+            "raw_p99_ms": to_ms(feed.raw_p99_us),
+            "clock_offset_ms": feed.clock_offset_us.map(|us| us as f64 / 1000.0),
+            "sample_count": feed.sample_count,
+            "negative_samples": feed.negative_samples,
+        },
+        "parse": {
+            "p50_us": ns_to_us(parse.p50_ns),
+            "p95_us": ns_to_us(parse.p95_ns),
+            "p99_us": ns_to_us(parse.p99_ns),
+            "max_us": ns_to_us(parse.max_ns),
+            "sample_count": parse.sample_count,
+        },
     }))
 }
 
@@ -364,17 +831,93 @@ async fn get_health(
     } else {
         serde_json::Value::Number(serde_json::Number::from(last_ns as i64))
     };
+    let detector_shards: Vec<serde_json::Value> = state
+        .health
+        .detector_shards()
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "shard": s.index,
+                "msgs_processed": s.msgs_processed.load(Ordering::Relaxed),
+                "msgs_per_sec": s.msgs_per_sec.load(Ordering::Relaxed),
+                "queue_depth": s.queue_depth.load(Ordering::Relaxed),
+                "dropped": s.dropped.load(Ordering::Relaxed),
+            })
+        })
+        .collect();
+    let ws_connections: Vec<serde_json::Value> = state
+        .health
+        .ws_connections()
+        .iter()
+        .map(|c| {
+            let last_frame_ns = c.last_frame_at_ns.load(Ordering::Relaxed);
+            let disconnected_since_ns = c.disconnected_since_ns.load(Ordering::Relaxed);
+            serde_json::json!({
+                "connection": c.index,
+                "connected": c.is_connected(),
+                "subscribed_assets": c.subscribed_assets.load(Ordering::Relaxed),
+                "frames_received": c.frames_received.load(Ordering::Relaxed),
+                "reconnects": c.reconnects.load(Ordering::Relaxed),
+                "last_frame_at_ns": (last_frame_ns != 0).then_some(last_frame_ns),
+                "disconnected_since_ns": (disconnected_since_ns != 0).then_some(disconnected_since_ns),
+                "disconnected_ms_total": c.disconnected_ns_total.load(Ordering::Relaxed) / 1_000_000,
+                "last_outage_ms": c.last_outage_ns.load(Ordering::Relaxed) / 1_000_000,
+                "books_invalidated": c.books_invalidated.load(Ordering::Relaxed),
+                "breaker": c.breaker().to_string(),
+                "consecutive_failures": c.consecutive_failures.load(Ordering::Relaxed),
+            })
+        })
+        .collect();
+    let (price_checks, price_divergences, hash_mismatches) = state.store.integrity_totals();
+    let divergence_rate = (price_checks > 0).then(|| price_divergences as f64 / price_checks as f64);
     Json(serde_json::json!({
         "ws_connected": state.health.ws_connected(),
+        "ws_connections": ws_connections,
         "markets_subscribed": state.store.market_count(),
         "hydrated_markets": state.store.hydrated_market_count(),
         "total_markets": state.store.market_count(),
+        "silent_tokens": state.health.silent_tokens.load(Ordering::Relaxed),
+        "unsnapshotted_tokens": state.health.unsnapshotted_tokens.load(Ordering::Relaxed),
+        "stale_markets": state.health.stale_markets.load(Ordering::Relaxed),
+        "interrupted_markets": state.store.interrupted_market_count(),
+        "price_checks": price_checks,
+        "price_divergences": price_divergences,
+        "divergence_rate": divergence_rate,
+        "hash_mismatches": hash_mismatches,
+        "corrupt_books": state.store.corrupt_tokens().len(),
+        "parse_failures": crate::ws::messages::parse_failures(),
+        "unknown_event_types": crate::ws::messages::unknown_event_counts(),
+        "resubscribes": state.health.resubscribes.load(Ordering::Relaxed),
         "last_window_at_ns": last_window_at_ns,
         "write_queue_pending": state.health.write_queue_pending(),
         "detection_p99_us": p99,
+        "detector_shards": detector_shards,
     }))
 }
 
+/// Per-connection reconnect state and the recent connection history.
+async fn get_health_ws(
+    State(state): State<ApiState>,
+) -> Json<serde_json::Value> {
+    let connections: Vec<serde_json::Value> = state
+        .health
+        .ws_connections()
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "connection": c.index,
+                "connected": c.is_connected(),
+                "breaker": c.breaker().to_string(),
+                "breaker_opens": c.breaker_opens.load(Ordering::Relaxed),
+                "consecutive_failures": c.consecutive_failures.load(Ordering::Relaxed),
+                "reconnects": c.reconnects.load(Ordering::Relaxed),
+                "history": c.history(),
+            })
+        })
+        .collect();
+    Json(serde_json::json!({ "connections": connections }))
+}
+
 async fn ws_events_handler(
     ws: WebSocketUpgrade,
     State(state): State<ApiState>,
@@ -387,7 +930,7 @@ async fn handle_window_events_ws(mut socket: WebSocket, mut rx: broadcast::Recei
     while let Ok(event) = rx.recv().await {
         match serde_json::to_string(&event) {
             Ok(json) => {
-                if socket.send(Message::Text(json.into())).await.is_err() {
+                if socket.send(Message::Text(json)).await.is_err() {
                     break;
                 }
             }
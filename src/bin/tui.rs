@@ -115,22 +115,18 @@ async fn run_loop(
                                 Focus::Windows => Focus::Markets,
                             };
                         }
-                        KeyCode::Esc => {
-                            if app.showing_market_windows() {
-                                app.clear_market_windows();
-                                *focus = Focus::Markets;
-                                window_state.select(None);
-                            }
+                        KeyCode::Esc if app.showing_market_windows() => {
+                            app.clear_market_windows();
+                            *focus = Focus::Markets;
+                            window_state.select(None);
                         }
-                        KeyCode::Enter => {
-                            if *focus == Focus::Markets {
-                                if let Some(i) = market_state.selected() {
-                                    if let Some(m) = app.markets.get(i) {
-                                        let id = m.id.clone();
-                                        app.fetch_market_windows(client, &id).await;
-                                        *focus = Focus::Windows;
-                                        window_state.select(Some(0));
-                                    }
+                        KeyCode::Enter if *focus == Focus::Markets => {
+                            if let Some(i) = market_state.selected() {
+                                if let Some(m) = app.markets.get(i) {
+                                    let id = m.id.clone();
+                                    app.fetch_market_windows(client, &id).await;
+                                    *focus = Focus::Windows;
+                                    window_state.select(Some(0));
                                 }
                             }
                         }
@@ -624,6 +620,9 @@ fn shorten_reason(r: &str) -> &'static str {
         "volume_spike_instant" => "vol-inst",
         "price_drift" => "drift",
         "order_vanished" => "vanished",
+        "feed_stale" => "stale",
+        "feed_interrupted" => "intr",
+        "book_corrupt" => "corrupt",
         _ => "—",
     }
 }
@@ -260,6 +260,7 @@ pub fn truncate(s: &str, max: usize) -> String {
     }
 }
 
+#[allow(dead_code)]
 fn main() {
     // TUI app is a work in progress — entry point lives in src/bin/tui.rs
 }
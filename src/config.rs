@@ -1,42 +1,436 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use crate::error::{AppError, Result};
+use crate::types::{Category, ConfirmationRule, Price};
 
 pub const WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
 pub const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
 pub const CLOB_API_URL: &str = "https://clob.polymarket.com";
 
-/// Minimum consecutive ticks a spread must survive before being registered as a real window.
-/// A window with tick_count < MIN_ARB_TICKS is classified as single_tick (noise).
-/// Must be >= 2 — the open event fires in the (true, true) branch so tick_count=1 can never
-/// reach the confirmation check.
+/// Default minimum consecutive ticks a spread must survive before being registered as a real
+/// window (CONFIRM_MIN_TICKS overrides it at runtime, see `ConfirmationRules`).
+/// A window that closes below its rule is classified as single_tick (noise).
 pub const MIN_ARB_TICKS: u32 = 2;
 
 /// Heartbeat ping interval (seconds).
 pub const WS_PING_INTERVAL_SECS: u64 = 30;
 
-/// Alert threshold: no messages received for this many seconds on an active market.
+/// Alert threshold: no messages received for this many seconds on an active market
+/// (one with an open window), or no `book` snapshot this long after subscribing.
 pub const WS_SILENCE_ALERT_SECS: u64 = 5;
 
-/// Reconnect backoff values in milliseconds.
-pub const RECONNECT_BACKOFF_MS: &[u64] = &[100, 200, 400, 800];
+/// Minimum seconds between automatic resubscribes of the same token.
+pub const WS_RESUBSCRIBE_COOLDOWN_SECS: u64 = 30;
+
+/// Local and server best prices further apart than this count as a divergence.
+pub const PRICE_DIVERGENCE_TOLERANCE: Price = Price::from_ticks(10); // 0.001
+
+/// Consecutive divergent price changes after which a token's book is marked corrupt.
+pub const BOOK_DIVERGENCE_LIMIT: u32 = 3;
+
+/// Default first reconnect delay (WS_RECONNECT_BASE_MS); doubles per consecutive failure.
+pub const DEFAULT_RECONNECT_BASE_MS: u64 = 100;
+
+/// Default reconnect delay ceiling (WS_RECONNECT_MAX_MS).
+pub const DEFAULT_RECONNECT_MAX_MS: u64 = 30_000;
+
+/// Default fraction of each reconnect delay that is randomised away (WS_RECONNECT_JITTER).
+pub const DEFAULT_RECONNECT_JITTER: f64 = 0.5;
+
+/// Default consecutive failures that open a connection's circuit breaker (WS_BREAKER_FAILURES).
+pub const DEFAULT_BREAKER_FAILURES: u32 = 8;
 
-/// Channel capacity for internal message routing.
+/// Default seconds an open circuit breaker waits before a probe (WS_BREAKER_COOLDOWN_SECS).
+pub const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 120;
+
+/// A connection that stays up this long resets its failure count.
+pub const WS_STABLE_CONNECTION_SECS: u64 = 30;
+
+/// Connection attempts kept per connection for `/health/ws`.
+pub const WS_HISTORY_LEN: usize = 32;
+
+/// Channel capacity for internal message routing (per detector shard).
 pub const CHANNEL_CAPACITY: usize = 1024;
 
+/// Default number of spread detector shards (DETECTOR_SHARDS).
+pub const DEFAULT_DETECTOR_SHARDS: usize = 4;
+
 /// Market scorer update interval (seconds).
 pub const SCORER_INTERVAL_SECS: u64 = 60;
 
+/// Default seconds between archived snapshots of every hydrated book (BOOK_SNAPSHOT_INTERVAL_SECS).
+pub const DEFAULT_BOOK_SNAPSHOT_INTERVAL_SECS: u64 = 60;
+
+/// Default levels per side kept in an archived book snapshot (BOOK_SNAPSHOT_DEPTH).
+pub const DEFAULT_BOOK_SNAPSHOT_DEPTH: usize = 10;
+
+/// Default hours archived book snapshots are kept (BOOK_SNAPSHOT_RETENTION_HOURS).
+pub const DEFAULT_BOOK_SNAPSHOT_RETENTION_HOURS: u64 = 72;
+
+/// How often snapshots older than the retention are deleted (seconds).
+pub const BOOK_SNAPSHOT_PRUNE_SECS: u64 = 300;
+
+/// Default seconds between warm-restart checkpoints of the market set (CHECKPOINT_INTERVAL_SECS).
+pub const DEFAULT_CHECKPOINT_INTERVAL_SECS: u64 = 30;
+
+/// Default hours a checkpoint stays usable for a warm restart (CHECKPOINT_MAX_AGE_HOURS).
+pub const DEFAULT_CHECKPOINT_MAX_AGE_HOURS: u64 = 24;
+
 /// Market refresh interval (seconds) — how often to re-fetch qualifying markets from Gamma.
 pub const MARKET_REFRESH_INTERVAL_SECS: u64 = 60;
 
 
+/// Cap on ticks recorded per window trace; later ticks are dropped (counters still update).
+pub const MAX_WINDOW_TICKS: usize = 5_000;
+
 /// Maximum asset IDs per WS subscribe frame to avoid server-side size limits.
 pub const WS_SUBSCRIBE_CHUNK_SIZE: usize = 500;
 
+/// Default number of pooled market-channel WS connections (WS_CONNECTIONS).
+pub const DEFAULT_WS_CONNECTIONS: usize = 4;
+
+/// The WS pool moves markets between connections once their partitions differ by more than this.
+pub const WS_REBALANCE_SLACK: usize = 10;
+
+/// Default capture file size (uncompressed MB) before rotating to a new file.
+pub const DEFAULT_CAPTURE_ROTATE_MB: u64 = 256;
+
+/// Capture records buffered ahead of the writer; frames beyond this are dropped, never awaited.
+pub const CAPTURE_CHANNEL_CAPACITY: usize = 65_536;
+
+/// Tick size assumed for a market until a `tick_size_change` says otherwise.
+pub const DEFAULT_TICK_SIZE: Price = Price::from_ticks(100); // 0.01
+
+/// Bands, in ticks from the best price, over which `BookMetrics` sums resting
+/// size. The last (widest) band also drives imbalance and window depth.
+pub const BOOK_DEPTH_BANDS: [i64; 3] = [1, 2, 5];
+
+/// Time constant of the per-token book update rate (an exponentially decayed
+/// updates-per-second estimate).
+pub const BOOK_UPDATE_RATE_TAU_SECS: f64 = 10.0;
+
 /// Spread size thresholds (1.00 - combined_cost).
 pub mod spread_thresholds {
-    pub const NOISE_MAX: f64 = 0.02;
-    pub const SMALL_MAX: f64 = 0.05;
-    pub const MEDIUM_MAX: f64 = 0.10;
+    use crate::types::Price;
+
+    /// Spreads under this many ticks are noise, so the floor follows the market's
+    /// tick size (0.02 at the default 0.01 tick).
+    pub const NOISE_MAX_TICKS: i64 = 2;
+    pub const SMALL_MAX: Price = Price::from_ticks(500); // 0.05
+    pub const MEDIUM_MAX: Price = Price::from_ticks(1_000); // 0.10
+}
+
+/// Trading cost model used to turn the gross spread into a net spread.
+///
+/// Taker fees are charged in basis points of notional on each leg; the fixed cost
+/// (gas / relayer) is charged once per leg trade and amortised over the executable size.
+#[derive(Debug, Clone, Default)]
+pub struct FeeModel {
+    /// Default taker fee in bps (FEE_TAKER_BPS).
+    pub taker_fee_bps: f64,
+    /// Per-category overrides (FEE_TAKER_BPS_BY_CATEGORY, e.g. "crypto=100,sports=0").
+    pub taker_fee_bps_by_category: HashMap<Category, f64>,
+    /// Per-market overrides keyed by market id (FEE_TAKER_BPS_BY_MARKET, "0xabc=50").
+    pub taker_fee_bps_by_market: HashMap<String, f64>,
+    /// Fixed USD cost per leg trade (FEE_FIXED_COST_USD).
+    pub fixed_cost_usd: f64,
+}
+
+impl FeeModel {
+    /// Taker fee in bps for a market. Market override wins over category, category over default.
+    pub fn taker_fee_bps(&self, market_id: &str, category: Option<Category>) -> f64 {
+        if let Some(&bps) = self.taker_fee_bps_by_market.get(market_id) {
+            return bps;
+        }
+        category
+            .and_then(|c| self.taker_fee_bps_by_category.get(&c).copied())
+            .unwrap_or(self.taker_fee_bps)
+    }
+
+    /// Net spread per share: gross spread minus taker fees on both legs, minus the
+    /// fixed cost of two trades amortised over `size` shares (ignored when size is unknown).
+    pub fn net_spread(&self, gross_spread: f64, combined_cost: f64, fee_bps: f64, size: Option<f64>) -> f64 {
+        let fees = combined_cost * fee_bps / 10_000.0;
+        let fixed = match size {
+            Some(s) if s > 0.0 => 2.0 * self.fixed_cost_usd / s,
+            _ => 0.0,
+        };
+        gross_spread - fees - fixed
+    }
+
+    /// Net dollar profit: gross `profit` minus taker fees on `notional` and two fixed trade costs.
+    pub fn net_profit(&self, profit: f64, notional: f64, fee_bps: f64) -> f64 {
+        profit - notional * fee_bps / 10_000.0 - 2.0 * self.fixed_cost_usd
+    }
+}
+
+/// Runtime window confirmation rules: a default plus per-category and per-pinned-prefix overrides.
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmationRules {
+    /// CONFIRM_MIN_TICKS / CONFIRM_MIN_ELAPSED_MS.
+    pub default: ConfirmationRule,
+    /// CONFIRM_RULES_BY_CATEGORY, e.g. "crypto=1:250,sports=3:0" (ticks:ms).
+    pub by_category: HashMap<Category, ConfirmationRule>,
+    /// CONFIRM_RULES_BY_PINNED, keyed by a PINNED_SLUGS prefix, e.g. "btc-updown-5m=1:100".
+    pub by_pinned_prefix: HashMap<String, ConfirmationRule>,
+}
+
+impl ConfirmationRules {
+    /// Rule for a market. Pinned prefix wins over category, category over default.
+    pub fn rule_for(&self, category: Option<Category>, pinned_prefix: Option<&str>) -> &ConfirmationRule {
+        pinned_prefix
+            .and_then(|p| self.by_pinned_prefix.get(p))
+            .or_else(|| category.and_then(|c| self.by_category.get(&c)))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Parse a comma-separated `key=ticks:ms` list into rules tagged `<scope>:<key>`,
+/// skipping malformed entries. Either half may be empty to fall back to `default`.
+fn parse_rule_list(raw: &str, scope: &str, default: &ConfirmationRule) -> Vec<(String, ConfirmationRule)> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            let (ticks, ms) = v.trim().split_once(':')?;
+            let min_ticks = match ticks.trim() {
+                "" => default.min_ticks,
+                t => t.parse::<u32>().ok()?,
+            };
+            let min_elapsed_ms = match ms.trim() {
+                "" => default.min_elapsed_ms,
+                m => m.parse::<u64>().ok()?,
+            };
+            let key = k.trim().to_string();
+            let source = format!("{scope}:{key}");
+            Some((key, ConfirmationRule { min_ticks, min_elapsed_ms, source }))
+        })
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
+}
+
+/// Book snapshot archive settings, see `db::book_archive::BookArchiver`.
+#[derive(Debug, Clone)]
+pub struct BookArchiveConfig {
+    /// Interval between snapshots of every hydrated book; zero disables periodic
+    /// snapshots but keeps the ones forced by window opens (BOOK_SNAPSHOT_INTERVAL_SECS).
+    pub interval: Duration,
+    /// Levels per side kept in each snapshot (BOOK_SNAPSHOT_DEPTH).
+    pub depth: usize,
+    /// Snapshots older than this are deleted; zero keeps them forever (BOOK_SNAPSHOT_RETENTION_HOURS).
+    pub retention: Duration,
+}
+
+impl Default for BookArchiveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(DEFAULT_BOOK_SNAPSHOT_INTERVAL_SECS),
+            depth: DEFAULT_BOOK_SNAPSHOT_DEPTH,
+            retention: Duration::from_secs(DEFAULT_BOOK_SNAPSHOT_RETENTION_HOURS * 3_600),
+        }
+    }
+}
+
+fn book_archive_config_from_env() -> Result<BookArchiveConfig> {
+    let interval_secs = env_or("BOOK_SNAPSHOT_INTERVAL_SECS", DEFAULT_BOOK_SNAPSHOT_INTERVAL_SECS)
+        .parse::<u64>()
+        .map_err(|_| AppError::Config("BOOK_SNAPSHOT_INTERVAL_SECS must be a non-negative integer".to_string()))?;
+    let depth = env_or("BOOK_SNAPSHOT_DEPTH", DEFAULT_BOOK_SNAPSHOT_DEPTH)
+        .parse::<usize>()
+        .ok()
+        .filter(|&d| d > 0)
+        .ok_or_else(|| AppError::Config("BOOK_SNAPSHOT_DEPTH must be a positive integer".to_string()))?;
+    let retention_hours = env_or("BOOK_SNAPSHOT_RETENTION_HOURS", DEFAULT_BOOK_SNAPSHOT_RETENTION_HOURS)
+        .parse::<u64>()
+        .map_err(|_| AppError::Config("BOOK_SNAPSHOT_RETENTION_HOURS must be a non-negative integer".to_string()))?;
+    Ok(BookArchiveConfig {
+        interval: Duration::from_secs(interval_secs),
+        depth,
+        retention: Duration::from_secs(retention_hours * 3_600),
+    })
+}
+
+/// Warm-restart checkpoint settings, see `db::checkpoint::Checkpointer`.
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    /// Interval between checkpoints; zero disables checkpointing and warm
+    /// restarts, so every start bootstraps from Gamma (CHECKPOINT_INTERVAL_SECS).
+    pub interval: Duration,
+    /// Checkpoints older than this are ignored at startup; zero accepts any age
+    /// (CHECKPOINT_MAX_AGE_HOURS).
+    pub max_age: Duration,
+}
+
+impl CheckpointConfig {
+    pub fn enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(DEFAULT_CHECKPOINT_INTERVAL_SECS),
+            max_age: Duration::from_secs(DEFAULT_CHECKPOINT_MAX_AGE_HOURS * 3_600),
+        }
+    }
+}
+
+fn checkpoint_config_from_env() -> Result<CheckpointConfig> {
+    let interval_secs = env_or("CHECKPOINT_INTERVAL_SECS", DEFAULT_CHECKPOINT_INTERVAL_SECS)
+        .parse::<u64>()
+        .map_err(|_| AppError::Config("CHECKPOINT_INTERVAL_SECS must be a non-negative integer".to_string()))?;
+    let max_age_hours = env_or("CHECKPOINT_MAX_AGE_HOURS", DEFAULT_CHECKPOINT_MAX_AGE_HOURS)
+        .parse::<u64>()
+        .map_err(|_| AppError::Config("CHECKPOINT_MAX_AGE_HOURS must be a non-negative integer".to_string()))?;
+    Ok(CheckpointConfig {
+        interval: Duration::from_secs(interval_secs),
+        max_age: Duration::from_secs(max_age_hours * 3_600),
+    })
+}
+
+/// Reconnect pacing for each pooled WS connection, see `ws::backoff::Backoff`.
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// First delay after a failure (WS_RECONNECT_BASE_MS).
+    pub base: Duration,
+    /// Ceiling on the exponential delay (WS_RECONNECT_MAX_MS).
+    pub max: Duration,
+    /// Up to this fraction of each delay is randomly taken off (WS_RECONNECT_JITTER, 0–1).
+    pub jitter: f64,
+    /// Consecutive failures that open the circuit breaker; 0 disables it (WS_BREAKER_FAILURES).
+    pub breaker_failures: u32,
+    /// How long an open breaker waits before letting a probe through (WS_BREAKER_COOLDOWN_SECS).
+    pub breaker_cooldown: Duration,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(DEFAULT_RECONNECT_BASE_MS),
+            max: Duration::from_millis(DEFAULT_RECONNECT_MAX_MS),
+            jitter: DEFAULT_RECONNECT_JITTER,
+            breaker_failures: DEFAULT_BREAKER_FAILURES,
+            breaker_cooldown: Duration::from_secs(DEFAULT_BREAKER_COOLDOWN_SECS),
+        }
+    }
+}
+
+fn reconnect_config_from_env() -> Result<ReconnectConfig> {
+    let base_ms = env_or("WS_RECONNECT_BASE_MS", DEFAULT_RECONNECT_BASE_MS)
+        .parse::<u64>()
+        .ok()
+        .filter(|&ms| ms > 0)
+        .ok_or_else(|| AppError::Config("WS_RECONNECT_BASE_MS must be a positive integer".to_string()))?;
+    let max_ms = env_or("WS_RECONNECT_MAX_MS", DEFAULT_RECONNECT_MAX_MS)
+        .parse::<u64>()
+        .ok()
+        .filter(|&ms| ms >= base_ms)
+        .ok_or_else(|| AppError::Config("WS_RECONNECT_MAX_MS must be an integer >= WS_RECONNECT_BASE_MS".to_string()))?;
+    let jitter = env_or("WS_RECONNECT_JITTER", DEFAULT_RECONNECT_JITTER)
+        .parse::<f64>()
+        .ok()
+        .filter(|j| (0.0..=1.0).contains(j))
+        .ok_or_else(|| AppError::Config("WS_RECONNECT_JITTER must be a number between 0 and 1".to_string()))?;
+    let breaker_failures = env_or("WS_BREAKER_FAILURES", DEFAULT_BREAKER_FAILURES)
+        .parse::<u32>()
+        .map_err(|_| AppError::Config("WS_BREAKER_FAILURES must be a non-negative integer".to_string()))?;
+    let cooldown_secs = env_or("WS_BREAKER_COOLDOWN_SECS", DEFAULT_BREAKER_COOLDOWN_SECS)
+        .parse::<u64>()
+        .map_err(|_| AppError::Config("WS_BREAKER_COOLDOWN_SECS must be a non-negative integer".to_string()))?;
+    Ok(ReconnectConfig {
+        base: Duration::from_millis(base_ms),
+        max: Duration::from_millis(max_ms),
+        jitter,
+        breaker_failures,
+        breaker_cooldown: Duration::from_secs(cooldown_secs),
+    })
+}
+
+/// Settings handed to the spread detector.
+#[derive(Debug, Clone, Default)]
+pub struct DetectorConfig {
+    /// Taker fee and fixed cost model for net spread computation.
+    pub fees: FeeModel,
+    /// Windows open only when the net spread is strictly above this (MIN_NET_SPREAD).
+    pub min_net_spread: f64,
+    /// When a pending window counts as confirmed, and how closed windows are classified.
+    pub confirmation: ConfirmationRules,
+    /// Classifier name or `name@vN` (WINDOW_CLASSIFIER); empty selects the default.
+    pub classifier: String,
+}
+
+fn classifier_from_env() -> Result<String> {
+    let name = std::env::var("WINDOW_CLASSIFIER")
+        .unwrap_or_else(|_| crate::detector::classifier::DEFAULT_CLASSIFIER.to_string());
+    if crate::detector::classifier::by_name(&name).is_none() {
+        let known: Vec<String> = crate::detector::classifier::available().iter().map(|c| c.id()).collect();
+        return Err(AppError::Config(format!(
+            "WINDOW_CLASSIFIER '{name}' is unknown (available: {})",
+            known.join(", ")
+        )));
+    }
+    Ok(name)
+}
+
+fn confirmation_rules_from_env() -> Result<ConfirmationRules> {
+    let default = ConfirmationRule {
+        min_ticks: std::env::var("CONFIRM_MIN_TICKS")
+            .unwrap_or_else(|_| MIN_ARB_TICKS.to_string())
+            .parse::<u32>()
+            .map_err(|_| AppError::Config("CONFIRM_MIN_TICKS must be a non-negative integer".to_string()))?,
+        min_elapsed_ms: std::env::var("CONFIRM_MIN_ELAPSED_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<u64>()
+            .map_err(|_| AppError::Config("CONFIRM_MIN_ELAPSED_MS must be a non-negative integer".to_string()))?,
+        source: "default".to_string(),
+    };
+    let by_category = category_map(
+        parse_rule_list(&std::env::var("CONFIRM_RULES_BY_CATEGORY").unwrap_or_default(), "category", &default),
+        "CONFIRM_RULES_BY_CATEGORY",
+    )?;
+    let by_pinned_prefix = parse_rule_list(
+        &std::env::var("CONFIRM_RULES_BY_PINNED").unwrap_or_default(),
+        "pinned",
+        &default,
+    )
+    .into_iter()
+    .collect();
+    Ok(ConfirmationRules { default, by_category, by_pinned_prefix })
+}
+
+/// Value of the `name` environment variable, or `default` when it is unset.
+fn env_or(name: &str, default: impl ToString) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+/// Keys `entries` by category for the `var` setting. Unknown category names are
+/// rejected rather than falling back to `Other`, so a typo can't retarget every
+/// uncategorised market.
+fn category_map<V>(entries: Vec<(String, V)>, var: &str) -> Result<HashMap<Category, V>> {
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let category = Category::from_name(&key).ok_or_else(|| {
+                AppError::Config(format!(
+                    "{var}: unknown category '{key}' (expected sports, weather, crypto, politics, economics or other)"
+                ))
+            })?;
+            Ok((category, value))
+        })
+        .collect()
+}
+
+/// Parse a comma-separated `key=value` list into (key, f64) pairs, skipping malformed entries.
+fn parse_kv_list(raw: &str) -> Vec<(String, f64)> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            let v = v.trim().parse::<f64>().ok()?;
+            Some((k.trim().to_string(), v))
+        })
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +453,25 @@ pub struct Config {
     /// Slug prefixes to always track regardless of filters (PINNED_SLUGS, comma-separated).
     /// Example: "btc-updown-5m,btc-updown-15m,eth-updown-5m"
     pub pinned_slugs: Vec<String>,
+    /// Max neg-risk (multi-outcome) events to watch as baskets (SCANNER_MAX_NEG_RISK_EVENTS).
+    /// 0 disables event-level detection.
+    pub scanner_max_neg_risk_events: usize,
+    /// Fees, net spread threshold and confirmation rules for the detector.
+    pub detector: DetectorConfig,
+    /// Number of spread detector tasks, each owning a hash partition of markets (DETECTOR_SHARDS).
+    pub detector_shards: usize,
+    /// Number of market-channel WS connections, each owning a partition of assets (WS_CONNECTIONS).
+    pub ws_connections: usize,
+    /// Reconnect backoff and circuit breaker settings for each WS connection.
+    pub reconnect: ReconnectConfig,
+    /// Directory for raw feed capture files (CAPTURE_DIR); capture is off when unset.
+    pub capture_dir: Option<String>,
+    /// Uncompressed bytes written to one capture file before rotating (CAPTURE_ROTATE_MB).
+    pub capture_rotate_bytes: u64,
+    /// Periodic and window-open book snapshots into `book_snapshots`.
+    pub book_archive: BookArchiveConfig,
+    /// Warm-restart checkpoint of the market set, pins and last-known prices.
+    pub checkpoint: CheckpointConfig,
 }
 
 impl Config {
@@ -99,6 +512,147 @@ impl Config {
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
                 .collect(),
+            scanner_max_neg_risk_events: std::env::var("SCANNER_MAX_NEG_RISK_EVENTS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse::<usize>()
+                .unwrap_or(10),
+            detector: DetectorConfig {
+                fees: FeeModel {
+                    taker_fee_bps: std::env::var("FEE_TAKER_BPS")
+                        .unwrap_or_else(|_| "0".to_string())
+                        .parse::<f64>()
+                        .map_err(|_| AppError::Config("FEE_TAKER_BPS must be a number".to_string()))?,
+                    taker_fee_bps_by_category: category_map(
+                        parse_kv_list(&std::env::var("FEE_TAKER_BPS_BY_CATEGORY").unwrap_or_default()),
+                        "FEE_TAKER_BPS_BY_CATEGORY",
+                    )?,
+                    taker_fee_bps_by_market: parse_kv_list(
+                        &std::env::var("FEE_TAKER_BPS_BY_MARKET").unwrap_or_default(),
+                    )
+                    .into_iter()
+                    .collect(),
+                    fixed_cost_usd: std::env::var("FEE_FIXED_COST_USD")
+                        .unwrap_or_else(|_| "0".to_string())
+                        .parse::<f64>()
+                        .map_err(|_| AppError::Config("FEE_FIXED_COST_USD must be a number".to_string()))?,
+                },
+                min_net_spread: std::env::var("MIN_NET_SPREAD")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse::<f64>()
+                    .map_err(|_| AppError::Config("MIN_NET_SPREAD must be a number".to_string()))?,
+                confirmation: confirmation_rules_from_env()?,
+                classifier: classifier_from_env()?,
+            },
+            detector_shards: match std::env::var("DETECTOR_SHARDS")
+                .unwrap_or_else(|_| DEFAULT_DETECTOR_SHARDS.to_string())
+                .parse::<usize>()
+            {
+                Ok(n) if n > 0 => n,
+                _ => return Err(AppError::Config("DETECTOR_SHARDS must be a positive integer".to_string())),
+            },
+            ws_connections: match std::env::var("WS_CONNECTIONS")
+                .unwrap_or_else(|_| DEFAULT_WS_CONNECTIONS.to_string())
+                .parse::<usize>()
+            {
+                Ok(n) if n > 0 => n,
+                _ => return Err(AppError::Config("WS_CONNECTIONS must be a positive integer".to_string())),
+            },
+            reconnect: reconnect_config_from_env()?,
+            capture_dir: std::env::var("CAPTURE_DIR").ok().filter(|d| !d.is_empty()),
+            capture_rotate_bytes: match std::env::var("CAPTURE_ROTATE_MB")
+                .unwrap_or_else(|_| DEFAULT_CAPTURE_ROTATE_MB.to_string())
+                .parse::<u64>()
+            {
+                Ok(mb) if mb > 0 => mb * 1024 * 1024,
+                _ => return Err(AppError::Config("CAPTURE_ROTATE_MB must be a positive integer".to_string())),
+            },
+            book_archive: book_archive_config_from_env()?,
+            checkpoint: checkpoint_config_from_env()?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> FeeModel {
+        FeeModel {
+            taker_fee_bps: 10.0,
+            taker_fee_bps_by_category: HashMap::from([(Category::Crypto, 100.0)]),
+            taker_fee_bps_by_market: HashMap::from([("m1".to_string(), 0.0)]),
+            fixed_cost_usd: 0.05,
+        }
+    }
+
+    #[test]
+    fn fee_lookup_prefers_market_then_category() {
+        let fees = model();
+        assert_eq!(fees.taker_fee_bps("m1", Some(Category::Crypto)), 0.0);
+        assert_eq!(fees.taker_fee_bps("m2", Some(Category::Crypto)), 100.0);
+        assert_eq!(fees.taker_fee_bps("m2", Some(Category::Sports)), 10.0);
+        assert_eq!(fees.taker_fee_bps("m2", None), 10.0);
+    }
+
+    #[test]
+    fn net_spread_deducts_fees_and_amortised_fixed_cost() {
+        let fees = model();
+        // 1% of 0.96 = 0.0096; 2 * 0.05 / 100 shares = 0.001
+        let net = fees.net_spread(0.04, 0.96, 100.0, Some(100.0));
+        assert!((net - (0.04 - 0.0096 - 0.001)).abs() < 1e-12, "net={net}");
+        // Unknown size: fixed cost not applied
+        let net = fees.net_spread(0.04, 0.96, 100.0, None);
+        assert!((net - (0.04 - 0.0096)).abs() < 1e-12, "net={net}");
+    }
+
+    #[test]
+    fn net_profit_deducts_fees_and_both_fixed_costs() {
+        let fees = model();
+        // 100 pairs at 0.96 → $4 gross on $96 notional
+        let profit = fees.net_profit(4.0, 96.0, 100.0);
+        assert!((profit - (100.0 - 96.0 - 0.96 - 0.10)).abs() < 1e-9, "profit={profit}");
+    }
+
+    #[test]
+    fn confirmation_rule_prefers_pinned_then_category() {
+        let default = ConfirmationRule::default();
+        let rules = ConfirmationRules {
+            by_category: category_map(
+                parse_rule_list("crypto=1:250, sports=:500, bad, x=y:1", "category", &default),
+                "CONFIRM_RULES_BY_CATEGORY",
+            )
+            .unwrap(),
+            by_pinned_prefix: parse_rule_list("btc-updown-5m=3:", "pinned", &default).into_iter().collect(),
+            default,
+        };
+        assert_eq!(rules.by_category.len(), 2);
+
+        let pinned = rules.rule_for(Some(Category::Crypto), Some("btc-updown-5m"));
+        assert_eq!((pinned.min_ticks, pinned.min_elapsed_ms), (3, 0));
+        assert_eq!(pinned.source, "pinned:btc-updown-5m");
+
+        let crypto = rules.rule_for(Some(Category::Crypto), Some("eth-updown-5m"));
+        assert_eq!((crypto.min_ticks, crypto.min_elapsed_ms), (1, 250));
+        let sports = rules.rule_for(Some(Category::Sports), None);
+        assert_eq!((sports.min_ticks, sports.min_elapsed_ms), (MIN_ARB_TICKS, 500));
+        assert_eq!(rules.rule_for(None, None).source, "default");
+
+        let typo = parse_rule_list("crytpo=1:250", "category", &ConfirmationRule::default());
+        assert!(category_map(typo, "CONFIRM_RULES_BY_CATEGORY").is_err(), "a typo must not retarget Other");
+    }
+
+    #[test]
+    fn category_map_rejects_unknown_categories() {
+        let fees = category_map(parse_kv_list("crypto=100,Sports=0"), "FEE_TAKER_BPS_BY_CATEGORY").unwrap();
+        assert_eq!(fees, HashMap::from([(Category::Crypto, 100.0), (Category::Sports, 0.0)]));
+
+        let err = category_map(parse_kv_list("crytpo=200"), "FEE_TAKER_BPS_BY_CATEGORY").unwrap_err();
+        assert!(err.to_string().contains("unknown category 'crytpo'"), "{err}");
+    }
+
+    #[test]
+    fn kv_list_skips_malformed_entries() {
+        let parsed = parse_kv_list("crypto=100, sports = 25 ,bad,=5,x=y");
+        assert_eq!(parsed, vec![("crypto".to_string(), 100.0), ("sports".to_string(), 25.0)]);
+    }
+}
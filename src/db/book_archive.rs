@@ -0,0 +1,218 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{error, info};
+
+use crate::config::{BookArchiveConfig, BOOK_SNAPSHOT_PRUNE_SECS};
+use crate::error::Result;
+use crate::state::MarketStore;
+use crate::types::WindowKind;
+
+/// Why a book snapshot was archived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotReason {
+    /// Regular pass over every hydrated book.
+    Periodic,
+    /// Forced by a window opening on the market.
+    WindowOpen,
+}
+
+impl std::fmt::Display for SnapshotReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotReason::Periodic => write!(f, "periodic"),
+            SnapshotReason::WindowOpen => write!(f, "window_open"),
+        }
+    }
+}
+
+/// Archives the top levels of order books into `book_snapshots`: every hydrated
+/// book each `interval`, plus the window's markets whenever a window opens.
+/// Snapshots older than the retention are pruned in the background.
+pub struct BookArchiver {
+    pool: sqlx::SqlitePool,
+    store: Arc<MarketStore>,
+    config: BookArchiveConfig,
+    /// `(kind, market or event id)` of each opened window.
+    open_rx: mpsc::Receiver<(WindowKind, String)>,
+}
+
+impl BookArchiver {
+    pub fn new(
+        pool: sqlx::SqlitePool,
+        store: Arc<MarketStore>,
+        config: BookArchiveConfig,
+        open_rx: mpsc::Receiver<(WindowKind, String)>,
+    ) -> Self {
+        Self { pool, store, config, open_rx }
+    }
+
+    pub async fn run(mut self) {
+        let mut periodic = (!self.config.interval.is_zero()).then(|| skipping_interval(self.config.interval));
+        let mut prune = skipping_interval(Duration::from_secs(BOOK_SNAPSHOT_PRUNE_SECS));
+        info!(
+            "[BOOK ARCHIVE] every {:?} (zero = window opens only), {} levels, retention {:?}",
+            self.config.interval, self.config.depth, self.config.retention,
+        );
+
+        loop {
+            tokio::select! {
+                Some((kind, id)) = self.open_rx.recv() => {
+                    let markets = match kind {
+                        WindowKind::Binary => vec![id],
+                        WindowKind::NegRiskYes | WindowKind::NegRiskNo => {
+                            self.store.event_member_ids(&id).unwrap_or_default()
+                        }
+                    };
+                    if let Err(e) = self.snapshot_markets(&markets, SnapshotReason::WindowOpen).await {
+                        error!("[BOOK ARCHIVE] window-open snapshot failed: {e}");
+                    }
+                }
+                _ = next_tick(&mut periodic) => {
+                    let markets: Vec<String> =
+                        self.store.all_market_ids().into_iter().filter(|id| self.store.is_hydrated(id)).collect();
+                    if let Err(e) = self.snapshot_markets(&markets, SnapshotReason::Periodic).await {
+                        error!("[BOOK ARCHIVE] periodic snapshot failed: {e}");
+                    }
+                }
+                _ = prune.tick() => {
+                    if let Err(e) = self.prune(now_ns()).await {
+                        error!("[BOOK ARCHIVE] prune failed: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes one row per token of each market in a single transaction. Tokens
+    /// with an empty book are skipped. Returns the number of rows written.
+    pub async fn snapshot_markets(&self, market_ids: &[String], reason: SnapshotReason) -> Result<usize> {
+        let taken_at = now_ns() as i64;
+        let reason = reason.to_string();
+        let mut written = 0;
+        let mut tx = self.pool.begin().await?;
+        for market_id in market_ids {
+            let Some((yes_token, no_token)) = self.store.market_tokens(market_id) else {
+                continue;
+            };
+            for asset_id in [&*yes_token, &*no_token] {
+                let Some((asks, bids)) = self.store.book_depth(asset_id, self.config.depth) else {
+                    continue;
+                };
+                if asks.is_empty() && bids.is_empty() {
+                    continue;
+                }
+                let best = self.store.best_prices(asset_id);
+                let best_ask = best.map(|(ask, _)| ask).filter(|p| p.is_positive());
+                let best_bid = best.map(|(_, bid)| bid).filter(|p| p.is_positive());
+                let asks = serde_json::to_string(&asks)?;
+                let bids = serde_json::to_string(&bids)?;
+                sqlx::query!(
+                    r#"
+                    INSERT INTO book_snapshots (market_id, asset_id, taken_at, reason, best_ask, best_bid, asks, bids)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                    market_id,
+                    asset_id,
+                    taken_at,
+                    reason,
+                    best_ask,
+                    best_bid,
+                    asks,
+                    bids,
+                )
+                .execute(&mut *tx)
+                .await?;
+                written += 1;
+            }
+        }
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    /// Deletes snapshots older than the retention. Returns the rows deleted.
+    pub async fn prune(&self, now_ns: u64) -> Result<u64> {
+        if self.config.retention.is_zero() {
+            return Ok(0);
+        }
+        let cutoff = now_ns.saturating_sub(self.config.retention.as_nanos() as u64) as i64;
+        let deleted = sqlx::query!("DELETE FROM book_snapshots WHERE taken_at < ?", cutoff)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        if deleted > 0 {
+            info!("[BOOK ARCHIVE] pruned {deleted} snapshots");
+        }
+        Ok(deleted)
+    }
+}
+
+/// An interval whose first tick is one period out and which skips ticks missed
+/// while a pass was running.
+fn skipping_interval(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval
+}
+
+/// Next tick of an optional interval; never resolves when it is disabled.
+async fn next_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory_pool;
+    use crate::types::{Category, Market, Price};
+
+    #[tokio::test]
+    async fn snapshots_top_levels_and_prunes_past_retention() {
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "market1".to_string(),
+            question: "Test".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+        let px = Price::from_f64;
+        store.apply_book_snapshot("yes1", &[(px(0.45), 100.0), (px(0.46), 5.0), (px(0.5), 1.0)], &[(px(0.44), 7.0)]);
+
+        let pool = memory_pool().await;
+        let (_open_tx, open_rx) = mpsc::channel(1);
+        let config = BookArchiveConfig { depth: 2, retention: Duration::from_secs(3_600), ..Default::default() };
+        let archiver = BookArchiver::new(pool.clone(), store, config, open_rx);
+
+        let written = archiver.snapshot_markets(&["market1".to_string()], SnapshotReason::WindowOpen).await.unwrap();
+        assert_eq!(written, 1, "the empty NO book is skipped");
+        let row: (String, String, Option<f64>, String, String) =
+            sqlx::query_as("SELECT asset_id, reason, best_ask, asks, bids FROM book_snapshots")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            ("yes1".into(), "window_open".into(), Some(0.45), "[[0.45,100.0],[0.46,5.0]]".into(), "[[0.44,7.0]]".into())
+        );
+
+        assert_eq!(archiver.prune(now_ns()).await.unwrap(), 0);
+        assert_eq!(archiver.prune(now_ns() + 3_601 * 1_000_000_000).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info};
+
+use crate::error::Result;
+use crate::fetcher::parse_category_str;
+use crate::state::MarketStore;
+use crate::types::{Market, NegRiskEvent, Price};
+
+/// Rewrites the warm-restart checkpoint every `interval`: each tracked market
+/// (neg-risk members tagged with their event) plus its tokens' cached best prices.
+/// Pinned markets are left to the `PinnedMarketWatcher`, which persists its own
+/// series state in `pinned_markets`.
+pub struct Checkpointer {
+    pool: sqlx::SqlitePool,
+    store: Arc<MarketStore>,
+    interval: Duration,
+}
+
+impl Checkpointer {
+    pub fn new(pool: sqlx::SqlitePool, store: Arc<MarketStore>, interval: Duration) -> Self {
+        Self { pool, store, interval }
+    }
+
+    pub async fn run(self) {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + self.interval, self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        info!("[CHECKPOINT] saving the market set every {:?}", self.interval);
+
+        loop {
+            ticker.tick().await;
+            match self.save().await {
+                Ok(saved) => debug!("[CHECKPOINT] saved {saved} markets"),
+                Err(e) => error!("[CHECKPOINT] save failed: {e}"),
+            }
+        }
+    }
+
+    /// Replaces the checkpoint with the store's current market set in a single
+    /// transaction. Returns the number of markets saved. An empty store (e.g. a
+    /// failed bootstrap) leaves the previous checkpoint in place.
+    pub async fn save(&self) -> Result<usize> {
+        if self.store.market_count() == 0 {
+            return Ok(0);
+        }
+        let saved_at = now_ns() as i64;
+        let mut saved = 0;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!("DELETE FROM checkpoint_markets").execute(&mut *tx).await?;
+        sqlx::query!("DELETE FROM checkpoint_events").execute(&mut *tx).await?;
+
+        let mut markets: Vec<(Market, Option<String>)> = Vec::new();
+        for event_id in self.store.event_ids() {
+            let Some(event) = self.store.event(&event_id) else {
+                continue;
+            };
+            let category = event.category.to_string();
+            sqlx::query!(
+                r#"
+                INSERT INTO checkpoint_events (event_id, title, category, end_date_iso, saved_at)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+                event.id,
+                event.title,
+                category,
+                event.end_date_iso,
+                saved_at,
+            )
+            .execute(&mut *tx)
+            .await?;
+            markets.extend(event.members.iter().map(|m| (m.clone(), Some(event.id.clone()))));
+        }
+        for market_id in self.store.all_market_ids() {
+            if self.store.is_pinned(&market_id) || self.store.is_event_member(&market_id) {
+                continue;
+            }
+            if let Some(market) = self.store.get_market(&market_id) {
+                markets.push((market, None));
+            }
+        }
+
+        for (market, event_id) in &markets {
+            let category = market.category.to_string();
+            let (yes_ask, yes_bid) = self.store.best_prices(&market.yes_token_id).unzip();
+            let (no_ask, no_bid) = self.store.best_prices(&market.no_token_id).unzip();
+            sqlx::query!(
+                r#"
+                INSERT INTO checkpoint_markets (
+                    market_id, event_id, question, category, end_date_iso, total_volume,
+                    yes_token_id, no_token_id, yes_ask, yes_bid, no_ask, no_bid, saved_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                market.id,
+                event_id,
+                market.question,
+                category,
+                market.end_date_iso,
+                market.total_volume,
+                market.yes_token_id,
+                market.no_token_id,
+                yes_ask,
+                yes_bid,
+                no_ask,
+                no_bid,
+                saved_at,
+            )
+            .execute(&mut *tx)
+            .await?;
+            saved += 1;
+        }
+        tx.commit().await?;
+        Ok(saved)
+    }
+}
+
+/// A market read back from the checkpoint, with each token's last-known
+/// `(best_ask, best_bid)`.
+struct SavedMarket {
+    market: Market,
+    event_id: Option<String>,
+    yes: Option<(Price, Price)>,
+    no: Option<(Price, Price)>,
+}
+
+/// The market set as of the last checkpoint.
+pub struct Checkpoint {
+    pub saved_at_ns: u64,
+    markets: Vec<SavedMarket>,
+    /// Neg-risk events without their members, which are in `markets`.
+    events: Vec<NegRiskEvent>,
+}
+
+impl Checkpoint {
+    pub fn market_count(&self) -> usize {
+        self.markets.len()
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Adds the checkpointed markets and events to the store and seeds their
+    /// last-known prices (see `MarketStore::restore_prices`).
+    pub fn restore(self, store: &MarketStore, now_ns: u64) {
+        let mut events: HashMap<String, NegRiskEvent> = self.events.into_iter().map(|e| (e.id.clone(), e)).collect();
+        let mut prices = Vec::with_capacity(self.markets.len());
+        for saved in self.markets {
+            prices.push((saved.market.id.clone(), saved.yes, saved.no));
+            match saved.event_id.and_then(|id| events.get_mut(&id)) {
+                Some(event) => event.members.push(saved.market),
+                None => store.add_market(saved.market),
+            }
+        }
+        for event in events.into_values().filter(|e| !e.members.is_empty()) {
+            store.add_event(event);
+        }
+        for (market_id, yes, no) in prices {
+            store.restore_prices(&market_id, yes, no, now_ns);
+        }
+    }
+}
+
+/// Reads the last checkpoint. Returns None when there is none, or when it is
+/// older than `max_age` (zero accepts any age).
+pub async fn load_checkpoint(pool: &sqlx::SqlitePool, max_age: Duration, now_ns: u64) -> Result<Option<Checkpoint>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT market_id as "market_id!", event_id, question, category, end_date_iso, total_volume,
+               yes_token_id, no_token_id, yes_ask, yes_bid, no_ask, no_bid, saved_at
+        FROM checkpoint_markets
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    let Some(saved_at_ns) = rows.iter().map(|r| r.saved_at as u64).max() else {
+        return Ok(None);
+    };
+    if !max_age.is_zero() && now_ns.saturating_sub(saved_at_ns) > max_age.as_nanos() as u64 {
+        return Ok(None);
+    }
+
+    let prices = |ask: Option<f64>, bid: Option<f64>| Some((Price::from_f64(ask?), Price::from_f64(bid?)));
+    let markets = rows
+        .into_iter()
+        .map(|r| SavedMarket {
+            market: Market {
+                id: r.market_id,
+                question: r.question,
+                category: parse_category_str(&r.category),
+                end_date_iso: r.end_date_iso,
+                total_volume: r.total_volume,
+                yes_token_id: r.yes_token_id,
+                no_token_id: r.no_token_id,
+            },
+            event_id: r.event_id,
+            yes: prices(r.yes_ask, r.yes_bid),
+            no: prices(r.no_ask, r.no_bid),
+        })
+        .collect();
+
+    let events = sqlx::query!(r#"SELECT event_id as "event_id!", title, category, end_date_iso FROM checkpoint_events"#)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| NegRiskEvent {
+            id: r.event_id,
+            title: r.title,
+            category: parse_category_str(&r.category),
+            end_date_iso: r.end_date_iso,
+            members: Vec::new(),
+        })
+        .collect();
+
+    Ok(Some(Checkpoint { saved_at_ns, markets, events }))
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory_pool;
+    use crate::types::Category;
+
+    fn market(id: &str) -> Market {
+        Market {
+            id: id.to_string(),
+            question: format!("Question {id}"),
+            category: Category::Crypto,
+            end_date_iso: None,
+            total_volume: Some(1_000.0),
+            yes_token_id: format!("{id}-yes"),
+            no_token_id: format!("{id}-no"),
+        }
+    }
+
+    #[tokio::test]
+    async fn restores_markets_events_and_prices_but_not_pins() {
+        let store = MarketStore::new();
+        store.add_market(market("plain"));
+        store.add_market(market("pinned"));
+        store.pin_market("pinned", "btc-updown-5m");
+        store.add_event(NegRiskEvent {
+            id: "event1".to_string(),
+            title: "Who wins?".to_string(),
+            category: Category::Politics,
+            end_date_iso: None,
+            members: vec![market("leg1"), market("leg2")],
+        });
+        let px = Price::from_f64;
+        store.apply_book_snapshot("plain-yes", &[(px(0.45), 10.0)], &[(px(0.44), 10.0)]);
+        store.apply_book_snapshot("plain-no", &[(px(0.56), 10.0)], &[(px(0.54), 10.0)]);
+
+        let pool = memory_pool().await;
+        let checkpointer = Checkpointer::new(pool.clone(), Arc::clone(&store), Duration::from_secs(30));
+        assert_eq!(checkpointer.save().await.unwrap(), 3, "the pinned market is left to the watcher");
+        let empty = Checkpointer::new(pool.clone(), MarketStore::new(), Duration::from_secs(30));
+        assert_eq!(empty.save().await.unwrap(), 0, "an empty store keeps the last checkpoint");
+
+        let now = now_ns();
+        let checkpoint = load_checkpoint(&pool, Duration::from_secs(3_600), now).await.unwrap().unwrap();
+        assert_eq!((checkpoint.market_count(), checkpoint.event_count()), (3, 1));
+        assert!(load_checkpoint(&pool, Duration::from_secs(3_600), now + 3_601 * 1_000_000_000).await.unwrap().is_none());
+
+        let restored = MarketStore::new();
+        checkpoint.restore(&restored, now);
+        let mut ids = restored.all_market_ids();
+        ids.sort();
+        assert_eq!(ids, ["leg1", "leg2", "plain"]);
+        assert_eq!(restored.event_member_ids("event1").unwrap().len(), 2);
+        assert_eq!(restored.best_prices("plain-yes"), Some((px(0.45), px(0.44))));
+        assert_eq!(restored.best_prices("leg1-yes"), None);
+        assert!(restored.is_interrupted("plain"), "restored prices aren't live until a fresh snapshot");
+        assert!(!restored.is_hydrated("plain"));
+        assert_eq!(restored.hydrated_market_count(), 0);
+        restored.apply_book_snapshot("plain-yes", &[(px(0.46), 1.0)], &[]);
+        assert!(!restored.is_hydrated("plain"), "the no token still only has its restored price");
+        restored.apply_book_snapshot("plain-no", &[(px(0.55), 1.0)], &[]);
+        assert!(!restored.is_interrupted("plain"));
+        assert!(restored.is_hydrated("plain"));
+    }
+}
@@ -1,2 +1,16 @@
-pub mod models;
+pub mod book_archive;
+pub mod checkpoint;
+pub mod reclassify;
 pub mod writer;
+
+/// Fresh single-connection in-memory database with every migration applied.
+#[cfg(test)]
+pub(crate) async fn memory_pool() -> sqlx::SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
@@ -0,0 +1,154 @@
+use tracing::info;
+
+use crate::detector::classifier::WindowClassifier;
+use crate::error::Result;
+use crate::types::{opportunity_class, CloseReason, ConfirmationRule, WindowObservables};
+
+/// Rows read and rewritten per transaction.
+const BATCH_SIZE: i64 = 5_000;
+
+/// Outcome of one reclassification pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReclassifySummary {
+    /// Closed windows re-run through the classifier.
+    pub scanned: u64,
+    /// Rows whose open_duration_class, close_reason or opportunity_class changed.
+    pub changed: u64,
+}
+
+/// Re-runs `classifier` over the stored observables of every closed window opened
+/// after `since_ns`, rewriting `open_duration_class`, `close_reason`,
+/// `opportunity_class` and `classifier_version`.
+///
+/// Rows written before confirmation rules were recorded are classified against
+/// the default rule. Forced close reasons (feed faults) are kept as stored.
+/// Callers should rescore `market_stats` afterwards.
+pub async fn reclassify_windows(
+    pool: &sqlx::SqlitePool,
+    classifier: &dyn WindowClassifier,
+    since_ns: i64,
+) -> Result<ReclassifySummary> {
+    let version = classifier.id();
+    let mut summary = ReclassifySummary::default();
+    let mut after_id = 0i64;
+
+    loop {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", tick_count, duration_ms, volume_changed, volume_change_ticks, price_shifted,
+                   confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source,
+                   open_duration_class, close_reason, opportunity_class
+            FROM windows
+            WHERE closed_at IS NOT NULL AND opened_at > ? AND id > ?
+            ORDER BY id
+            LIMIT ?
+            "#,
+            since_ns,
+            after_id,
+            BATCH_SIZE,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id;
+
+        let mut tx = pool.begin().await?;
+        for row in &rows {
+            let obs = WindowObservables {
+                tick_count: row.tick_count.unwrap_or(1).max(0) as u32,
+                elapsed_ms: row.duration_ms.unwrap_or(0.0),
+                trade_event_fired: row.volume_changed.unwrap_or(0) != 0,
+                volume_change_ticks: row.volume_change_ticks.unwrap_or(0).max(0) as u32,
+                price_shifted: row.price_shifted.unwrap_or(0) != 0,
+                ..Default::default()
+            };
+            let default = ConfirmationRule::default();
+            let rule = ConfirmationRule {
+                min_ticks: row.confirm_min_ticks.map_or(default.min_ticks, |t| t.max(0) as u32),
+                min_elapsed_ms: row.confirm_min_elapsed_ms.map_or(default.min_elapsed_ms, |m| m.max(0) as u64),
+                source: row.confirm_rule_source.clone().unwrap_or(default.source),
+            };
+
+            let (open_class, mut close_reason) = classifier.classify(&obs, &rule);
+            if let Some(forced) = row.close_reason.as_deref().and_then(CloseReason::forced_from_str) {
+                close_reason = Some(forced);
+            }
+            let opp_class = i64::from(opportunity_class(open_class, close_reason));
+            let open_class = open_class.to_string();
+            let close_reason = close_reason.map(|r| r.to_string());
+
+            let changed = row.open_duration_class.as_deref() != Some(open_class.as_str())
+                || row.close_reason != close_reason
+                || row.opportunity_class != Some(opp_class);
+            if changed {
+                summary.changed += 1;
+            }
+            summary.scanned += 1;
+
+            sqlx::query!(
+                r#"
+                UPDATE windows
+                SET open_duration_class = ?, close_reason = ?, opportunity_class = ?, classifier_version = ?
+                WHERE id = ?
+                "#,
+                open_class,
+                close_reason,
+                opp_class,
+                version,
+                row.id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        info!("[RECLASSIFY] {version}: {} rows scanned, {} changed so far", summary.scanned, summary.changed);
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory_pool;
+    use crate::detector::classifier::{ConfirmationClassifier, TickCountClassifier};
+
+    #[tokio::test]
+    async fn rewrites_classes_with_chosen_classifier() {
+        let pool = memory_pool().await;
+        // 3 ticks over 50ms, held to a 100ms rule: noise under the confirmation
+        // classifier, P4 (order vanished) under the tick-count classifier.
+        sqlx::query(
+            "INSERT INTO windows (market_id, opened_at, closed_at, duration_ms, yes_ask, no_ask, combined_cost,
+                                  spread_size, open_duration_class, close_reason, opportunity_class,
+                                  tick_count, volume_changed, price_shifted,
+                                  confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source)
+             VALUES ('m1', 10, 20, 50.0, 0.45, 0.5, 0.95, 0.05, 'single_tick', NULL, 0, 3, 0, 0, 1, 100, 'category:crypto')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let summary = reclassify_windows(&pool, &TickCountClassifier, 0).await.unwrap();
+        assert_eq!(summary, ReclassifySummary { scanned: 1, changed: 1 });
+        let row: (String, Option<String>, i64, String) = sqlx::query_as(
+            "SELECT open_duration_class, close_reason, opportunity_class, classifier_version FROM windows",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(row, ("multi_tick".into(), Some("order_vanished".into()), 4, "tick_count@v1".into()));
+
+        let summary = reclassify_windows(&pool, &ConfirmationClassifier, 0).await.unwrap();
+        assert_eq!(summary.changed, 1);
+        let class: i64 = sqlx::query_scalar("SELECT opportunity_class FROM windows")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(class, 0);
+    }
+}
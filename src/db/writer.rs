@@ -5,7 +5,7 @@ use tracing::error;
 
 use crate::api::health::HealthState;
 use crate::error::Result;
-use crate::types::{WindowCloseEvent, WindowOpenEvent, WindowEvent};
+use crate::types::{WindowCloseEvent, WindowEvent, WindowOpenEvent, WindowTick};
 
 /// Receives WindowEvents from the detector and persists them to SQLite.
 /// Runs as a dedicated background task — never blocks the detection path.
@@ -49,22 +49,49 @@ impl DbWriter {
     async fn write_window_open(&self, o: &WindowOpenEvent) -> Result<()> {
         let spread_category = o.spread_category.to_string();
         let opened_at = o.opened_at_ns as i64;
-        let combined_cost = o.yes_ask + o.no_ask;
+        let window_kind = o.kind.to_string();
+        let side = o.side.to_string();
+        let confirm_min_ticks = i64::from(o.confirmation.min_ticks);
+        let confirm_min_elapsed_ms = o.confirmation.min_elapsed_ms as i64;
+        let executable_size = o.depth.map(|d| d.size);
+        let executable_cost = o.depth.map(|d| d.vwap_cost);
+        let expected_profit = o.depth.map(|d| d.profit);
+        let net_profit = o.depth.map(|d| d.net_profit);
+        let exchange_opened_at = o.exchange_opened_at_ms.map(|ms| ms as i64);
+        let open_book_depth = o.open_book.map(|b| b.paired_depth(o.side));
 
         sqlx::query!(
             r#"
             INSERT INTO windows (
                 market_id, opened_at, closed_at, duration_ms,
-                yes_ask, no_ask, combined_cost, spread_size, spread_category
-            ) VALUES (?, ?, NULL, NULL, ?, ?, ?, ?, ?)
+                yes_ask, no_ask, combined_cost, spread_size, spread_category,
+                executable_size, executable_cost, expected_profit, net_spread, net_profit,
+                window_kind, side, yes_bid, no_bid,
+                confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, exchange_opened_at,
+                open_book_depth
+            ) VALUES (?, ?, NULL, NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             o.market_id,
             opened_at,
             o.yes_ask,
             o.no_ask,
-            combined_cost,
+            o.combined_cost,
             o.spread,
             spread_category,
+            executable_size,
+            executable_cost,
+            expected_profit,
+            o.net_spread,
+            net_profit,
+            window_kind,
+            side,
+            o.yes_bid,
+            o.no_bid,
+            confirm_min_ticks,
+            confirm_min_elapsed_ms,
+            o.confirmation.source,
+            exchange_opened_at,
+            open_book_depth,
         )
         .execute(&self.pool)
         .await?;
@@ -84,19 +111,40 @@ impl DbWriter {
         let tick_count = w.observables.tick_count as i64;
         let opened_at = w.opened_at_ns as i64;
         let closed_at = w.closed_at_ns as i64;
-        let combined_cost = w.yes_ask + w.no_ask;
+        let window_kind = w.kind.to_string();
+        let side = w.side.to_string();
+        let confirm_min_ticks = i64::from(w.confirmation.min_ticks);
+        let confirm_min_elapsed_ms = w.confirmation.min_elapsed_ms as i64;
+        let obs = &w.observables;
+        let peak_spread_at = obs.peak_spread_at_ns as i64;
+        let exchange_opened_at = w.exchange_opened_at_ms.map(|ms| ms as i64);
+        let exchange_closed_at = w.exchange_closed_at_ms.map(|ms| ms as i64);
+        let open_book_depth = w.open_book.as_ref().map(|b| b.paired_depth(w.side));
+        let close_book_depth = w.close_book.as_ref().map(|b| b.paired_depth(w.side));
 
         let detection_latency_us = w.detection_latency_us as i64;
+        let executable_size = w.depth.map(|d| d.size);
+        let executable_cost = w.depth.map(|d| d.vwap_cost);
+        let expected_profit = w.depth.map(|d| d.profit);
+        let net_profit = w.depth.map(|d| d.net_profit);
 
         // Try to update existing open row first
-        let update_result = sqlx::query!(
+        let updated = sqlx::query!(
             r#"
             UPDATE windows
             SET closed_at = ?, duration_ms = ?, open_duration_class = ?, close_reason = ?,
                 tick_count = ?, volume_changed = ?, volume_change_ticks = ?, price_shifted = ?,
                 opportunity_class = ?, detection_latency_us = ?,
-                yes_ask = ?, no_ask = ?, combined_cost = ?, spread_size = ?, spread_category = ?
-            WHERE market_id = ? AND opened_at = ? AND closed_at IS NULL
+                yes_ask = ?, no_ask = ?, combined_cost = ?, spread_size = ?, spread_category = ?,
+                executable_size = ?, executable_cost = ?, expected_profit = ?,
+                net_spread = ?, net_profit = ?, yes_bid = ?, no_bid = ?,
+                confirm_min_ticks = ?, confirm_min_elapsed_ms = ?, confirm_rule_source = ?,
+                classifier_version = ?,
+                peak_spread = ?, peak_spread_at = ?, closing_spread = ?, twa_spread = ?,
+                min_yes_ask = ?, max_yes_ask = ?, min_no_ask = ?, max_no_ask = ?,
+                exchange_closed_at = ?, open_book_depth = ?, close_book_depth = ?
+            WHERE market_id = ? AND opened_at = ? AND window_kind = ? AND side = ? AND closed_at IS NULL
+            RETURNING id as "id!"
             "#,
             closed_at,
             w.duration_ms,
@@ -110,29 +158,60 @@ impl DbWriter {
             detection_latency_us,
             w.yes_ask,
             w.no_ask,
-            combined_cost,
+            w.combined_cost,
             w.spread,
             spread_category,
+            executable_size,
+            executable_cost,
+            expected_profit,
+            w.net_spread,
+            net_profit,
+            w.yes_bid,
+            w.no_bid,
+            confirm_min_ticks,
+            confirm_min_elapsed_ms,
+            w.confirmation.source,
+            w.classifier_version,
+            obs.peak_spread,
+            peak_spread_at,
+            obs.closing_spread,
+            obs.twa_spread,
+            obs.min_yes_ask,
+            obs.max_yes_ask,
+            obs.min_no_ask,
+            obs.max_no_ask,
+            exchange_closed_at,
+            open_book_depth,
+            close_book_depth,
             w.market_id,
             opened_at,
+            window_kind,
+            side,
         )
-        .execute(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
-        if update_result.rows_affected() > 0 {
-            return Ok(());
+        if let Some(row) = updated {
+            return self.write_window_ticks(row.id, &w.ticks).await;
         }
 
         // Single-tick or missed open: insert full row
-        sqlx::query!(
+        let inserted = sqlx::query!(
             r#"
             INSERT INTO windows (
                 market_id, opened_at, closed_at, duration_ms,
                 yes_ask, no_ask, combined_cost, spread_size, spread_category,
                 open_duration_class, close_reason,
                 tick_count, volume_changed, volume_change_ticks, price_shifted,
-                opportunity_class, detection_latency_us
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                opportunity_class, detection_latency_us,
+                executable_size, executable_cost, expected_profit, net_spread, net_profit,
+                window_kind, side, yes_bid, no_bid,
+                confirm_min_ticks, confirm_min_elapsed_ms, confirm_rule_source, classifier_version,
+                peak_spread, peak_spread_at, closing_spread, twa_spread,
+                min_yes_ask, max_yes_ask, min_no_ask, max_no_ask,
+                exchange_opened_at, exchange_closed_at, open_book_depth, close_book_depth
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                      ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             w.market_id,
             opened_at,
@@ -140,7 +219,7 @@ impl DbWriter {
             w.duration_ms,
             w.yes_ask,
             w.no_ask,
-            combined_cost,
+            w.combined_cost,
             w.spread,
             spread_category,
             open_class,
@@ -151,10 +230,79 @@ impl DbWriter {
             price_shifted,
             opportunity_class,
             detection_latency_us,
+            executable_size,
+            executable_cost,
+            expected_profit,
+            w.net_spread,
+            net_profit,
+            window_kind,
+            side,
+            w.yes_bid,
+            w.no_bid,
+            confirm_min_ticks,
+            confirm_min_elapsed_ms,
+            w.confirmation.source,
+            w.classifier_version,
+            obs.peak_spread,
+            peak_spread_at,
+            obs.closing_spread,
+            obs.twa_spread,
+            obs.min_yes_ask,
+            obs.max_yes_ask,
+            obs.min_no_ask,
+            obs.max_no_ask,
+            exchange_opened_at,
+            exchange_closed_at,
+            open_book_depth,
+            close_book_depth,
         )
         .execute(&self.pool)
         .await?;
 
+        self.write_window_ticks(inserted.last_insert_rowid(), &w.ticks).await
+    }
+
+    /// Persists a closed window's tick trace in one transaction.
+    async fn write_window_ticks(&self, window_id: i64, ticks: &[WindowTick]) -> Result<()> {
+        if ticks.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (seq, t) in ticks.iter().enumerate() {
+            let seq = seq as i64;
+            let at_ns = t.at_ns as i64;
+            let kind = t.kind.to_string();
+            sqlx::query!(
+                r#"
+                INSERT INTO window_ticks (
+                    window_id, seq, at_ns, kind,
+                    yes_ask, no_ask, yes_bid, no_bid,
+                    yes_ask_size, no_ask_size, yes_bid_size, no_bid_size,
+                    spread, trade_asset_id, trade_price
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                window_id,
+                seq,
+                at_ns,
+                kind,
+                t.yes_ask,
+                t.no_ask,
+                t.yes_bid,
+                t.no_bid,
+                t.yes_ask_size,
+                t.no_ask_size,
+                t.yes_bid_size,
+                t.no_bid_size,
+                t.spread,
+                t.trade_asset_id,
+                t.trade_price,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
         Ok(())
     }
 }
@@ -1,21 +1,82 @@
-use crate::types::{CloseReason, OpenDurationClass, WindowObservables};
+use std::sync::Arc;
+
 use crate::config::MIN_ARB_TICKS;
+use crate::types::{CloseReason, ConfirmationRule, OpenDurationClass, WindowObservables};
 
-/// Classify a closing window on both dimensions using stored observables.
-/// Returns (OpenDurationClass, Option<CloseReason>).
-/// CloseReason is None for single_tick windows (not scored).
-pub fn classify(obs: &WindowObservables) -> (OpenDurationClass, Option<CloseReason>) {
-    let open_class = if obs.tick_count < MIN_ARB_TICKS {
-        OpenDurationClass::SingleTick
-    } else {
-        OpenDurationClass::MultiTick
-    };
+/// Name of the classifier used when WINDOW_CLASSIFIER is unset.
+pub const DEFAULT_CLASSIFIER: &str = "confirmation";
+
+/// Classifies a closed window on both dimensions from its stored observables.
+///
+/// Implementations are pure functions of `(obs, rule)` so historical rows can be
+/// re-run through any of them (`scanner reclassify`). Bump `version` whenever an
+/// implementation's output changes for the same inputs.
+pub trait WindowClassifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn version(&self) -> u32;
+    /// Returns (OpenDurationClass, Option<CloseReason>).
+    /// CloseReason is None for single_tick windows (not scored).
+    fn classify(&self, obs: &WindowObservables, rule: &ConfirmationRule) -> (OpenDurationClass, Option<CloseReason>);
+
+    /// Identifier stored in `windows.classifier_version`, e.g. `confirmation@v1`.
+    fn id(&self) -> String {
+        format!("{}@v{}", self.name(), self.version())
+    }
+}
+
+/// Original classifier: fixed MIN_ARB_TICKS threshold, ignores the confirmation rule.
+/// Matches rows written before confirmation rules existed.
+pub struct TickCountClassifier;
+
+impl WindowClassifier for TickCountClassifier {
+    fn name(&self) -> &'static str {
+        "tick_count"
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn classify(&self, obs: &WindowObservables, _rule: &ConfirmationRule) -> (OpenDurationClass, Option<CloseReason>) {
+        let open_class = if obs.tick_count < MIN_ARB_TICKS {
+            OpenDurationClass::SingleTick
+        } else {
+            OpenDurationClass::MultiTick
+        };
+        (open_class, close_reason(open_class, obs))
+    }
+}
+
+/// A window is multi_tick only if it met the same confirmation `rule` the detector
+/// used to confirm it (ticks and/or elapsed time).
+pub struct ConfirmationClassifier;
+
+impl WindowClassifier for ConfirmationClassifier {
+    fn name(&self) -> &'static str {
+        "confirmation"
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
 
+    fn classify(&self, obs: &WindowObservables, rule: &ConfirmationRule) -> (OpenDurationClass, Option<CloseReason>) {
+        let open_class = if rule.is_met(obs.tick_count, obs.elapsed_ms) {
+            OpenDurationClass::MultiTick
+        } else {
+            OpenDurationClass::SingleTick
+        };
+        (open_class, close_reason(open_class, obs))
+    }
+}
+
+/// Dimension 2, shared by all current classifiers.
+fn close_reason(open_class: OpenDurationClass, obs: &WindowObservables) -> Option<CloseReason> {
     if open_class == OpenDurationClass::SingleTick {
-        return (open_class, None);
+        return None;
     }
 
-    let close_reason = if obs.trade_event_fired {
+    let reason = if obs.trade_event_fired {
         if obs.volume_change_ticks > 1 {
             CloseReason::VolumeSpikeGradual
         } else {
@@ -26,54 +87,98 @@ pub fn classify(obs: &WindowObservables) -> (OpenDurationClass, Option<CloseReas
     } else {
         CloseReason::OrderVanished
     };
+    Some(reason)
+}
 
-    (open_class, Some(close_reason))
+/// All registered classifiers.
+pub fn available() -> Vec<Arc<dyn WindowClassifier>> {
+    vec![Arc::new(TickCountClassifier), Arc::new(ConfirmationClassifier)]
+}
+
+/// Look up a classifier by `name` or `name@vN`.
+pub fn by_name(name: &str) -> Option<Arc<dyn WindowClassifier>> {
+    available()
+        .into_iter()
+        .find(|c| c.name() == name || c.id() == name)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn classify(obs: &WindowObservables, rule: &ConfirmationRule) -> (OpenDurationClass, Option<CloseReason>) {
+        ConfirmationClassifier.classify(obs, rule)
+    }
+
     fn obs(tick_count: u32, trade: bool, volume_ticks: u32, price_shifted: bool) -> WindowObservables {
         WindowObservables {
             tick_count,
+            elapsed_ms: 10.0,
             trade_event_fired: trade,
             volume_change_ticks: volume_ticks,
             price_shifted,
+            ..Default::default()
         }
     }
 
     #[test]
     fn single_tick_is_noise() {
-        let (class, reason) = classify(&obs(1, true, 3, true));
+        let (class, reason) = classify(&obs(1, true, 3, true), &ConfirmationRule::default());
         assert_eq!(class, OpenDurationClass::SingleTick);
         assert!(reason.is_none());
     }
 
     #[test]
     fn multi_tick_gradual_spike() {
-        let (class, reason) = classify(&obs(3, true, 2, false));
+        let (class, reason) = classify(&obs(3, true, 2, false), &ConfirmationRule::default());
         assert_eq!(class, OpenDurationClass::MultiTick);
         assert_eq!(reason, Some(CloseReason::VolumeSpikeGradual));
     }
 
     #[test]
     fn multi_tick_instant_spike() {
-        let (class, reason) = classify(&obs(3, true, 1, false));
+        let (class, reason) = classify(&obs(3, true, 1, false), &ConfirmationRule::default());
         assert_eq!(class, OpenDurationClass::MultiTick);
         assert_eq!(reason, Some(CloseReason::VolumeSpikeInstant));
     }
 
     #[test]
     fn multi_tick_price_drift() {
-        let (class, reason) = classify(&obs(4, false, 0, true));
+        let (class, reason) = classify(&obs(4, false, 0, true), &ConfirmationRule::default());
         assert_eq!(class, OpenDurationClass::MultiTick);
         assert_eq!(reason, Some(CloseReason::PriceDrift));
     }
 
+    #[test]
+    fn elapsed_rule_overrides_tick_count() {
+        let rule = ConfirmationRule { min_ticks: 1, min_elapsed_ms: 500, source: "test".to_string() };
+        let mut o = obs(5, false, 0, false);
+        assert_eq!(classify(&o, &rule).0, OpenDurationClass::SingleTick, "5 ticks in 10ms is still noise");
+        o.tick_count = 1;
+        o.elapsed_ms = 750.0;
+        assert_eq!(classify(&o, &rule).0, OpenDurationClass::MultiTick, "one tick held 750ms is real");
+    }
+
+    #[test]
+    fn tick_count_classifier_ignores_rule() {
+        let rule = ConfirmationRule { min_ticks: 1, min_elapsed_ms: 500, source: "test".to_string() };
+        let (class, _) = TickCountClassifier.classify(&obs(3, false, 0, false), &rule);
+        assert_eq!(class, OpenDurationClass::MultiTick);
+        let (class, _) = TickCountClassifier.classify(&obs(1, false, 0, false), &ConfirmationRule::default());
+        assert_eq!(class, OpenDurationClass::SingleTick);
+    }
+
+    #[test]
+    fn lookup_by_name_or_versioned_id() {
+        assert_eq!(by_name("confirmation").unwrap().id(), "confirmation@v1");
+        assert_eq!(by_name("tick_count@v1").unwrap().name(), "tick_count");
+        assert!(by_name("tick_count@v9").is_none());
+        assert!(by_name(DEFAULT_CLASSIFIER).is_some());
+    }
+
     #[test]
     fn multi_tick_order_vanished() {
-        let (class, reason) = classify(&obs(2, false, 0, false));
+        let (class, reason) = classify(&obs(2, false, 0, false), &ConfirmationRule::default());
         assert_eq!(class, OpenDurationClass::MultiTick);
         assert_eq!(reason, Some(CloseReason::OrderVanished));
     }
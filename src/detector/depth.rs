@@ -0,0 +1,171 @@
+use crate::types::{ArbDepth, Price};
+
+/// Walk both ask ladders and find the largest paired size that is still profitable.
+///
+/// `yes_asks`/`no_asks` are `(price, size)` pairs, best (lowest) first — the shape
+/// returned by `MarketStore::ask_ladder`. Buying one YES and one NO share pays out
+/// exactly $1.00, so we keep pairing the cheapest remaining levels while their
+/// combined price plus the taker fee (`fee_bps` on both legs) is strictly below 1.0.
+///
+/// Returns None when not even the top-of-book pair is profitable after fees.
+pub fn walk_ask_ladders(yes_asks: &[(Price, f64)], no_asks: &[(Price, f64)], fee_bps: f64) -> Option<ArbDepth> {
+    walk_paired_levels(yes_asks, no_asks, fee_bps, |combined| Price::ONE - combined)
+}
+
+/// Mirror of `walk_ask_ladders` for the sell-both case.
+///
+/// `yes_bids`/`no_bids` are best (highest) first — the shape returned by
+/// `MarketStore::bid_ladder`. Selling one YES and one NO share is covered by $1.00
+/// of collateral (split into a pair), so we keep pairing while the combined bid
+/// less the taker fee is strictly above 1.0. `vwap_cost` holds the VWAP combined proceeds.
+pub fn walk_bid_ladders(yes_bids: &[(Price, f64)], no_bids: &[(Price, f64)], fee_bps: f64) -> Option<ArbDepth> {
+    walk_paired_levels(yes_bids, no_bids, fee_bps, |combined| combined - Price::ONE)
+}
+
+/// One step of pairing the top of two ladders: `size` shares of each leg at
+/// `yes_price` and `no_price`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairedLevel {
+    pub yes_price: Price,
+    pub no_price: Price,
+    pub size: f64,
+}
+
+impl PairedLevel {
+    pub fn combined(&self) -> Price {
+        self.yes_price + self.no_price
+    }
+}
+
+/// Pairs levels from the top of both ladders, consuming the thinner level each
+/// step, until either ladder runs out. Levels with no size are skipped.
+pub fn paired_levels<'a>(
+    yes_levels: &'a [(Price, f64)],
+    no_levels: &'a [(Price, f64)],
+) -> impl Iterator<Item = PairedLevel> + 'a {
+    let mut yes_iter = yes_levels.iter().copied().filter(|&(_, s)| s > 0.0);
+    let mut no_iter = no_levels.iter().copied().filter(|&(_, s)| s > 0.0);
+    let mut yes_level = yes_iter.next();
+    let mut no_level = no_iter.next();
+
+    std::iter::from_fn(move || {
+        let ((yes_price, yes_size), (no_price, no_size)) = (yes_level?, no_level?);
+        let fill = yes_size.min(no_size);
+        // Consume the filled amount; advance whichever side(s) ran out.
+        yes_level = if yes_size > fill { Some((yes_price, yes_size - fill)) } else { yes_iter.next() };
+        no_level = if no_size > fill { Some((no_price, no_size - fill)) } else { no_iter.next() };
+        Some(PairedLevel { yes_price, no_price, size: fill })
+    })
+}
+
+/// Pairs levels from the top of both ladders while `edge(combined_price)` is
+/// still positive after a taker fee of `fee_bps` on the combined price. `profit`
+/// stays gross; the caller nets it with its `FeeModel`.
+fn walk_paired_levels(
+    yes_levels: &[(Price, f64)],
+    no_levels: &[(Price, f64)],
+    fee_bps: f64,
+    edge: impl Fn(Price) -> Price,
+) -> Option<ArbDepth> {
+    let mut size = 0.0;
+    let mut notional = 0.0;
+    let mut profit = 0.0;
+
+    for level in paired_levels(yes_levels, no_levels) {
+        let combined = level.combined();
+        let per_pair = edge(combined);
+        if per_pair.to_f64() - combined.to_f64() * fee_bps / 10_000.0 <= 0.0 {
+            break;
+        }
+        size += level.size;
+        notional += level.size * combined.to_f64();
+        profit += level.size * per_pair.to_f64();
+    }
+
+    if size <= 0.0 {
+        return None;
+    }
+
+    Some(ArbDepth {
+        size,
+        vwap_cost: notional / size,
+        profit,
+        // Fee-free until the caller applies its FeeModel.
+        net_profit: profit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lv(levels: &[(f64, f64)]) -> Vec<(Price, f64)> {
+        levels.iter().map(|&(p, s)| (Price::from_f64(p), s)).collect()
+    }
+
+    #[test]
+    fn no_arb_at_top_of_book_returns_none() {
+        assert!(walk_ask_ladders(&lv(&[(0.55, 100.0)]), &lv(&[(0.46, 100.0)]), 0.0).is_none());
+        assert!(walk_ask_ladders(&lv(&[(0.50, 100.0)]), &lv(&[(0.50, 100.0)]), 0.0).is_none());
+        assert!(walk_ask_ladders(&lv(&[]), &lv(&[(0.40, 100.0)]), 0.0).is_none());
+    }
+
+    #[test]
+    fn single_level_limited_by_thinner_side() {
+        let depth = walk_ask_ladders(&lv(&[(0.45, 100.0)]), &lv(&[(0.50, 40.0)]), 0.0).unwrap();
+        assert!((depth.size - 40.0).abs() < 1e-9);
+        assert!((depth.vwap_cost - 0.95).abs() < 1e-9);
+        assert!((depth.profit - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn walks_multiple_levels_until_unprofitable() {
+        // Pairs: 0.45+0.50 x50, 0.45+0.52 x50, 0.47+0.52 x30, then 0.47+0.55 >= 1 stops.
+        let yes = [(0.45, 100.0), (0.47, 200.0)];
+        let no = [(0.50, 50.0), (0.52, 80.0), (0.55, 500.0)];
+        let depth = walk_ask_ladders(&lv(&yes), &lv(&no), 0.0).unwrap();
+
+        assert!((depth.size - 130.0).abs() < 1e-9, "size={}", depth.size);
+        let cost = 50.0 * 0.95 + 50.0 * 0.97 + 30.0 * 0.99;
+        assert!((depth.vwap_cost - cost / 130.0).abs() < 1e-9);
+        assert!((depth.profit - (130.0 - cost)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stops_at_the_first_level_fees_make_unprofitable() {
+        // At 200 bps the 0.97 pair still nets 0.03 - 0.0194 per pair; the 0.99 pair
+        // (edge 0.01, fee 0.0198) is fee-negative and ends the walk.
+        let yes = [(0.45, 100.0), (0.47, 200.0)];
+        let no = [(0.50, 50.0), (0.52, 80.0), (0.55, 500.0)];
+        let depth = walk_ask_ladders(&lv(&yes), &lv(&no), 200.0).unwrap();
+        assert!((depth.size - 100.0).abs() < 1e-9, "size={}", depth.size);
+        assert!((depth.profit - (50.0 * 0.05 + 50.0 * 0.03)).abs() < 1e-9);
+        assert!(walk_ask_ladders(&lv(&[(0.49, 10.0)]), &lv(&[(0.50, 10.0)]), 200.0).is_none());
+    }
+
+    #[test]
+    fn bid_walk_pairs_while_combined_above_one() {
+        // 0.55+0.50 x20, 0.55+0.47 x60, then 0.52+0.47 <= 1 stops.
+        let yes = [(0.55, 80.0), (0.52, 100.0)];
+        let no = [(0.50, 20.0), (0.47, 100.0)];
+        let depth = walk_bid_ladders(&lv(&yes), &lv(&no), 0.0).unwrap();
+
+        assert!((depth.size - 80.0).abs() < 1e-9, "size={}", depth.size);
+        let proceeds = 20.0 * 1.05 + 60.0 * 1.02;
+        assert!((depth.vwap_cost - proceeds / 80.0).abs() < 1e-9);
+        assert!((depth.profit - (proceeds - 80.0)).abs() < 1e-9);
+        assert!(walk_bid_ladders(&lv(&[(0.50, 10.0)]), &lv(&[(0.50, 10.0)]), 0.0).is_none());
+    }
+
+    #[test]
+    fn paired_levels_keep_pairing_past_the_profitable_depth() {
+        let yes = lv(&[(0.45, 100.0), (0.47, 200.0)]);
+        let no = lv(&[(0.50, 50.0), (0.0, 0.0), (0.52, 80.0), (0.55, 500.0)]);
+        let steps: Vec<_> = paired_levels(&yes, &no).map(|l| (l.combined(), l.size)).collect();
+        let px = Price::from_f64;
+        assert_eq!(
+            steps,
+            vec![(px(0.95), 50.0), (px(0.97), 50.0), (px(0.99), 30.0), (px(1.02), 170.0)]
+        );
+    }
+}
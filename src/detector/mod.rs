@@ -1,4 +1,7 @@
 pub mod classifier;
+pub mod depth;
+pub mod shard;
 pub mod spread;
 
+pub use shard::ShardRouter;
 pub use spread::SpreadDetector;
@@ -0,0 +1,235 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::api::health::{DetectorShardHealth, HealthState};
+use crate::api::latency::LatencyStats;
+use crate::config::{DetectorConfig, CHANNEL_CAPACITY};
+use crate::detector::SpreadDetector;
+use crate::state::MarketStore;
+use crate::types::{CloseReason, FaultSweep, PriceChangeMsg, TradeMsg, WindowEvent};
+
+/// Channels into one detector shard.
+struct ShardLink {
+    price_tx: mpsc::Sender<PriceChangeMsg>,
+    trade_tx: mpsc::Sender<TradeMsg>,
+    /// Asks the shard to force-close windows now.
+    sweep_tx: mpsc::Sender<FaultSweep>,
+    health: Arc<DetectorShardHealth>,
+}
+
+/// Routes price and trade messages to detector shards by market.
+///
+/// Neg-risk event members are routed by event id, so every leg of a basket is
+/// priced by the same shard; other markets are routed by market id.
+pub struct ShardRouter {
+    store: Arc<MarketStore>,
+    shards: Vec<ShardLink>,
+}
+
+impl ShardRouter {
+    pub fn new(store: Arc<MarketStore>) -> Self {
+        Self { store, shards: Vec::new() }
+    }
+
+    /// Spawns `count` detector tasks that all emit into `window_tx`, and returns the
+    /// router feeding them. Each shard registers its counters with `health`.
+    pub fn spawn(
+        count: usize,
+        store: Arc<MarketStore>,
+        window_tx: mpsc::Sender<WindowEvent>,
+        latency_stats: Arc<LatencyStats>,
+        cfg: DetectorConfig,
+        health: &HealthState,
+    ) -> Self {
+        let mut router = Self::new(Arc::clone(&store));
+        for _ in 0..count.max(1) {
+            let (price_tx, price_rx) = mpsc::channel(CHANNEL_CAPACITY);
+            let (trade_tx, trade_rx) = mpsc::channel(CHANNEL_CAPACITY);
+            let (sweep_tx, sweep_rx) = mpsc::channel(CHANNEL_CAPACITY);
+            let shard_health = router.add_shard(price_tx, trade_tx, sweep_tx);
+            health.add_detector_shard(Arc::clone(&shard_health));
+            let detector = SpreadDetector::new(
+                Arc::clone(&store),
+                price_rx,
+                trade_rx,
+                window_tx.clone(),
+                Arc::clone(&latency_stats),
+                cfg.clone(),
+            )
+            .with_shard(shard_health)
+            .with_fault_sweeps(sweep_rx);
+            tokio::spawn(async move { detector.run().await });
+        }
+        info!("[DETECTOR] {} shard(s) running", router.shard_count());
+        router
+    }
+
+    /// Adds a shard fed by the given channels; returns its counters.
+    pub fn add_shard(
+        &mut self,
+        price_tx: mpsc::Sender<PriceChangeMsg>,
+        trade_tx: mpsc::Sender<TradeMsg>,
+        sweep_tx: mpsc::Sender<FaultSweep>,
+    ) -> Arc<DetectorShardHealth> {
+        let health = Arc::new(DetectorShardHealth::new(self.shards.len()));
+        self.shards.push(ShardLink { price_tx, trade_tx, sweep_tx, health: Arc::clone(&health) });
+        health
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Messages routed but not yet handled, summed over shards.
+    pub fn backlog(&self) -> u64 {
+        self.shards.iter().map(|s| s.health.queue_depth.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Asks every shard to force-close windows on faulted markets without waiting
+    /// for its once-a-second sweep. Each request counts towards `backlog` until
+    /// the shard has swept.
+    pub async fn sweep_faults(&self) {
+        for link in &self.shards {
+            link.health.queue_depth.fetch_add(1, Ordering::Relaxed);
+            if link.sweep_tx.send(FaultSweep::All).await.is_err() {
+                link.health.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Asks the shard owning `market_id` to force-close every window priced off it
+    /// with `reason`. Like price updates, the request is dropped if the shard is
+    /// backed up.
+    pub fn close_market_windows(&self, market_id: Arc<str>, reason: CloseReason) {
+        let Some(link) = self.shards.get(self.shard_for(&market_id)) else {
+            return;
+        };
+        link.health.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = link.sweep_tx.try_send(FaultSweep::Market { market_id, reason }) {
+            link.health.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            link.health.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("sweep channel full (shard {}), dropping request: {e}", link.health.index);
+        }
+    }
+
+    /// Shard index that owns `market_id`.
+    pub fn shard_for(&self, market_id: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        match self.store.event_for_market(market_id) {
+            Some(event) => event.id.hash(&mut hasher),
+            None => market_id.hash(&mut hasher),
+        }
+        (hasher.finish() % self.shards.len().max(1) as u64) as usize
+    }
+
+    pub fn route_price(&self, msg: PriceChangeMsg) {
+        let Some(link) = self.shards.get(self.shard_for(&msg.market_id)) else {
+            return;
+        };
+        // Counted before sending, so the shard can't handle (and decrement) it first.
+        link.health.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = link.price_tx.try_send(msg) {
+            link.health.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            link.health.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("price channel full (shard {}), dropping message: {e}", link.health.index);
+        }
+    }
+
+    /// Routes a trade to the shard owning its token's market; unknown tokens are ignored.
+    pub fn route_trade(&self, msg: TradeMsg) {
+        let Some((_, market_id)) = self.store.token_ref(&msg.asset_id) else {
+            return;
+        };
+        let Some(link) = self.shards.get(self.shard_for(&market_id)) else {
+            return;
+        };
+        link.health.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = link.trade_tx.try_send(msg) {
+            link.health.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            link.health.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("trade channel full (shard {}), dropping message: {e}", link.health.index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Category, Market, NegRiskEvent, Price, TokenQuote};
+    use std::time::Instant;
+
+    fn market(id: &str) -> Market {
+        Market {
+            id: id.to_string(),
+            question: id.to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: format!("{id}-yes"),
+            no_token_id: format!("{id}-no"),
+        }
+    }
+
+    fn price_msg(market_id: &str) -> PriceChangeMsg {
+        PriceChangeMsg {
+            market_id: Arc::from(market_id),
+            quotes: vec![TokenQuote { asset_id: Arc::from(format!("{market_id}-yes")), best_ask: Price::from_f64(0.5), best_bid: Price::from_f64(0.49) }],
+            received_at_ns: 0,
+            exchange_ts_ms: None,
+            received_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn event_members_share_a_shard_and_messages_reach_their_owner() {
+        let store = MarketStore::new();
+        store.add_event(NegRiskEvent {
+            id: "event1".to_string(),
+            title: "Who wins?".to_string(),
+            category: Category::Politics,
+            end_date_iso: None,
+            members: (0..8).map(|i| market(&format!("member{i}"))).collect(),
+        });
+        for i in 0..8 {
+            store.add_market(market(&format!("market{i}")));
+        }
+
+        let mut router = ShardRouter::new(store.clone());
+        let mut receivers = Vec::new();
+        let mut healths = Vec::new();
+        for _ in 0..4 {
+            let (price_tx, price_rx) = mpsc::channel(16);
+            let (trade_tx, trade_rx) = mpsc::channel(16);
+            let (sweep_tx, _sweep_rx) = mpsc::channel(1);
+            healths.push(router.add_shard(price_tx, trade_tx, sweep_tx));
+            receivers.push((price_rx, trade_rx));
+        }
+
+        let event_shard = router.shard_for("member0");
+        assert!((1..8).all(|i| router.shard_for(&format!("member{i}")) == event_shard));
+
+        for i in 0..8 {
+            router.route_price(price_msg(&format!("market{i}")));
+        }
+        router.route_trade(TradeMsg { asset_id: "member3-no".to_string(), price: Price::from_f64(0.2), received_at_ns: 0 });
+        router.route_trade(TradeMsg { asset_id: "unknown".to_string(), price: Price::from_f64(0.2), received_at_ns: 0 });
+
+        let mut routed = 0;
+        for (index, (price_rx, trade_rx)) in receivers.iter_mut().enumerate() {
+            while let Ok(msg) = price_rx.try_recv() {
+                assert_eq!(router.shard_for(&msg.market_id), index);
+                routed += 1;
+            }
+            let trades = std::iter::from_fn(|| trade_rx.try_recv().ok()).count();
+            assert_eq!(trades, usize::from(index == event_shard));
+        }
+        assert_eq!(routed, 8);
+        let queued: u64 = healths.iter().map(|h| h.queue_depth.load(Ordering::Relaxed)).sum();
+        assert_eq!(queued, 9);
+    }
+}
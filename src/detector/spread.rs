@@ -1,48 +1,184 @@
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
-use crate::config::MIN_ARB_TICKS;
-use crate::detector::classifier;
+use crate::api::health::DetectorShardHealth;
+use crate::config::{ConfirmationRules, DetectorConfig, FeeModel, DEFAULT_TICK_SIZE, MAX_WINDOW_TICKS};
+use crate::detector::classifier::{self, WindowClassifier};
+use crate::detector::depth::{walk_ask_ladders, walk_bid_ladders};
 use crate::state::MarketStore;
 use crate::types::{
-    opportunity_class, PriceChangeMsg, SpreadCategory, TradeMsg, WindowCloseEvent, WindowEvent,
-    WindowObservables, WindowOpenEvent,
+    opportunity_class, ArbDepth, CloseReason, ConfirmationRule, FaultSweep, MarketBookMetrics, NegRiskEvent, Price, PriceChangeMsg, SpreadCategory, TradeMsg,
+    WindowCloseEvent, WindowEvent, WindowKind, WindowObservables, WindowOpenEvent, WindowSide, WindowTick,
+    WindowTickKind,
 };
 
+/// Identifies one window state machine: one side of a binary market, or one
+/// basket of a neg-risk event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WindowKey {
+    /// market_id for binary windows, Gamma event id for neg-risk windows.
+    id: Arc<str>,
+    kind: WindowKind,
+    side: WindowSide,
+}
+
+impl WindowKey {
+    fn binary(market_id: Arc<str>, side: WindowSide) -> Self {
+        Self { id: market_id, kind: WindowKind::Binary, side }
+    }
+}
+
+/// One evaluated tick fed into the window state machine.
+#[derive(Default)]
+struct ArbTick {
+    yes_ask: Price,
+    no_ask: Price,
+    yes_bid: Price,
+    no_bid: Price,
+    /// Basket cost (buy) or proceeds (sell).
+    combined: Price,
+    spread: Price,
+    net_spread: f64,
+    depth: Option<ArbDepth>,
+    is_arb: bool,
+}
+
+impl ArbTick {
+    /// The two prices the window trades against: asks when buying, bids when selling.
+    fn legs(&self, side: WindowSide) -> (Price, Price) {
+        match side {
+            WindowSide::Buy => (self.yes_ask, self.no_ask),
+            WindowSide::Sell => (self.yes_bid, self.no_bid),
+        }
+    }
+}
+
+/// Running spread statistics over a window's arb ticks.
+struct SpreadProfile {
+    peak: Price,
+    peak_at_ns: u64,
+    last: Price,
+    last_at_ns: u64,
+    /// Σ spread·Δt (ticks·ns) over completed arb-tick intervals.
+    weighted_sum: i128,
+    min_yes_ask: Price,
+    max_yes_ask: Price,
+    min_no_ask: Price,
+    max_no_ask: Price,
+}
+
+impl SpreadProfile {
+    fn new(spread: Price, yes_ask: Price, no_ask: Price, at_ns: u64) -> Self {
+        Self {
+            peak: spread,
+            peak_at_ns: at_ns,
+            last: spread,
+            last_at_ns: at_ns,
+            weighted_sum: 0,
+            min_yes_ask: yes_ask,
+            max_yes_ask: yes_ask,
+            min_no_ask: no_ask,
+            max_no_ask: no_ask,
+        }
+    }
+
+    fn observe(&mut self, spread: Price, yes_ask: Price, no_ask: Price, at_ns: u64) {
+        self.weighted_sum += i128::from(self.last.ticks()) * i128::from(at_ns.saturating_sub(self.last_at_ns));
+        self.last = spread;
+        self.last_at_ns = at_ns;
+        if spread > self.peak {
+            self.peak = spread;
+            self.peak_at_ns = at_ns;
+        }
+        self.min_yes_ask = self.min_yes_ask.min(yes_ask);
+        self.max_yes_ask = self.max_yes_ask.max(yes_ask);
+        self.min_no_ask = self.min_no_ask.min(no_ask);
+        self.max_no_ask = self.max_no_ask.max(no_ask);
+    }
+
+    /// Time-weighted spread from `opened_at_ns` to `closed_at_ns`; the last arb
+    /// tick's spread holds until close. Zero-length windows use the last spread.
+    fn time_weighted(&self, opened_at_ns: u64, closed_at_ns: u64) -> f64 {
+        let span = closed_at_ns.saturating_sub(opened_at_ns);
+        if span == 0 {
+            return self.last.to_f64();
+        }
+        let tail = i128::from(self.last.ticks()) * i128::from(closed_at_ns.saturating_sub(self.last_at_ns));
+        (self.weighted_sum + tail) as f64 / span as f64 / Price::SCALE as f64
+    }
+}
+
 /// Tracks state for a currently open arbitrage window.
 struct ActiveWindow {
-    yes_ask: f64,
-    no_ask: f64,
-    spread: f64,
+    yes_ask: Price,
+    no_ask: Price,
+    yes_bid: Price,
+    no_bid: Price,
+    combined: Price,
+    spread: Price,
+    net_spread: f64,
     opened_at_ns: u64,
-    opened_at: Instant,
+    exchange_opened_at_ms: Option<u64>,
+    /// Exchange timestamp of the latest arb tick; the close time of a forced close.
+    last_exchange_ts_ms: Option<u64>,
     tick_count: u32,
-    /// Previous ask to detect gradual price drift
-    prev_yes_ask: f64,
-    prev_no_ask: f64,
+    /// Previous traded-side prices (asks for buy, bids for sell) to detect gradual price drift
+    prev_yes: Price,
+    prev_no: Price,
     trade_event_fired: bool,
     volume_change_ticks: u32,
     /// Count of ticks where price moved (not just opened)
     price_shift_ticks: u32,
-    /// Whether we're currently in a 2-tick pending state before the window is "open"
+    /// Whether the window is still pending confirmation before it is "open"
     pending: bool,
+    /// Executable depth captured on the opening tick.
+    open_depth: Option<ArbDepth>,
+    /// Most profitable executable depth seen on any arb tick.
+    best_depth: Option<ArbDepth>,
+    /// Both legs' book metrics on the opening tick (binary windows only).
+    open_book: Option<MarketBookMetrics>,
+    /// Confirmation rule resolved when the window opened; also drives classification.
+    rule: ConfirmationRule,
+    /// Tick trace (opening tick first), capped at MAX_WINDOW_TICKS.
+    ticks: Vec<WindowTick>,
+    /// Peak / closing / time-weighted spread over the arb ticks.
+    profile: SpreadProfile,
+}
+
+impl ActiveWindow {
+    fn record(&mut self, tick: WindowTick) {
+        if self.ticks.len() < MAX_WINDOW_TICKS {
+            self.ticks.push(tick);
+        }
+    }
 }
 
 pub struct SpreadDetector {
     store: Arc<MarketStore>,
     price_rx: mpsc::Receiver<PriceChangeMsg>,
     trade_rx: mpsc::Receiver<TradeMsg>,
+    /// On-demand fault sweeps, on top of the once-a-second one.
+    sweep_rx: mpsc::Receiver<FaultSweep>,
     window_tx: mpsc::Sender<WindowEvent>,
     latency_stats: Arc<crate::api::latency::LatencyStats>,
-    /// market_id → active window state
-    active_windows: HashMap<String, ActiveWindow>,
+    /// Taker fee / fixed cost model for net spread.
+    fees: FeeModel,
+    /// Windows open only when the net spread is strictly above this.
+    min_net_spread: f64,
+    /// Per-category / per-pinned-prefix confirmation rules.
+    confirmation: ConfirmationRules,
+    /// Classifier applied to every closing window.
+    classifier: Arc<dyn WindowClassifier>,
+    /// (market or event id, kind, side) → active window state
+    active_windows: HashMap<WindowKey, ActiveWindow>,
     /// Detector-local price cache: asset_id → (best_ask, best_bid).
     /// Ensures spread is computed from prices in strict message order,
     /// avoiding the race where the shared store is updated ahead of us.
-    local_prices: HashMap<String, (f64, f64)>,
+    local_prices: HashMap<Arc<str>, (Price, Price)>,
     /// Count of price_change messages processed (for diagnostics).
     price_msgs_processed: u64,
     /// Whether the 10s readiness snapshot has been logged.
@@ -52,8 +188,10 @@ pub struct SpreadDetector {
     windows_opened: u64,
     windows_closed: u64,
     /// Track tightest spread seen per 30-second diagnostic window.
-    tightest_spread: f64,
+    tightest_spread: Option<Price>,
     last_diag_at: Instant,
+    /// This detector's shard index and /health counters.
+    shard: Arc<DetectorShardHealth>,
 }
 
 impl SpreadDetector {
@@ -63,14 +201,23 @@ impl SpreadDetector {
         trade_rx: mpsc::Receiver<TradeMsg>,
         window_tx: mpsc::Sender<WindowEvent>,
         latency_stats: Arc<crate::api::latency::LatencyStats>,
+        cfg: DetectorConfig,
     ) -> Self {
+        let DetectorConfig { fees, min_net_spread, confirmation, classifier } = cfg;
+        let classifier = classifier::by_name(&classifier)
+            .unwrap_or_else(|| Arc::new(classifier::ConfirmationClassifier));
         let now = Instant::now();
         Self {
             store,
             price_rx,
             trade_rx,
+            sweep_rx: mpsc::channel(1).1,
             window_tx,
             latency_stats,
+            fees,
+            min_net_spread,
+            confirmation,
+            classifier,
             active_windows: HashMap::new(),
             local_prices: HashMap::new(),
             price_msgs_processed: 0,
@@ -78,22 +225,59 @@ impl SpreadDetector {
             started_at: now,
             windows_opened: 0,
             windows_closed: 0,
-            tightest_spread: f64::NEG_INFINITY,
+            tightest_spread: None,
             last_diag_at: now,
+            shard: Arc::new(DetectorShardHealth::new(0)),
         }
     }
 
+    /// Runs this detector as one shard of a `ShardRouter`, reporting into `shard`.
+    pub fn with_shard(mut self, shard: Arc<DetectorShardHealth>) -> Self {
+        self.shard = shard;
+        self
+    }
+
+    /// Force-closes windows whenever `sweep_rx` receives (see
+    /// `ShardRouter::sweep_faults` and `ShardRouter::close_market_windows`).
+    pub fn with_fault_sweeps(mut self, sweep_rx: mpsc::Receiver<FaultSweep>) -> Self {
+        self.sweep_rx = sweep_rx;
+        self
+    }
+
     pub async fn run(mut self) {
+        let mut rate_interval = tokio::time::interval(Duration::from_secs(1));
+        let mut handled_at_last_tick = 0u64;
+
         loop {
             tokio::select! {
-                Some(msg) = self.price_rx.recv() => {
+                msg = self.price_rx.recv() => {
+                    // The router owns the senders; a closed price channel means shutdown.
+                    let Some(msg) = msg else { break };
                     self.handle_price_change(msg).await;
+                    // Decremented only once handled, so a zero backlog means the shard is idle.
+                    self.shard.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                    self.shard.msgs_processed.fetch_add(1, Ordering::Relaxed);
                     self.maybe_log_readiness();
                 }
                 Some(trade) = self.trade_rx.recv() => {
                     self.handle_trade(trade);
+                    self.shard.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                    self.shard.msgs_processed.fetch_add(1, Ordering::Relaxed);
+                }
+                Some(sweep) = self.sweep_rx.recv() => {
+                    match sweep {
+                        FaultSweep::All => self.close_faulted_windows().await,
+                        FaultSweep::Market { market_id, reason } => self.close_market_windows(&market_id, reason).await,
+                    }
+                    self.shard.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                }
+                _ = rate_interval.tick() => {
+                    let handled = self.shard.msgs_processed.load(Ordering::Relaxed);
+                    self.shard.msgs_per_sec.store(handled - handled_at_last_tick, Ordering::Relaxed);
+                    handled_at_last_tick = handled;
+                    // Silent or disconnected markets never send the tick that would close their windows.
+                    self.close_faulted_windows().await;
                 }
-                else => break,
             }
         }
     }
@@ -109,17 +293,22 @@ impl SpreadDetector {
         }
         self.startup_logged = true;
 
+        let shard = self.shard.index;
         let total_markets = self.store.market_count();
         let hydrated = self.store.hydrated_market_count();
         info!(
+            shard,
             total_markets,
             hydrated_markets = hydrated,
             price_msgs_processed = self.price_msgs_processed,
-            "[DETECTOR] 10s readiness: {hydrated}/{total_markets} markets have both sides populated | {processed} price msgs processed",
+            "[DETECTOR:{shard}] 10s readiness: {hydrated}/{total_markets} markets have both sides populated | {processed} price msgs processed",
             processed = self.price_msgs_processed,
         );
 
-        self.log_hydration_audit();
+        // The audit reads the shared store, so one shard is enough.
+        if shard == 0 {
+            self.log_hydration_audit();
+        }
     }
 
     /// One-shot audit at startup: dump full price info for up to 5 hydrated markets.
@@ -135,6 +324,7 @@ impl SpreadDetector {
                 if !seen_markets.insert(market_id.clone()) { continue; }
                 count += 1;
 
+                let (yes_ask, no_ask, yes_bid, no_bid) = (yes_ask.to_f64(), no_ask.to_f64(), yes_bid.to_f64(), no_bid.to_f64());
                 let combined_ask = yes_ask + no_ask;
                 let combined_bid = yes_bid + no_bid;
                 let yes_mid = (yes_ask + yes_bid) / 2.0;
@@ -162,22 +352,25 @@ impl SpreadDetector {
         if self.last_diag_at.elapsed() < Duration::from_secs(30) {
             return;
         }
-        let tightest = self.tightest_spread;
-        self.tightest_spread = f64::NEG_INFINITY;
+        let tightest = self.tightest_spread.take().map_or(f64::NEG_INFINITY, Price::to_f64);
         self.last_diag_at = Instant::now();
 
+        let shard = self.shard.index;
         info!(
+            shard,
             price_msgs = self.price_msgs_processed,
             opened = self.windows_opened,
             closed = self.windows_closed,
             active = self.active_windows.len(),
             tightest_spread = format_args!("{tightest:.4}"),
-            "[DETECTOR] 30s diag | msgs={} open={} close={} active={} tightest_spread={:.4}",
+            "[DETECTOR:{shard}] 30s diag | msgs={} open={} close={} active={} tightest_spread={:.4}",
             self.price_msgs_processed, self.windows_opened, self.windows_closed,
             self.active_windows.len(), tightest,
         );
 
-        self.log_sample_market_breakdown();
+        if shard == 0 {
+            self.log_sample_market_breakdown();
+        }
     }
 
     /// Logs a full price breakdown for a sample hydrated market so we can
@@ -186,6 +379,7 @@ impl SpreadDetector {
         let ids = self.store.all_asset_ids();
         for asset_id in ids.iter().take(20) {
             if let Some((market_id, yes_ask, no_ask, yes_bid, no_bid)) = self.store.get_spread_inputs(asset_id) {
+                let (yes_ask, no_ask, yes_bid, no_bid) = (yes_ask.to_f64(), no_ask.to_f64(), yes_bid.to_f64(), no_bid.to_f64());
                 let combined_ask = yes_ask + no_ask;
                 let yes_mid = (yes_ask + yes_bid) / 2.0;
                 let no_mid = (no_ask + no_bid) / 2.0;
@@ -209,123 +403,279 @@ impl SpreadDetector {
         self.price_msgs_processed += 1;
 
         // Update detector-local price cache (strict message order — no store race).
-        self.local_prices.insert(msg.asset_id.clone(), (msg.best_ask, msg.best_bid));
+        // The whole frame is already applied, so both legs update before evaluation.
+        for q in &msg.quotes {
+            self.local_prices.insert(Arc::clone(&q.asset_id), (q.best_ask, q.best_bid));
+        }
 
         // Look up market structure (immutable metadata, no price read).
-        let Some((market_id, yes_token_id, no_token_id)) = self.store.get_market_for_token(&msg.asset_id) else {
+        let market_id: &str = &msg.market_id;
+        let Some((yes_token_id, no_token_id)) = self.store.market_tokens(market_id) else {
             debug!(
-                asset_id = %msg.asset_id,
-                "market lookup failed: token not in store"
+                market_id = %market_id,
+                "market lookup failed: market not in store"
             );
             return;
         };
 
+        // A stale, interrupted or corrupt-book market's legs can't be trusted: no windows
+        // open or step until its feed recovers (its open windows are force-closed by the sweep).
+        if self.store.feed_fault(market_id).is_some() {
+            return;
+        }
+
         // Read both sides from local cache only — counterpart must have been
         // received through the channel before we can compute a spread.
-        let Some(&(yes_ask, _)) = self.local_prices.get(&yes_token_id) else {
+        let Some(&(yes_ask, yes_bid)) = self.local_prices.get(&*yes_token_id) else {
             return;
         };
-        let Some(&(no_ask, _)) = self.local_prices.get(&no_token_id) else {
+        let Some(&(no_ask, no_bid)) = self.local_prices.get(&*no_token_id) else {
             return;
         };
 
-        if yes_ask <= 0.0 || no_ask <= 0.0 {
-            return;
-        }
+        let detect_elapsed = msg.received_at.elapsed();
+        self.latency_stats.record(detect_elapsed);
+
+        // Buy-both at the asks and sell-both at the bids run as independent windows.
+        for side in [WindowSide::Buy, WindowSide::Sell] {
+            let mut tick = ArbTick {
+                yes_ask,
+                no_ask,
+                yes_bid,
+                no_bid,
+                combined: Price::ZERO,
+                spread: Price::ZERO,
+                net_spread: 0.0,
+                depth: None,
+                is_arb: false,
+            };
+            let (yes_px, no_px) = tick.legs(side);
+            if !yes_px.is_positive() || !no_px.is_positive() {
+                // An empty leg means the arb is gone: close any open window on this side.
+                self.step_window(WindowKey::binary(Arc::clone(&msg.market_id), side), tick, &msg, detect_elapsed).await;
+                continue;
+            }
+            self.price_binary_tick(&mut tick, side, market_id, &yes_token_id, &no_token_id);
 
-        let combined = yes_ask + no_ask;
-        let spread = 1.0 - combined;
-        let is_arb = spread > 0.0;
-        let in_window = self.active_windows.contains_key(&market_id);
+            // Track tightest spread for periodic diagnostics.
+            if self.tightest_spread.is_none_or(|tightest| tick.spread > tightest) {
+                self.tightest_spread = Some(tick.spread);
+            }
+
+            // Every tick at debug level — use LOG_LEVEL=debug to see the full feed.
+            let id_short = if market_id.len() > 12 { &market_id[..12] } else { market_id };
+            let (combined, spread) = (tick.combined, tick.spread);
+            if tick.is_arb {
+                debug!(
+                    "\x1b[32m ARB  | {id_short} ({side}) | yes={yes_px:.4} no={no_px:.4} | combined={combined:.4} spread=+{spread:.4} | {latency}us\x1b[0m",
+                    latency = detect_elapsed.as_micros(),
+                );
+            } else if side == WindowSide::Buy {
+                debug!(
+                    " TICK | {id_short} | yes={yes_px:.4} no={no_px:.4} | combined={combined:.4} spread={spread:.4} | {latency}us",
+                    latency = detect_elapsed.as_micros(),
+                );
+            }
 
-        // Track tightest spread for periodic diagnostics.
-        if spread > self.tightest_spread {
-            self.tightest_spread = spread;
+            self.step_window(WindowKey::binary(Arc::clone(&msg.market_id), side), tick, &msg, detect_elapsed).await;
         }
         self.maybe_log_diagnostics();
 
-        let detect_elapsed = msg.received_at.elapsed();
-        self.latency_stats.record(detect_elapsed);
+        // Neg-risk member markets also feed their event-level windows.
+        if let Some(event) = self.store.event_for_market(market_id) {
+            self.evaluate_event(&event, &msg, detect_elapsed).await;
+        }
+    }
 
-        // Every tick at debug level — use LOG_LEVEL=debug to see the full feed.
-        let mid = &market_id;
-        let id_short = if mid.len() > 12 { &mid[..12] } else { mid };
-        if is_arb {
-            debug!(
-                "\x1b[32m ARB  | {id_short} | yes={yes_ask:.4} no={no_ask:.4} | combined={combined:.4} spread=+{spread:.4} | {latency}us\x1b[0m",
-                latency = detect_elapsed.as_micros(),
-            );
+    /// Fills in combined price, spread, fees and depth for one side of a binary market.
+    fn price_binary_tick(
+        &self,
+        tick: &mut ArbTick,
+        side: WindowSide,
+        market_id: &str,
+        yes_token_id: &str,
+        no_token_id: &str,
+    ) {
+        let (yes_px, no_px) = tick.legs(side);
+        let combined = (yes_px + no_px).round_to(self.store.tick_size(market_id));
+        let spread = match side {
+            WindowSide::Buy => Price::ONE - combined,
+            WindowSide::Sell => combined - Price::ONE,
+        };
+
+        // Fixed costs only lower the net spread, so the ladders are only worth
+        // walking once the spread net of taker fees clears the threshold.
+        let fee_bps = self.fees.taker_fee_bps(market_id, self.store.market_category(market_id));
+        let fee_net = self.fees.net_spread(spread.to_f64(), combined.to_f64(), fee_bps, None);
+        let (net_spread, depth) = if fee_net > self.min_net_spread {
+            let depth = self.executable_depth(side, yes_token_id, no_token_id, fee_bps).map(|mut d| {
+                d.net_profit = self.fees.net_profit(d.profit, d.size * d.vwap_cost, fee_bps);
+                d
+            });
+            let net = self.fees.net_spread(spread.to_f64(), combined.to_f64(), fee_bps, depth.map(|d| d.size));
+            (net, depth)
         } else {
-            debug!(
-                " TICK | {id_short} | yes={yes_ask:.4} no={no_ask:.4} | combined={combined:.4} spread={spread:.4} | {latency}us",
-                latency = detect_elapsed.as_micros(),
-            );
+            (fee_net, None)
+        };
+
+        tick.combined = combined;
+        tick.spread = spread;
+        tick.net_spread = net_spread;
+        tick.depth = depth;
+        tick.is_arb = net_spread > self.min_net_spread;
+    }
+
+    /// Evaluates both baskets of a neg-risk event from the local price cache:
+    /// - YES basket: buy YES on every member, exactly one pays $1 → spread = 1 - ΣYES
+    /// - NO basket: buy NO on every member, all but one pay $1 → spread = (N-1) - ΣNO
+    async fn evaluate_event(&mut self, event: &NegRiskEvent, msg: &PriceChangeMsg, detect_elapsed: Duration) {
+        let mut sum_yes = Price::ZERO;
+        let mut sum_no = Price::ZERO;
+        let mut sum_yes_bid = Price::ZERO;
+        let mut sum_no_bid = Price::ZERO;
+        for member in &event.members {
+            if self.store.feed_fault(&member.id).is_some() {
+                return;
+            }
+            let Some(&(yes_ask, yes_bid)) = self.local_prices.get(member.yes_token_id.as_str()) else {
+                return;
+            };
+            let Some(&(no_ask, no_bid)) = self.local_prices.get(member.no_token_id.as_str()) else {
+                return;
+            };
+            if !yes_ask.is_positive() || !no_ask.is_positive() {
+                // A member with no asks leaves neither basket buyable: close any open one.
+                for kind in [WindowKind::NegRiskYes, WindowKind::NegRiskNo] {
+                    let key = WindowKey { id: Arc::from(event.id.as_str()), kind, side: WindowSide::Buy };
+                    self.step_window(key, ArbTick::default(), msg, detect_elapsed).await;
+                }
+                return;
+            }
+            sum_yes += yes_ask;
+            sum_no += no_ask;
+            sum_yes_bid += yes_bid;
+            sum_no_bid += no_bid;
+        }
+
+        let legs = event.members.len() as i64;
+        let fee_bps = self.fees.taker_fee_bps(&event.id, Some(event.category));
+        let tick_size = event
+            .members
+            .iter()
+            .map(|m| self.store.tick_size(&m.id))
+            .min()
+            .unwrap_or(DEFAULT_TICK_SIZE);
+        let baskets = [
+            (WindowKind::NegRiskYes, sum_yes.round_to(tick_size), Price::ONE),
+            (WindowKind::NegRiskNo, sum_no.round_to(tick_size), Price::ONE * (legs - 1)),
+        ];
+        for (kind, cost, payout) in baskets {
+            let spread = payout - cost;
+            let net_spread = self.fees.net_spread(spread.to_f64(), cost.to_f64(), fee_bps, None);
+            let tick = ArbTick {
+                yes_ask: sum_yes,
+                no_ask: sum_no,
+                yes_bid: sum_yes_bid,
+                no_bid: sum_no_bid,
+                combined: cost,
+                spread,
+                net_spread,
+                depth: None,
+                is_arb: net_spread > self.min_net_spread,
+            };
+            let key = WindowKey { id: Arc::from(event.id.as_str()), kind, side: WindowSide::Buy };
+            self.step_window(key, tick, msg, detect_elapsed).await;
         }
+    }
+
+    /// Advances the open/confirm/close state machine for one window key.
+    async fn step_window(
+        &mut self,
+        key: WindowKey,
+        tick: ArbTick,
+        msg: &PriceChangeMsg,
+        detect_elapsed: Duration,
+    ) {
+        let in_window = self.active_windows.contains_key(&key);
+        let id_short = if key.id.len() > 12 { &key.id[..12] } else { &key.id };
+        let (kind, side) = (key.kind, key.side);
+        let (yes_px, no_px) = tick.legs(side);
+        let trace = (tick.is_arb || in_window).then(|| self.trace_tick(&key, &tick, msg.received_at_ns));
+        let ArbTick { yes_ask, no_ask, yes_bid, no_bid, combined, spread, net_spread, depth, is_arb } = tick;
 
         match (is_arb, in_window) {
             (true, false) => {
                 info!(
-                    "\x1b[32;1m>>> WINDOW OPENING | {id_short} | yes={yes_ask:.4} no={no_ask:.4} | spread=+{spread:.4}\x1b[0m",
+                    "\x1b[32;1m>>> WINDOW OPENING | {id_short} ({kind}/{side}) | yes={yes_px:.4} no={no_px:.4} | spread=+{spread:.4}\x1b[0m",
                 );
-                self.active_windows.insert(market_id.clone(), ActiveWindow {
+                let rule = self.confirmation_rule(&key);
+                for market_id in self.window_markets(&key) {
+                    self.store.note_window_opened(&market_id);
+                }
+                self.active_windows.insert(key.clone(), ActiveWindow {
                     yes_ask,
                     no_ask,
+                    yes_bid,
+                    no_bid,
+                    combined,
                     spread,
+                    net_spread,
                     opened_at_ns: msg.received_at_ns,
-                    opened_at: msg.received_at,
+                    exchange_opened_at_ms: msg.exchange_ts_ms,
+                    last_exchange_ts_ms: msg.exchange_ts_ms,
                     tick_count: 1,
-                    prev_yes_ask: yes_ask,
-                    prev_no_ask: no_ask,
+                    prev_yes: yes_px,
+                    prev_no: no_px,
                     trade_event_fired: false,
                     volume_change_ticks: 0,
                     price_shift_ticks: 0,
                     pending: true,
+                    open_depth: depth,
+                    best_depth: depth,
+                    open_book: self.book_metrics(&key),
+                    rule,
+                    ticks: trace.into_iter().collect(),
+                    profile: SpreadProfile::new(spread, yes_ask, no_ask, msg.received_at_ns),
                 });
+                // A one-tick, zero-elapsed rule confirms on the opening tick itself.
+                self.maybe_confirm(&key, msg.received_at_ns);
             }
 
             (true, true) => {
-                let window = self.active_windows.get_mut(&market_id).unwrap();
+                let window = self.active_windows.get_mut(&key).unwrap();
                 window.tick_count += 1;
+                window.record(trace.expect("trace is built for active windows"));
+                window.profile.observe(spread, yes_ask, no_ask, msg.received_at_ns);
+                window.last_exchange_ts_ms = msg.exchange_ts_ms.or(window.last_exchange_ts_ms);
 
-                // Detect gradual price drift: ask moved since last tick
-                let yes_drifted = (yes_ask - window.prev_yes_ask).abs() > 1e-6;
-                let no_drifted = (no_ask - window.prev_no_ask).abs() > 1e-6;
-                if yes_drifted || no_drifted {
+                // Detect gradual price drift: traded-side price moved since last tick
+                if yes_px != window.prev_yes || no_px != window.prev_no {
                     window.price_shift_ticks += 1;
                 }
-                window.prev_yes_ask = yes_ask;
-                window.prev_no_ask = no_ask;
-
-                // Confirm window open once we hit MIN_ARB_TICKS
-                if window.pending && window.tick_count >= MIN_ARB_TICKS {
-                    window.pending = false;
-                    self.windows_opened += 1;
-                    let spread_category = SpreadCategory::from_spread(window.spread);
-                    let event = WindowEvent::Open(WindowOpenEvent {
-                        market_id: market_id.clone(),
-                        yes_ask: window.yes_ask,
-                        no_ask: window.no_ask,
-                        spread: window.spread,
-                        spread_category,
-                        opened_at_ns: window.opened_at_ns,
-                        detected_at: window.opened_at,
-                    });
-                    if let Err(e) = self.window_tx.try_send(event) {
-                        warn!("window channel full, dropping open event: {e}");
+                window.prev_yes = yes_px;
+                window.prev_no = no_px;
+
+                if let Some(d) = depth {
+                    if window.best_depth.is_none_or(|best| d.net_profit > best.net_profit) {
+                        window.best_depth = Some(d);
                     }
                 }
+
+                self.maybe_confirm(&key, msg.received_at_ns);
             }
 
             (false, true) => {
                 self.windows_closed += 1;
-                let window = self.active_windows.remove(&market_id).unwrap();
+                let mut window = self.active_windows.remove(&key).unwrap();
+                window.record(trace.expect("trace is built for active windows"));
                 let dur_ms = (msg.received_at_ns.saturating_sub(window.opened_at_ns)) as f64 / 1_000_000.0;
                 let detection_latency_us = detect_elapsed.as_micros().min(u128::from(u64::MAX)) as u64;
                 info!(
-                    "\x1b[31m<<< WINDOW CLOSED  | {id_short} | ticks={} | {dur_ms:.0}ms | spread was +{:.4}\x1b[0m",
+                    "\x1b[31m<<< WINDOW CLOSED  | {id_short} ({kind}/{side}) | ticks={} | {dur_ms:.0}ms | spread was +{:.4}\x1b[0m",
                     window.tick_count, window.spread,
                 );
-                self.emit_close(market_id, window, msg.received_at_ns, detection_latency_us).await;
+                let closed_at = (msg.received_at_ns, msg.exchange_ts_ms);
+                self.emit_close(key, window, closed_at, detection_latency_us, None).await;
             }
 
             (false, false) => {
@@ -334,52 +684,273 @@ impl SpreadDetector {
         }
     }
 
+    /// Builds a price trace entry for `key`. Binary windows add top-of-book sizes
+    /// from the store; neg-risk baskets only carry the summed prices.
+    fn trace_tick(&self, key: &WindowKey, tick: &ArbTick, at_ns: u64) -> WindowTick {
+        let sizes = match key.kind {
+            WindowKind::Binary => self.store.market_tokens(&key.id).map(|(yes, no)| {
+                (self.store.top_sizes(&yes), self.store.top_sizes(&no))
+            }),
+            WindowKind::NegRiskYes | WindowKind::NegRiskNo => None,
+        };
+        let (yes_sizes, no_sizes) = sizes.unwrap_or((None, None));
+        WindowTick {
+            at_ns,
+            kind: WindowTickKind::Price,
+            yes_ask: tick.yes_ask,
+            no_ask: tick.no_ask,
+            yes_bid: tick.yes_bid,
+            no_bid: tick.no_bid,
+            yes_ask_size: yes_sizes.map(|(ask, _)| ask),
+            no_ask_size: no_sizes.map(|(ask, _)| ask),
+            yes_bid_size: yes_sizes.map(|(_, bid)| bid),
+            no_bid_size: no_sizes.map(|(_, bid)| bid),
+            spread: tick.spread,
+            trade_asset_id: None,
+            trade_price: None,
+        }
+    }
+
+    /// Fires the Open event once a pending window meets its confirmation rule.
+    fn maybe_confirm(&mut self, key: &WindowKey, now_ns: u64) {
+        let Some(window) = self.active_windows.get(key) else {
+            return;
+        };
+        let elapsed_ms = now_ns.saturating_sub(window.opened_at_ns) as f64 / 1_000_000.0;
+        if !window.pending || !window.rule.is_met(window.tick_count, elapsed_ms) {
+            return;
+        }
+
+        let tick_size = self.tick_size(key);
+        let window = self.active_windows.get_mut(key).expect("window checked above");
+        window.pending = false;
+        self.windows_opened += 1;
+        let spread_category = SpreadCategory::from_spread(window.spread, tick_size);
+        let event = WindowEvent::Open(WindowOpenEvent {
+            market_id: key.id.to_string(),
+            kind: key.kind,
+            side: key.side,
+            yes_ask: window.yes_ask,
+            no_ask: window.no_ask,
+            yes_bid: window.yes_bid,
+            no_bid: window.no_bid,
+            combined_cost: window.combined,
+            spread: window.spread,
+            net_spread: window.net_spread,
+            spread_category,
+            depth: window.open_depth,
+            open_book: window.open_book,
+            confirmation: window.rule.clone(),
+            opened_at_ns: window.opened_at_ns,
+            exchange_opened_at_ms: window.exchange_opened_at_ms,
+        });
+        if let Err(e) = self.window_tx.try_send(event) {
+            warn!("window channel full, dropping open event: {e}");
+        }
+    }
+
+    /// Resolves the confirmation rule for a new window: pinned prefix, then category,
+    /// then default. Neg-risk baskets use their event's category.
+    fn confirmation_rule(&self, key: &WindowKey) -> ConfirmationRule {
+        let (category, prefix) = match key.kind {
+            WindowKind::Binary => (self.store.market_category(&key.id), self.store.pinned_prefix(&key.id)),
+            WindowKind::NegRiskYes | WindowKind::NegRiskNo => (self.store.event(&key.id).map(|e| e.category), None),
+        };
+        self.confirmation.rule_for(category, prefix.as_deref()).clone()
+    }
+
+    /// Size the opportunity against the store's full ladders for both tokens:
+    /// asks for buy-side windows, bids for sell-side windows. The walk stops at the
+    /// first level the taker fee makes unprofitable.
+    fn executable_depth(&self, side: WindowSide, yes_token_id: &str, no_token_id: &str, fee_bps: f64) -> Option<ArbDepth> {
+        match side {
+            WindowSide::Buy => {
+                let yes_asks = self.store.ask_ladder(yes_token_id)?;
+                let no_asks = self.store.ask_ladder(no_token_id)?;
+                walk_ask_ladders(&yes_asks, &no_asks, fee_bps)
+            }
+            WindowSide::Sell => {
+                let yes_bids = self.store.bid_ladder(yes_token_id)?;
+                let no_bids = self.store.bid_ladder(no_token_id)?;
+                walk_bid_ladders(&yes_bids, &no_bids, fee_bps)
+            }
+        }
+    }
+
     fn handle_trade(&mut self, trade: TradeMsg) {
-        if let Some((market_id, _, _)) = self.store.get_market_for_token(&trade.asset_id) {
-            if let Some(window) = self.active_windows.get_mut(&market_id) {
+        let Some((_, market_id)) = self.store.token_ref(&trade.asset_id) else {
+            return;
+        };
+
+        // A trade counts toward both sides of the market, and toward both
+        // event-level baskets when it is a neg-risk member.
+        let mut keys = vec![
+            WindowKey::binary(market_id.clone(), WindowSide::Buy),
+            WindowKey::binary(market_id.clone(), WindowSide::Sell),
+        ];
+        if let Some(event) = self.store.event_for_market(&market_id) {
+            for kind in [WindowKind::NegRiskYes, WindowKind::NegRiskNo] {
+                keys.push(WindowKey { id: Arc::from(event.id.as_str()), kind, side: WindowSide::Buy });
+            }
+        }
+
+        for key in &keys {
+            if let Some(window) = self.active_windows.get_mut(key) {
                 if !window.trade_event_fired {
                     window.trade_event_fired = true;
                     window.volume_change_ticks = 1;
                 } else {
                     window.volume_change_ticks += 1;
                 }
+                // Trade ticks repeat the last observed prices alongside the trade.
+                if let Some(last) = window.ticks.last() {
+                    let tick = WindowTick {
+                        at_ns: trade.received_at_ns,
+                        kind: WindowTickKind::Trade,
+                        trade_asset_id: Some(trade.asset_id.clone()),
+                        trade_price: Some(trade.price),
+                        ..last.clone()
+                    };
+                    window.record(tick);
+                }
+            }
+        }
+    }
+
+    /// Book metrics of a binary window's market; neg-risk baskets have none.
+    fn book_metrics(&self, key: &WindowKey) -> Option<MarketBookMetrics> {
+        match key.kind {
+            WindowKind::Binary => self.store.market_book_metrics(&key.id),
+            WindowKind::NegRiskYes | WindowKind::NegRiskNo => None,
+        }
+    }
+
+    /// Tick size a window's prices move in: the finest among its markets.
+    fn tick_size(&self, key: &WindowKey) -> Price {
+        self.window_markets(key)
+            .iter()
+            .map(|m| self.store.tick_size(m))
+            .min()
+            .unwrap_or(DEFAULT_TICK_SIZE)
+    }
+
+    /// Markets whose feeds a window depends on: the market itself, or every event member.
+    fn window_markets(&self, key: &WindowKey) -> Vec<String> {
+        match key.kind {
+            WindowKind::Binary => vec![key.id.to_string()],
+            WindowKind::NegRiskYes | WindowKind::NegRiskNo => {
+                self.store.event_member_ids(&key.id).unwrap_or_default()
             }
         }
     }
 
+    /// Force-closes every window on a market with a feed fault (`FeedInterrupted`,
+    /// `BookCorrupt` or `FeedStale`).
+    async fn close_faulted_windows(&mut self) {
+        let faulted: Vec<(WindowKey, CloseReason)> = self
+            .active_windows
+            .keys()
+            .filter_map(|key| {
+                let reason = self.window_markets(key).iter().find_map(|m| self.store.feed_fault(m))?;
+                Some((key.clone(), reason))
+            })
+            .collect();
+        self.force_close(faulted).await;
+    }
+
+    /// Force-closes every window priced off `market_id` (its own, and its event's
+    /// baskets) with `reason`.
+    async fn close_market_windows(&mut self, market_id: &str, reason: CloseReason) {
+        let affected: Vec<(WindowKey, CloseReason)> = self
+            .active_windows
+            .keys()
+            .filter(|key| self.window_markets(key).iter().any(|m| m == market_id))
+            .map(|key| (key.clone(), reason))
+            .collect();
+        self.force_close(affected).await;
+    }
+
+    /// Closes each window at its last observed arb tick, so the gap is not counted
+    /// as window time.
+    async fn force_close(&mut self, windows: Vec<(WindowKey, CloseReason)>) {
+        for (key, reason) in windows {
+            let window = self.active_windows.remove(&key).unwrap();
+            self.windows_closed += 1;
+            let closed_at = (window.profile.last_at_ns, window.last_exchange_ts_ms);
+            warn!(
+                "[FEED] force-closing {} ({}/{}) after {} ticks — {reason}",
+                key.id, key.kind, key.side, window.tick_count,
+            );
+            self.emit_close(key, window, closed_at, 0, Some(reason)).await;
+        }
+    }
+
+    /// Classifies and sends a Close event closing at `(received ns, exchange ms)`.
+    /// `forced` overrides the classifier's close reason for windows closed by a
+    /// feed fault rather than by a tick.
     async fn emit_close(
         &self,
-        market_id: String,
+        key: WindowKey,
         window: ActiveWindow,
-        closed_at_ns: u64,
+        (closed_at_ns, exchange_closed_at_ms): (u64, Option<u64>),
         detection_latency_us: u64,
+        forced: Option<CloseReason>,
     ) {
+        for market_id in self.window_markets(&key) {
+            self.store.note_window_closed(&market_id);
+        }
         let duration_ms = (closed_at_ns.saturating_sub(window.opened_at_ns)) as f64 / 1_000_000.0;
 
+        let profile = &window.profile;
         let obs = WindowObservables {
             tick_count: window.tick_count,
+            elapsed_ms: duration_ms,
             trade_event_fired: window.trade_event_fired,
             volume_change_ticks: window.volume_change_ticks,
             price_shifted: window.price_shift_ticks > 1,
+            peak_spread: profile.peak,
+            peak_spread_at_ns: profile.peak_at_ns,
+            closing_spread: profile.last,
+            twa_spread: profile.time_weighted(window.opened_at_ns, closed_at_ns),
+            min_yes_ask: profile.min_yes_ask,
+            max_yes_ask: profile.max_yes_ask,
+            min_no_ask: profile.min_no_ask,
+            max_no_ask: profile.max_no_ask,
         };
 
-        let (open_class, close_reason) = classifier::classify(&obs);
+        let (open_class, classified_reason) = self.classifier.classify(&obs, &window.rule);
+        let close_reason = forced.or(classified_reason);
         let opp_class = opportunity_class(open_class, close_reason);
-        let spread_category = SpreadCategory::from_spread(window.spread);
+        let spread_category = SpreadCategory::from_spread(window.spread, self.tick_size(&key));
+        let close_book = self.book_metrics(&key);
 
         let event = WindowEvent::Close(WindowCloseEvent {
-            market_id,
+            market_id: key.id.to_string(),
+            kind: key.kind,
+            side: key.side,
             yes_ask: window.yes_ask,
             no_ask: window.no_ask,
+            yes_bid: window.yes_bid,
+            no_bid: window.no_bid,
+            combined_cost: window.combined,
             spread: window.spread,
+            net_spread: window.net_spread,
             spread_category,
+            depth: window.best_depth,
+            open_book: window.open_book.map(Box::new),
+            close_book: close_book.map(Box::new),
+            confirmation: window.rule,
             opened_at_ns: window.opened_at_ns,
             closed_at_ns,
+            exchange_opened_at_ms: window.exchange_opened_at_ms,
+            exchange_closed_at_ms,
             duration_ms,
             open_duration_class: open_class,
             close_reason,
             opportunity_class: opp_class,
             observables: obs,
+            classifier_version: self.classifier.id(),
+            ticks: window.ticks,
             detection_latency_us,
         });
 
@@ -389,19 +960,20 @@ impl SpreadDetector {
     }
 }
 
-fn now_ns() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos() as u64
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::api::latency::LatencyStats;
     use crate::state::MarketStore;
-    use crate::types::{Category, Market, OpenDurationClass};
+    use crate::types::{Category, Market, OpenDurationClass, TokenQuote};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn now_ns() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64
+    }
 
     fn make_store_with_market() -> Arc<MarketStore> {
         let store = MarketStore::new();
@@ -417,12 +989,24 @@ mod tests {
         store
     }
 
-    fn price_msg(asset_id: &str, best_ask: f64) -> PriceChangeMsg {
+    fn px(value: f64) -> Price {
+        Price::from_f64(value)
+    }
+
+    fn quote(asset_id: &str, best_ask: f64) -> TokenQuote {
+        TokenQuote { asset_id: Arc::from(asset_id), best_ask: px(best_ask), best_bid: px(best_ask - 0.01) }
+    }
+
+    fn price_msg(market_id: &str, asset_id: &str, best_ask: f64) -> PriceChangeMsg {
+        price_msg_at(market_id, asset_id, best_ask, now_ns())
+    }
+
+    fn price_msg_at(market_id: &str, asset_id: &str, best_ask: f64, received_at_ns: u64) -> PriceChangeMsg {
         PriceChangeMsg {
-            asset_id: asset_id.to_string(),
-            best_ask,
-            best_bid: best_ask - 0.01,
-            received_at_ns: now_ns(),
+            market_id: Arc::from(market_id),
+            quotes: vec![quote(asset_id, best_ask)],
+            received_at_ns,
+            exchange_ts_ms: None,
             received_at: Instant::now(),
         }
     }
@@ -441,14 +1025,15 @@ mod tests {
             trade_rx,
             window_tx,
             latency_stats,
+            DetectorConfig::default(),
         );
 
         // Seed no-side in detector's local cache
-        detector.handle_price_change(price_msg("no1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "no1", 0.45)).await;
         // yes=0.45, no=0.45 → spread=0.10 (arb) — opens as pending
-        detector.handle_price_change(price_msg("yes1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.45)).await;
         // Immediately close on next tick (only 1 arb tick)
-        detector.handle_price_change(price_msg("yes1", 0.55)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.55)).await;
 
         // Only a Close event should fire (classified SingleTick), never an Open.
         let event = window_rx.try_recv().expect("expected Close event");
@@ -462,6 +1047,73 @@ mod tests {
         assert!(window_rx.try_recv().is_err(), "no further events expected");
     }
 
+    #[tokio::test]
+    async fn tick_size_sets_quantisation_and_noise_floor() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        // On the default 0.01 grid, 0.495 + 0.5 snaps to 1.00: no spread.
+        detector.handle_price_change(price_msg("market1", "no1", 0.5)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.495)).await;
+        assert!(detector.active_windows.is_empty());
+
+        // At a 0.001 tick the same quotes are a 5-tick spread, above the 2-tick noise floor.
+        store.set_tick_size("yes1", px(0.001));
+        detector.handle_price_change(price_msg("market1", "yes1", 0.495)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.495)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.51)).await;
+        let mut events = Vec::new();
+        while let Ok(event) = window_rx.try_recv() {
+            events.push(event);
+        }
+        match events.last() {
+            Some(WindowEvent::Close(c)) => {
+                assert_eq!(c.spread, px(0.005), "spread={}", c.spread);
+                assert_eq!(c.spread_category, SpreadCategory::Small);
+            }
+            other => panic!("expected Close, got {other:?}"),
+        }
+        assert_eq!(SpreadCategory::from_spread(px(0.005), DEFAULT_TICK_SIZE), SpreadCategory::Noise);
+    }
+
+    #[tokio::test]
+    async fn coalesced_frame_does_not_open_phantom_window() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        detector.handle_price_change(price_msg("market1", "yes1", 0.56)).await;
+        detector.handle_price_change(price_msg("market1", "no1", 0.45)).await;
+
+        // One frame swaps the legs: yes 0.56→0.45, no 0.45→0.56. Applied one entry at a
+        // time this would briefly read yes=0.45 + no=0.45; coalesced it stays at 1.01.
+        let mut msg = price_msg("market1", "yes1", 0.45);
+        msg.quotes.push(quote("no1", 0.56));
+        detector.handle_price_change(msg).await;
+
+        assert!(detector.active_windows.is_empty(), "no window may open mid-frame");
+        assert!(window_rx.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn multi_tick_fires_open_then_close() {
         let store = make_store_with_market();
@@ -476,22 +1128,480 @@ mod tests {
             trade_rx,
             window_tx,
             latency_stats,
+            DetectorConfig::default(),
         );
 
         // Seed no-side in detector's local cache
-        detector.handle_price_change(price_msg("no1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "no1", 0.45)).await;
 
         // Tick 1: spread opens (pending)
-        detector.handle_price_change(price_msg("yes1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.45)).await;
         // Tick 2: confirms window — should fire Open
-        detector.handle_price_change(price_msg("yes1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.45)).await;
 
         let event = window_rx.try_recv().expect("expected Open event");
         assert!(matches!(event, WindowEvent::Open(_)));
 
         // Close the window
-        detector.handle_price_change(price_msg("yes1", 0.56)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.56)).await;
         let event = window_rx.try_recv().expect("expected Close event");
         assert!(matches!(event, WindowEvent::Close(_)));
     }
+
+    #[tokio::test]
+    async fn market_sweep_closes_its_windows_with_the_given_reason() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        detector.handle_price_change(price_msg_at("market1", "no1", 0.45, 100)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, 200)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.44, 300)).await;
+        detector.close_market_windows("other", CloseReason::BookCorrupt).await;
+        assert!(store.has_open_window("market1"));
+
+        detector.close_market_windows("market1", CloseReason::BookCorrupt).await;
+        let close = std::iter::from_fn(|| window_rx.try_recv().ok())
+            .find_map(|e| match e {
+                WindowEvent::Close(c) => Some(c),
+                WindowEvent::Open(_) => None,
+            })
+            .expect("expected Close event");
+        assert_eq!(close.close_reason, Some(CloseReason::BookCorrupt));
+        assert_eq!(close.closed_at_ns, 300, "closed at the last observed arb tick");
+        assert!(!store.has_open_window("market1"));
+    }
+
+    #[tokio::test]
+    async fn stale_market_closes_window_and_opens_none() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        detector.handle_price_change(price_msg_at("market1", "no1", 0.45, 100)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, 200)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.44, 300)).await;
+        assert!(store.has_open_window("market1"));
+
+        store.mark_stale("market1", now_ns());
+        detector.close_faulted_windows().await;
+        let close = std::iter::from_fn(|| window_rx.try_recv().ok())
+            .find_map(|e| match e {
+                WindowEvent::Close(c) => Some(c),
+                WindowEvent::Open(_) => None,
+            })
+            .expect("expected Close event");
+        assert_eq!(close.close_reason, Some(CloseReason::FeedStale));
+        assert_eq!(close.opportunity_class, 0);
+        assert_eq!(close.closed_at_ns, 300, "closed at the last observed arb tick");
+        assert!(!store.has_open_window("market1"));
+
+        // Arb ticks on a stale market are ignored until both legs update again.
+        detector.handle_price_change(price_msg("market1", "yes1", 0.40)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.40)).await;
+        assert!(detector.active_windows.is_empty());
+        assert!(window_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn close_event_carries_tick_trace() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        detector.handle_price_change(price_msg_at("market1", "no1", 0.45, 100)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, 200)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.44, 300)).await;
+        detector.handle_trade(TradeMsg { asset_id: "yes1".to_string(), price: px(0.44), received_at_ns: 350 });
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.56, 400)).await;
+
+        let close = std::iter::from_fn(|| window_rx.try_recv().ok())
+            .find_map(|e| match e {
+                WindowEvent::Close(c) => Some(c),
+                WindowEvent::Open(_) => None,
+            })
+            .expect("expected Close event");
+        let trace: Vec<_> = close.ticks.iter().map(|t| (t.at_ns, t.kind, t.yes_ask)).collect();
+        assert_eq!(
+            trace,
+            vec![
+                (200, WindowTickKind::Price, px(0.45)),
+                (300, WindowTickKind::Price, px(0.44)),
+                (350, WindowTickKind::Trade, px(0.44)),
+                (400, WindowTickKind::Price, px(0.56)),
+            ]
+        );
+        assert_eq!(close.ticks[2].trade_price, Some(px(0.44)));
+    }
+
+    #[tokio::test]
+    async fn close_reports_peak_closing_and_time_weighted_spread() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        // Spread 0.10 for 100ns, widens to 0.15 for 200ns, narrows to 0.11 for 200ns.
+        detector.handle_price_change(price_msg_at("market1", "no1", 0.45, 100)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, 200)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.40, 300)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.44, 500)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.56, 700)).await;
+
+        let close = std::iter::from_fn(|| window_rx.try_recv().ok())
+            .find_map(|e| match e {
+                WindowEvent::Close(c) => Some(c),
+                WindowEvent::Open(_) => None,
+            })
+            .expect("expected Close event");
+        let obs = &close.observables;
+        assert_eq!(close.spread, px(0.10), "opening spread is kept");
+        assert_eq!(obs.peak_spread, px(0.15));
+        assert_eq!(obs.peak_spread_at_ns, 300);
+        assert_eq!(obs.closing_spread, px(0.11));
+        assert!((obs.twa_spread - 0.124).abs() < 1e-9, "twa={}", obs.twa_spread);
+        assert_eq!((obs.min_yes_ask, obs.max_yes_ask), (px(0.40), px(0.45)));
+        assert_eq!((obs.min_no_ask, obs.max_no_ask), (px(0.45), px(0.45)));
+    }
+
+    #[tokio::test]
+    async fn elapsed_rule_confirms_by_time_not_ticks() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let rule = ConfirmationRule { min_ticks: 1, min_elapsed_ms: 100, source: "category:other".to_string() };
+        let mut cfg = DetectorConfig::default();
+        cfg.confirmation.by_category.insert(Category::Other, rule.clone());
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            cfg,
+        );
+
+        let t0 = now_ns();
+        let ms = 1_000_000;
+        detector.handle_price_change(price_msg_at("market1", "no1", 0.45, t0)).await;
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, t0)).await;
+        // Many fast ticks do not confirm a 100ms rule.
+        for i in 1..=5 {
+            detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, t0 + i * 10 * ms)).await;
+        }
+        assert!(window_rx.try_recv().is_err(), "must not confirm before 100ms");
+
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.45, t0 + 120 * ms)).await;
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected Open once elapsed");
+        };
+        assert_eq!(open.confirmation, rule);
+
+        detector.handle_price_change(price_msg_at("market1", "yes1", 0.56, t0 + 130 * ms)).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected Close");
+        };
+        assert_eq!(close.open_duration_class, OpenDurationClass::MultiTick);
+        assert_eq!(close.confirmation.source, "category:other");
+    }
+
+    #[tokio::test]
+    async fn window_events_carry_executable_depth() {
+        let store = make_store_with_market();
+        store.apply_book_snapshot("yes1", &[(px(0.45), 100.0), (px(0.5), 100.0)], &[]);
+        store.apply_book_snapshot("no1", &[(px(0.45), 30.0), (px(0.52), 100.0)], &[]);
+
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        detector.handle_price_change(price_msg("market1", "no1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.45)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.45)).await;
+
+        // 30 @ 0.90 + 70 @ 0.97 (0.45+0.52); 0.50+0.52 is not profitable.
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected Open event");
+        };
+        let depth = open.depth.expect("depth should be computed from store books");
+        assert!((depth.size - 100.0).abs() < 1e-9, "size={}", depth.size);
+        assert!((depth.profit - (100.0 - (30.0 * 0.90 + 70.0 * 0.97))).abs() < 1e-9);
+        // Within 5 ticks of the best ask: YES 100 @ 0.45, NO 30 @ 0.45.
+        let open_book = open.open_book.expect("binary windows carry book metrics");
+        assert_eq!(open_book.paired_depth(WindowSide::Buy), 30.0);
+
+        detector.handle_price_change(price_msg("market1", "yes1", 0.56)).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected Close event");
+        };
+        assert_eq!(close.depth, Some(depth));
+        assert_eq!(close.open_book.as_deref(), Some(&open_book));
+        assert!(close.close_book.is_some());
+    }
+
+    #[tokio::test]
+    async fn fees_below_threshold_suppress_window() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        // 200 bps on ~0.99 notional eats the whole 0.01 gross spread.
+        let fees = FeeModel { taker_fee_bps: 200.0, ..FeeModel::default() };
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig { fees, ..DetectorConfig::default() },
+        );
+
+        detector.handle_price_change(price_msg("market1", "no1", 0.49)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.50)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.50)).await;
+        assert!(window_rx.try_recv().is_err(), "net-negative spread must not open a window");
+
+        // A wide spread still clears the fees.
+        detector.handle_price_change(price_msg("market1", "yes1", 0.40)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.40)).await;
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected Open event");
+        };
+        assert_eq!(open.spread, px(0.11));
+        assert!((open.net_spread - (0.11 - 0.89 * 0.02)).abs() < 1e-9, "net={}", open.net_spread);
+    }
+
+    #[tokio::test]
+    async fn bid_side_opens_sell_window() {
+        let store = make_store_with_market();
+        store.apply_book_snapshot("yes1", &[], &[(px(0.56), 40.0), (px(0.5), 100.0)]);
+        store.apply_book_snapshot("no1", &[], &[(px(0.5), 100.0)]);
+
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        // Asks sum to 1.08 (no buy arb); bids 0.56 + 0.50 = 1.06.
+        detector.handle_price_change(price_msg("market1", "no1", 0.51)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.57)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.57)).await;
+
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected sell Open");
+        };
+        assert_eq!(open.side, WindowSide::Sell);
+        assert_eq!(open.kind, WindowKind::Binary);
+        assert_eq!(open.spread, px(0.06), "spread={}", open.spread);
+        assert_eq!(open.combined_cost, px(1.06));
+        // Only the 0.56 level pairs profitably: 40 @ 1.06.
+        let depth = open.depth.expect("depth from bid ladders");
+        assert!((depth.size - 40.0).abs() < 1e-9, "size={}", depth.size);
+        assert!((depth.profit - 40.0 * 0.06).abs() < 1e-9);
+        assert!(window_rx.try_recv().is_err(), "buy side must stay closed");
+
+        // Bids drop back below 1 → sell window closes.
+        detector.handle_price_change(price_msg("market1", "yes1", 0.48)).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected sell Close");
+        };
+        assert_eq!(close.side, WindowSide::Sell);
+        assert_eq!(close.observables.tick_count, 2);
+    }
+
+    #[tokio::test]
+    async fn emptied_bid_side_closes_sell_window() {
+        let store = make_store_with_market();
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        // Bids 0.56 + 0.50 = 1.06.
+        detector.handle_price_change(price_msg("market1", "no1", 0.51)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.57)).await;
+        detector.handle_price_change(price_msg("market1", "yes1", 0.57)).await;
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected sell Open");
+        };
+        assert_eq!(open.side, WindowSide::Sell);
+
+        // YES bids empty out: the sell leg is gone, so the window closes.
+        let mut msg = price_msg("market1", "yes1", 0.57);
+        msg.quotes[0].best_bid = Price::ZERO;
+        detector.handle_price_change(msg).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected sell Close");
+        };
+        assert_eq!(close.side, WindowSide::Sell);
+        assert_eq!(close.observables.tick_count, 2);
+    }
+
+    #[tokio::test]
+    async fn neg_risk_yes_basket_opens_event_window() {
+        let store = MarketStore::new();
+        let member = |i: u32| Market {
+            id: format!("m{i}"),
+            question: format!("Outcome {i}"),
+            category: Category::Politics,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: format!("y{i}"),
+            no_token_id: format!("n{i}"),
+        };
+        store.add_event(NegRiskEvent {
+            id: "event1".to_string(),
+            title: "Who wins?".to_string(),
+            category: Category::Politics,
+            end_date_iso: None,
+            members: vec![member(1), member(2), member(3)],
+        });
+
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        // Each binary market is priced fairly (yes + no > 1) but ΣYES = 0.90.
+        for (i, yes) in [(1, 0.30), (2, 0.30), (3, 0.30)] {
+            detector.handle_price_change(price_msg(&format!("m{i}"), &format!("n{i}"), 0.71)).await;
+            detector.handle_price_change(price_msg(&format!("m{i}"), &format!("y{i}"), yes)).await;
+        }
+        detector.handle_price_change(price_msg("m1", "y1", 0.30)).await;
+
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected event Open");
+        };
+        assert_eq!(open.kind, WindowKind::NegRiskYes);
+        assert_eq!(open.market_id, "event1");
+        assert_eq!(open.spread, px(0.10), "spread={}", open.spread);
+        assert!(window_rx.try_recv().is_err(), "binary and NO baskets must stay closed");
+
+        // ΣYES back above 1 closes it.
+        detector.handle_price_change(price_msg("m2", "y2", 0.45)).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected event Close");
+        };
+        assert_eq!(close.kind, WindowKind::NegRiskYes);
+        assert_eq!(close.observables.tick_count, 2);
+    }
+
+    #[tokio::test]
+    async fn emptied_member_book_closes_event_window() {
+        let store = MarketStore::new();
+        let member = |i: u32| Market {
+            id: format!("m{i}"),
+            question: format!("Outcome {i}"),
+            category: Category::Politics,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: format!("y{i}"),
+            no_token_id: format!("n{i}"),
+        };
+        store.add_event(NegRiskEvent {
+            id: "event1".to_string(),
+            title: "Who wins?".to_string(),
+            category: Category::Politics,
+            end_date_iso: None,
+            members: vec![member(1), member(2), member(3)],
+        });
+
+        let (_price_tx, price_rx) = mpsc::channel(16);
+        let (_trade_tx, trade_rx) = mpsc::channel(16);
+        let (window_tx, mut window_rx) = mpsc::channel(16);
+        let mut detector = SpreadDetector::new(
+            store.clone(),
+            price_rx,
+            trade_rx,
+            window_tx,
+            Arc::new(LatencyStats::new()),
+            DetectorConfig::default(),
+        );
+
+        for i in 1..=3 {
+            detector.handle_price_change(price_msg(&format!("m{i}"), &format!("n{i}"), 0.71)).await;
+            detector.handle_price_change(price_msg(&format!("m{i}"), &format!("y{i}"), 0.30)).await;
+        }
+        detector.handle_price_change(price_msg("m1", "y1", 0.30)).await;
+        let Ok(WindowEvent::Open(open)) = window_rx.try_recv() else {
+            panic!("expected event Open");
+        };
+        assert_eq!(open.kind, WindowKind::NegRiskYes);
+
+        // m2's YES asks empty out: the basket can't be bought, so the window closes.
+        let mut msg = price_msg("m2", "y2", 0.30);
+        msg.quotes[0].best_ask = Price::ZERO;
+        detector.handle_price_change(msg).await;
+        let Ok(WindowEvent::Close(close)) = window_rx.try_recv() else {
+            panic!("expected event Close");
+        };
+        assert_eq!(close.kind, WindowKind::NegRiskYes);
+        assert_eq!(close.market_id, "event1");
+        assert!(window_rx.try_recv().is_err());
+    }
 }
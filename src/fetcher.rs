@@ -1,12 +1,12 @@
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tracing::{debug, info, warn};
+use tracing::{info, warn};
 
 use crate::config::{CLOB_API_URL, Config};
 use crate::error::{AppError, Result};
 use crate::state::market_store::MarketStore;
-use crate::types::{Category, Market};
+use crate::types::{Category, Market, NegRiskEvent};
 
 #[derive(Debug, Default)]
 pub struct FetchStats {
@@ -149,6 +149,88 @@ pub async fn fetch_pinned_markets(
     Ok(results)
 }
 
+/// Fetch the highest-volume active neg-risk (multi-outcome) events from Gamma.
+///
+/// Unlike `fetch_markets`, member markets are not quality-filtered individually —
+/// an event basket is only meaningful if every live outcome is included.
+/// Augmented neg-risk events are skipped: their placeholder outcomes mean the
+/// listed members are not exhaustive.
+pub async fn fetch_neg_risk_events(cfg: &Config) -> Result<Vec<NegRiskEvent>> {
+    if cfg.scanner_max_neg_risk_events == 0 {
+        return Ok(Vec::new());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?;
+
+    let url = format!(
+        "{}/events?active=true&closed=false&limit=200&order=volume24hr&ascending=false",
+        cfg.gamma_api_url
+    );
+
+    let resp: serde_json::Value = client.get(&url).send().await?.json().await?;
+
+    let Some(items) = resp.as_array() else {
+        return Err(AppError::Bootstrap(
+            "GAMMA /events response was not an array".to_string(),
+        ));
+    };
+
+    Ok(items
+        .iter()
+        .filter_map(parse_neg_risk_event)
+        .take(cfg.scanner_max_neg_risk_events)
+        .collect())
+}
+
+/// Parse a Gamma event object into a `NegRiskEvent`.
+/// Returns None for non-neg-risk or augmented events, or if any live member is unparseable.
+pub fn parse_neg_risk_event(v: &serde_json::Value) -> Option<NegRiskEvent> {
+    let is_neg_risk = v.get("negRisk").and_then(|b| b.as_bool()).unwrap_or(false);
+    let augmented = v.get("negRiskAugmented").and_then(|b| b.as_bool()).unwrap_or(false);
+    if !is_neg_risk || augmented {
+        return None;
+    }
+
+    let id = match v.get("id")? {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+
+    let category = v
+        .get("category")
+        .and_then(|c| c.as_str())
+        .map(parse_category_str)
+        .unwrap_or(Category::Other);
+
+    let mut members = Vec::new();
+    for m in v.get("markets")?.as_array()? {
+        // Resolved members drop out of the basket; the remaining outcomes still sum to one winner.
+        let closed = m.get("closed").and_then(|b| b.as_bool()).unwrap_or(false);
+        let active = m.get("active").and_then(|b| b.as_bool()).unwrap_or(true);
+        if closed || !active {
+            continue;
+        }
+        let mut market = parse_gamma_market_unfiltered(m)?;
+        market.category = category;
+        members.push(market);
+    }
+
+    if members.len() < 2 {
+        return None;
+    }
+
+    Some(NegRiskEvent {
+        id,
+        title: v.get("title").and_then(|t| t.as_str()).unwrap_or("").to_string(),
+        category,
+        end_date_iso: v.get("endDate").and_then(|e| e.as_str()).map(|s| s.to_string()),
+        members,
+    })
+}
+
 /// Extract the Unix timestamp from the last numeric segment of a slug.
 /// `btc-updown-5m-1772068500` → 1772068500. Returns 0 if not present.
 pub fn parse_slug_end_ts(slug: &str) -> u64 {
@@ -356,17 +438,6 @@ fn parse_gamma_market_checked(
     })
 }
 
-/// Thin wrapper preserving the old signature for callers that don't need stats.
-pub fn parse_gamma_market(
-    v: &serde_json::Value,
-    cfg: &Config,
-    now_secs: f64,
-    min_expiry_secs: f64,
-    max_expiry_secs: f64,
-) -> Option<Market> {
-    parse_gamma_market_checked(v, cfg, now_secs, min_expiry_secs, max_expiry_secs).ok()
-}
-
 /// Fetch the CLOB REST order book for a sample of tokens and compare against
 /// the WS-derived local book prices. Logs discrepancies to help verify data integrity.
 pub async fn audit_book_prices(store: &Arc<MarketStore>, sample_count: usize) {
@@ -391,6 +462,8 @@ pub async fn audit_book_prices(store: &Arc<MarketStore>, sample_count: usize) {
         else {
             continue;
         };
+        let (ws_yes_ask, ws_no_ask, ws_yes_bid, ws_no_bid) =
+            (ws_yes_ask.to_f64(), ws_no_ask.to_f64(), ws_yes_bid.to_f64(), ws_no_bid.to_f64());
 
         let (rest_yes_ask, rest_yes_bid) = match fetch_rest_best_prices(&client, &market.yes_token_id).await {
             Some(p) => p,
@@ -509,12 +582,56 @@ pub fn parse_iso_to_unix_secs(s: &str) -> Option<f64> {
 }
 
 pub fn parse_category_str(s: &str) -> Category {
-    match s.to_lowercase().as_str() {
-        "sports" => Category::Sports,
-        "weather" => Category::Weather,
-        "crypto" => Category::Crypto,
-        "politics" => Category::Politics,
-        "economics" => Category::Economics,
-        _ => Category::Other,
+    Category::from_name(s).unwrap_or(Category::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: &str, closed: bool) -> serde_json::Value {
+        serde_json::json!({
+            "conditionId": id,
+            "question": format!("Will {id} win?"),
+            "clobTokenIds": format!("[\"{id}-yes\", \"{id}-no\"]"),
+            "outcomes": "[\"Yes\", \"No\"]",
+            "active": true,
+            "closed": closed,
+        })
+    }
+
+    #[test]
+    fn parses_neg_risk_event_and_skips_closed_members() {
+        let v = serde_json::json!({
+            "id": "903",
+            "title": "Who will win?",
+            "category": "Politics",
+            "negRisk": true,
+            "markets": [member("a", false), member("b", false), member("c", true)],
+        });
+        let event = parse_neg_risk_event(&v).expect("neg-risk event");
+        assert_eq!(event.id, "903");
+        assert_eq!(event.category, Category::Politics);
+        assert_eq!(event.members.len(), 2);
+        assert_eq!(event.members[0].yes_token_id, "a-yes");
+        assert_eq!(event.members[1].no_token_id, "b-no");
+        assert_eq!(event.members[1].category, Category::Politics);
+    }
+
+    #[test]
+    fn rejects_plain_and_augmented_events() {
+        let plain = serde_json::json!({
+            "id": 1, "negRisk": false, "markets": [member("a", false), member("b", false)],
+        });
+        assert!(parse_neg_risk_event(&plain).is_none());
+
+        let augmented = serde_json::json!({
+            "id": 2, "negRisk": true, "negRiskAugmented": true,
+            "markets": [member("a", false), member("b", false)],
+        });
+        assert!(parse_neg_risk_event(&augmented).is_none());
+
+        let single = serde_json::json!({ "id": 3, "negRisk": true, "markets": [member("a", false)] });
+        assert!(parse_neg_risk_event(&single).is_none());
     }
 }
@@ -4,6 +4,7 @@ mod detector;
 mod error;
 mod fetcher;
 mod market_refresh;
+mod replay;
 mod scorer;
 mod state;
 mod types;
@@ -17,18 +18,23 @@ use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
 use crate::api::health::HealthState;
-use crate::api::latency::LatencyStats;
+use crate::api::latency::{FeedLatency, LatencyStats, ParseLatency};
 use crate::api::routes::{ApiState, router};
 use crate::config::{Config, CHANNEL_CAPACITY};
+use crate::db::book_archive::BookArchiver;
+use crate::db::checkpoint::{load_checkpoint, Checkpointer};
+use crate::db::reclassify::reclassify_windows;
 use crate::db::writer::DbWriter;
-use crate::detector::SpreadDetector;
-use crate::error::Result;
-use crate::fetcher::{audit_book_prices, fetch_markets};
-use crate::market_refresh::{MarketRefresher, PinnedMarketWatcher};
+use crate::detector::{classifier, ShardRouter};
+use crate::error::{AppError, Result};
+use crate::fetcher::{audit_book_prices, fetch_markets, fetch_neg_risk_events};
+use crate::market_refresh::{persist_event, MarketRefresher, PinnedMarketWatcher};
 use crate::scorer::MarketScorer;
 use crate::state::MarketStore;
-use crate::types::{WindowCloseEvent, WindowEvent, WindowOpenEvent};
-use crate::ws::WsManager;
+use crate::types::{WindowCloseEvent, WindowEvent, WindowKind, WindowOpenEvent};
+use crate::replay::{replay_capture, ReplaySpeed};
+use crate::ws::capture::{capture_files, FrameCapture};
+use crate::ws::{SilenceMonitor, WsManager};
 
 #[tokio::main]
 async fn main() {
@@ -44,12 +50,94 @@ async fn main() {
         .with_env_filter(EnvFilter::new(&cfg.log_level))
         .init();
 
-    if let Err(e) = run(cfg).await {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("reclassify") => run_reclassify(cfg, &args[1..]).await,
+        Some("replay") => run_replay(cfg, &args[1..]).await,
+        _ => run(cfg).await,
+    };
+    if let Err(e) = result {
         error!("Fatal error: {e}");
         std::process::exit(1);
     }
 }
 
+/// `scanner reclassify [--classifier NAME[@vN]] [--since NS]`
+///
+/// Re-runs a classifier over stored window observables, then rescores market_stats.
+/// Defaults to the configured WINDOW_CLASSIFIER and all closed windows.
+async fn run_reclassify(cfg: Config, args: &[String]) -> Result<()> {
+    let mut classifier_name = cfg.detector.classifier.clone();
+    let mut since_ns = 0i64;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match (arg.as_str(), it.next()) {
+            ("--classifier", Some(v)) => classifier_name = v.clone(),
+            ("--since", Some(v)) => {
+                since_ns = v
+                    .parse()
+                    .map_err(|_| AppError::Config(format!("--since must be a ns timestamp, got '{v}'")))?;
+            }
+            _ => return Err(AppError::Config(format!("unrecognised reclassify argument '{arg}'"))),
+        }
+    }
+    let classifier = classifier::by_name(&classifier_name)
+        .ok_or_else(|| AppError::Config(format!("unknown classifier '{classifier_name}'")))?;
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", cfg.db_path)).await?;
+    run_migrations(&pool).await?;
+
+    let summary = reclassify_windows(&pool, classifier.as_ref(), since_ns).await?;
+    info!(
+        "[RECLASSIFY] {} done: {} windows scanned, {} changed",
+        classifier.id(),
+        summary.scanned,
+        summary.changed,
+    );
+    MarketScorer::new(pool).score_all_markets().await
+}
+
+/// `scanner replay <CAPTURE_FILE|CAPTURE_DIR> [--speed N|max] [--db PATH]`
+///
+/// Replays a raw feed capture through the detector into `--db` (default
+/// `replay.db`), at original speed, N× speed or as fast as possible.
+async fn run_replay(cfg: Config, args: &[String]) -> Result<()> {
+    let mut it = args.iter();
+    let path = it
+        .next()
+        .ok_or_else(|| AppError::Config("replay needs a capture file or directory".to_string()))?;
+    let mut speed = ReplaySpeed::Factor(1.0);
+    let mut db_path = "replay.db".to_string();
+    while let Some(arg) = it.next() {
+        match (arg.as_str(), it.next()) {
+            ("--speed", Some(v)) if v == "max" => speed = ReplaySpeed::Max,
+            ("--speed", Some(v)) => match v.parse::<f64>() {
+                Ok(f) if f > 0.0 => speed = ReplaySpeed::Factor(f),
+                _ => return Err(AppError::Config(format!("--speed must be a positive number or 'max', got '{v}'"))),
+            },
+            ("--db", Some(v)) => db_path = v.clone(),
+            _ => return Err(AppError::Config(format!("unrecognised replay argument '{arg}'"))),
+        }
+    }
+
+    let files = capture_files(std::path::Path::new(path))?;
+    if files.is_empty() {
+        return Err(AppError::Config(format!("no capture files (*.jsonl.gz) under '{path}'")));
+    }
+    let options = sqlx::sqlite::SqliteConnectOptions::new().filename(&db_path).create_if_missing(true);
+    let pool = sqlx::SqlitePool::connect_with(options).await?;
+    run_migrations(&pool).await?;
+
+    let summary = replay_capture(&files, speed, pool, cfg.detector_shards, cfg.detector.clone()).await?;
+    info!(
+        "[REPLAY] done: {} file(s), {} records, {} frames → {db_path}",
+        summary.files,
+        summary.records,
+        summary.frames,
+    );
+    Ok(())
+}
+
 /// Run migrations. On "duplicate column" (column already added manually), mark migration applied and retry.
 async fn run_migrations(pool: &sqlx::SqlitePool) -> Result<()> {
     let migrator = sqlx::migrate!("./migrations");
@@ -92,57 +180,29 @@ async fn run(cfg: Config) -> Result<()> {
     run_migrations(&pool).await?;
     info!("Database ready at {}", cfg.db_path);
 
-    // --- REST bootstrap: fetch filtered active markets ---
-    let (markets, stats) = fetch_markets(&cfg).await?;
-    info!(
-        "Bootstrap complete: {} markets from {} API results (min_vol=${:.0}, min_liq=${:.0}, expiry={:.0}m-{:.0}h)",
-        markets.len(),
-        stats.api_total,
-        cfg.scanner_min_volume_24h,
-        cfg.scanner_min_liquidity,
-        cfg.scanner_min_expiry_minutes,
-        cfg.scanner_max_expiry_hours,
-    );
-    info!(
-        "[FILTER] rejected: no_tokens={} no_outcomes={} low_volume={} low_liquidity={} expiry={}",
-        stats.rejected_no_tokens,
-        stats.rejected_no_outcomes,
-        stats.rejected_low_volume,
-        stats.rejected_low_liquidity,
-        stats.rejected_expiry,
-    );
-    if !stats.outcome_samples.is_empty() {
-        info!("[FILTER] sample of {} markets rejected by outcomes (showing labels we don't recognize):", stats.outcome_samples.len());
-        for (q, outcomes) in &stats.outcome_samples {
-            let q_short = if q.len() > 60 { &q[..60] } else { q };
-            info!("[FILTER]   \"{q_short}\" → outcomes: {outcomes:?}");
-        }
-    }
-
-    // --- In-memory market store ---
+    // --- In-memory market store: warm from the checkpoint, else bootstrap from Gamma ---
     let store = MarketStore::new();
-    store.add_markets(markets.clone());
-
-    // Persist market metadata to DB
-    let created_at = now_ns() as i64;
-    for market in &markets {
-        let category = market.category.to_string();
-        sqlx::query!(
-            r#"
-            INSERT OR IGNORE INTO markets (id, question, category, end_date_iso, total_volume, created_at)
-            VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-            market.id,
-            market.question,
-            category,
-            market.end_date_iso,
-            market.total_volume,
-            created_at,
-        )
-        .execute(&pool)
-        .await?;
+    let checkpoint = if cfg.checkpoint.enabled() {
+        load_checkpoint(&pool, cfg.checkpoint.max_age, now_ns()).await.unwrap_or_else(|e| {
+            warn!("Checkpoint load failed, bootstrapping from Gamma: {e}");
+            None
+        })
+    } else {
+        None
+    };
+    let warm_start = checkpoint.is_some();
+    match checkpoint {
+        Some(checkpoint) => {
+            info!(
+                "Warm start: {} markets and {} neg-risk events from the checkpoint saved {:.0}s ago; reconciling with Gamma in the background",
+                checkpoint.market_count(),
+                checkpoint.event_count(),
+                now_ns().saturating_sub(checkpoint.saved_at_ns) as f64 / 1e9,
+            );
+            checkpoint.restore(&store, now_ns());
+        }
+        None => bootstrap_from_gamma(&cfg, &pool, &store).await?,
     }
-    info!("Persisted {} markets to DB", markets.len());
 
     // --- Pinned market notice ---
     if cfg.pinned_slugs.is_empty() {
@@ -153,37 +213,66 @@ async fn run(cfg: Config) -> Result<()> {
 
     // --- Shared state for API ---
     let latency_stats = Arc::new(LatencyStats::new());
+    let feed_latency = Arc::new(FeedLatency::new());
+    let parse_latency = Arc::new(ParseLatency::new());
     let health = Arc::new(HealthState::new());
     let (window_broadcast_tx, _) = broadcast::channel::<WindowEvent>(256);
 
     // --- Channels ---
-    let (price_tx, price_rx) = mpsc::channel(CHANNEL_CAPACITY);
-    let (trade_tx, trade_rx) = mpsc::channel(CHANNEL_CAPACITY);
     let (window_tx, window_rx) = mpsc::channel(CHANNEL_CAPACITY);
     let (control_tx, control_rx) = mpsc::channel::<crate::types::ControlMsg>(CHANNEL_CAPACITY);
 
     // --- Spawn tasks ---
 
-    // WebSocket manager
-    let ws_manager = WsManager::new(
+    // Spread detector shards (hot path); all shards emit into window_tx
+    let shard_router = ShardRouter::spawn(
+        cfg.detector_shards,
+        Arc::clone(&store),
+        window_tx,
+        Arc::clone(&latency_stats),
+        cfg.detector.clone(),
+        &health,
+    );
+
+    // Raw feed capture (optional) — every frame to rotating gzip JSONL for `scanner replay`
+    let capture = match &cfg.capture_dir {
+        Some(dir) => {
+            info!("Capturing raw feed to {dir} (rotating every {} MB)", cfg.capture_rotate_bytes / (1024 * 1024));
+            Some(FrameCapture::spawn(dir, cfg.capture_rotate_bytes, Arc::clone(&store))?)
+        }
+        None => None,
+    };
+
+    // WebSocket pool — partitions assets across connections and routes each
+    // market's updates to its detector shard
+    let mut ws_manager = WsManager::new(
         cfg.ws_url.clone(),
         Arc::clone(&store),
-        price_tx,
-        trade_tx,
+        shard_router,
         control_rx,
         Arc::clone(&health),
-    );
+        cfg.ws_connections,
+    )
+    .with_feed_latency(Arc::clone(&feed_latency))
+    .with_parse_latency(Arc::clone(&parse_latency))
+    .with_reconnect(cfg.reconnect.clone());
+    if let Some(capture) = &capture {
+        ws_manager = ws_manager.with_capture(capture.clone());
+    }
     tokio::spawn(async move { ws_manager.run().await });
 
-    // Spread detector (hot path)
-    let detector = SpreadDetector::new(
-        Arc::clone(&store),
-        price_rx,
-        trade_rx,
-        window_tx,
-        Arc::clone(&latency_stats),
-    );
-    tokio::spawn(async move { detector.run().await });
+    // Silence monitor — resubscribes tokens with no snapshot and marks markets
+    // stale when a leg goes quiet under an open window
+    let mut silence_monitor = SilenceMonitor::new(Arc::clone(&store), control_tx.clone(), Arc::clone(&health));
+    if let Some(capture) = capture {
+        silence_monitor = silence_monitor.with_capture(capture);
+    }
+    tokio::spawn(async move { silence_monitor.run().await });
+
+    // Book snapshot archive: periodic, plus one per window open
+    let (snapshot_tx, snapshot_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let archiver = BookArchiver::new(pool.clone(), Arc::clone(&store), cfg.book_archive.clone(), snapshot_rx);
+    tokio::spawn(async move { archiver.run().await });
 
     // Window event consumer: telemetry logger + DB writer + broadcast to WS clients
     let pool_clone = pool.clone();
@@ -195,17 +284,25 @@ async fn run(cfg: Config) -> Result<()> {
             pool_clone,
             health_clone,
             window_broadcast_tx_clone,
+            snapshot_tx,
         )
         .await;
     });
 
+    // Warm-restart checkpoint of the market set and last-known prices
+    if cfg.checkpoint.enabled() {
+        let checkpointer = Checkpointer::new(pool.clone(), Arc::clone(&store), cfg.checkpoint.interval);
+        tokio::spawn(async move { checkpointer.run().await });
+    }
+
     // Market scorer (background, every 60s)
-    let scorer = MarketScorer::new(pool.clone());
+    let scorer = MarketScorer::new(pool.clone()).with_store(Arc::clone(&store));
     tokio::spawn(async move { scorer.run().await });
 
-    // Market refresher (background, every 300s)
+    // Market refresher (background, every 300s); reconciles a warm start right away
     let pinned_control_tx = control_tx.clone();
-    let refresher = MarketRefresher::new(cfg.clone(), Arc::clone(&store), control_tx, pool.clone());
+    let refresher = MarketRefresher::new(cfg.clone(), Arc::clone(&store), control_tx, pool.clone())
+        .with_reconcile_on_start(warm_start);
     tokio::spawn(async move { refresher.run().await });
 
     // Book price audit (one-shot, runs 20s after startup to let WS hydrate)
@@ -228,6 +325,8 @@ async fn run(cfg: Config) -> Result<()> {
     let api_state = ApiState {
         pool: pool.clone(),
         latency_stats,
+        feed_latency,
+        parse_latency,
         health,
         store,
         window_broadcast_tx,
@@ -242,12 +341,84 @@ async fn run(cfg: Config) -> Result<()> {
     Ok(())
 }
 
-/// Consumes WindowEvents: logs to console, writes closes to DB, broadcasts to WS clients.
+/// Cold start: fetches the qualifying markets and neg-risk events from Gamma into
+/// the store and the `markets` table.
+async fn bootstrap_from_gamma(cfg: &Config, pool: &sqlx::SqlitePool, store: &MarketStore) -> Result<()> {
+    // --- REST bootstrap: fetch filtered active markets ---
+    let (markets, stats) = fetch_markets(cfg).await?;
+    info!(
+        "Bootstrap complete: {} markets from {} API results (min_vol=${:.0}, min_liq=${:.0}, expiry={:.0}m-{:.0}h)",
+        markets.len(),
+        stats.api_total,
+        cfg.scanner_min_volume_24h,
+        cfg.scanner_min_liquidity,
+        cfg.scanner_min_expiry_minutes,
+        cfg.scanner_max_expiry_hours,
+    );
+    info!(
+        "[FILTER] rejected: no_tokens={} no_outcomes={} low_volume={} low_liquidity={} expiry={}",
+        stats.rejected_no_tokens,
+        stats.rejected_no_outcomes,
+        stats.rejected_low_volume,
+        stats.rejected_low_liquidity,
+        stats.rejected_expiry,
+    );
+    if !stats.outcome_samples.is_empty() {
+        info!("[FILTER] sample of {} markets rejected by outcomes (showing labels we don't recognize):", stats.outcome_samples.len());
+        for (q, outcomes) in &stats.outcome_samples {
+            let q_short = if q.len() > 60 { &q[..60] } else { q };
+            info!("[FILTER]   \"{q_short}\" → outcomes: {outcomes:?}");
+        }
+    }
+
+    store.add_markets(markets.clone());
+
+    // Persist market metadata to DB
+    let created_at = now_ns() as i64;
+    for market in &markets {
+        let category = market.category.to_string();
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO markets (id, question, category, end_date_iso, total_volume, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            market.id,
+            market.question,
+            category,
+            market.end_date_iso,
+            market.total_volume,
+            created_at,
+        )
+        .execute(pool)
+        .await?;
+    }
+    info!("Persisted {} markets to DB", markets.len());
+
+    // --- Neg-risk events: multi-outcome baskets watched alongside binary markets ---
+    let events = match fetch_neg_risk_events(cfg).await {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("Neg-risk event bootstrap failed, continuing without events: {e}");
+            Vec::new()
+        }
+    };
+    for event in events {
+        persist_event(pool, &event).await?;
+        info!(event_id = %event.id, legs = event.members.len(), "Neg-risk event: {}", event.title);
+        store.add_event(event);
+    }
+
+    Ok(())
+}
+
+/// Consumes WindowEvents: logs to console, writes closes to DB, broadcasts to WS clients,
+/// and asks the book archive for a snapshot of each opened window's markets.
 async fn window_consumer(
     mut rx: mpsc::Receiver<WindowEvent>,
     pool: sqlx::SqlitePool,
     health: Arc<HealthState>,
     window_broadcast_tx: broadcast::Sender<WindowEvent>,
+    snapshot_tx: mpsc::Sender<(WindowKind, String)>,
 ) {
     let db_writer_tx = {
         let (tx, window_rx) = mpsc::channel::<WindowEvent>(CHANNEL_CAPACITY);
@@ -261,6 +432,9 @@ async fn window_consumer(
         match &event {
             WindowEvent::Open(o) => {
                 log_window_open(o);
+                if let Err(e) = snapshot_tx.try_send((o.kind, o.market_id.clone())) {
+                    warn!("Book archive channel full (open): {e}");
+                }
                 if let Err(e) = db_writer_tx.try_send(event) {
                     warn!("DB writer channel full (open): {e}");
                 }
@@ -279,15 +453,21 @@ async fn window_consumer(
 }
 
 fn log_window_open(o: &WindowOpenEvent) {
+    let (size, profit) = o.depth.map_or((0.0, 0.0), |d| (d.size, d.profit));
     info!(
         event = "WINDOW_OPEN",
         market_id = %o.market_id,
-        spread = o.spread,
-        yes_ask = o.yes_ask,
-        no_ask = o.no_ask,
+        kind = %o.kind,
+        side = %o.side,
+        spread = o.spread.to_f64(),
+        net_spread = o.net_spread,
+        yes_ask = o.yes_ask.to_f64(),
+        no_ask = o.no_ask.to_f64(),
         category = %o.spread_category,
-        "WINDOW OPEN  | spread: ${:.4} | yes_ask: {:.4} | no_ask: {:.4} | category: {}",
-        o.spread, o.yes_ask, o.no_ask, o.spread_category,
+        executable_size = size,
+        expected_profit = profit,
+        "WINDOW OPEN  | spread: ${:.4} | yes_ask: {:.4} | no_ask: {:.4} | category: {} | size: {:.1} | profit: ${:.2}",
+        o.spread, o.yes_ask, o.no_ask, o.spread_category, size, profit,
     );
 }
 
@@ -307,6 +487,8 @@ fn log_window_close(c: &WindowCloseEvent) {
     info!(
         event = "WINDOW_CLOSE",
         market_id = %c.market_id,
+        kind = %c.kind,
+        side = %c.side,
         duration_ms = c.duration_ms,
         tick_count = c.observables.tick_count,
         open_class = %c.open_duration_class,
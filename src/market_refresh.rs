@@ -7,15 +7,20 @@ use tokio::time::interval;
 use tracing::{error, info, warn};
 
 use crate::config::{Config, MARKET_REFRESH_INTERVAL_SECS};
-use crate::fetcher::{fetch_markets, fetch_pinned_markets, parse_prefix_duration_secs};
+use crate::fetcher::{
+    fetch_markets, fetch_neg_risk_events, fetch_pinned_markets, parse_category_str, parse_prefix_duration_secs,
+};
 use crate::state::MarketStore;
-use crate::types::{ControlMsg, Market};
+use crate::types::{ControlMsg, Market, NegRiskEvent};
 
 pub struct MarketRefresher {
     cfg: Config,
     store: Arc<MarketStore>,
     control_tx: mpsc::Sender<ControlMsg>,
     pool: sqlx::SqlitePool,
+    /// Refresh on the first tick instead of one interval out, to reconcile a
+    /// market set restored from the checkpoint.
+    reconcile_on_start: bool,
 }
 
 impl MarketRefresher {
@@ -25,12 +30,20 @@ impl MarketRefresher {
         control_tx: mpsc::Sender<ControlMsg>,
         pool: sqlx::SqlitePool,
     ) -> Self {
-        Self { cfg, store, control_tx, pool }
+        Self { cfg, store, control_tx, pool, reconcile_on_start: false }
+    }
+
+    /// Reconciles with Gamma immediately, for a warm start from the checkpoint.
+    pub fn with_reconcile_on_start(mut self, reconcile: bool) -> Self {
+        self.reconcile_on_start = reconcile;
+        self
     }
 
     pub async fn run(self) {
         let mut ticker = interval(Duration::from_secs(MARKET_REFRESH_INTERVAL_SECS));
-        ticker.tick().await; // skip immediate first tick — bootstrap already ran
+        if !self.reconcile_on_start {
+            ticker.tick().await; // skip immediate first tick — bootstrap already ran
+        }
 
         loop {
             ticker.tick().await;
@@ -58,9 +71,10 @@ impl MarketRefresher {
 
         // Markets to remove: currently tracked but no longer in the fresh qualifying set.
         // Pinned markets are never removed — the PinnedMarketWatcher manages them.
+        // Neg-risk members are managed with their event in `refresh_events`.
         let to_remove: Vec<String> = current_ids
             .difference(&fresh_ids)
-            .filter(|id| !self.store.is_pinned(id))
+            .filter(|id| !self.store.is_pinned(id) && !self.store.is_event_member(id))
             .cloned()
             .collect();
 
@@ -122,10 +136,88 @@ impl MarketRefresher {
             "Market refresh complete: +{added_count} added, -{removed_count} removed, {unchanged_count} unchanged",
         );
 
+        if let Err(e) = self.refresh_events().await {
+            error!("Neg-risk event refresh failed: {e}");
+        }
+
+        Ok(())
+    }
+
+    /// Re-fetch neg-risk events; swap in new events and drop ones that no longer qualify.
+    async fn refresh_events(&self) -> crate::error::Result<()> {
+        let fresh = fetch_neg_risk_events(&self.cfg).await?;
+        let current_ids: HashSet<String> = self.store.event_ids().into_iter().collect();
+        let fresh_ids: HashSet<String> = fresh.iter().map(|e| e.id.clone()).collect();
+
+        for event_id in current_ids.difference(&fresh_ids) {
+            if let Some(member_ids) = self.store.event_member_ids(event_id) {
+                for market_id in member_ids {
+                    if let Err(e) = self.control_tx.send(ControlMsg::Unsubscribe(market_id.clone())).await {
+                        warn!("Failed to send Unsubscribe for event member {market_id}: {e}");
+                    }
+                }
+            }
+            self.store.remove_event(event_id);
+            info!(event_id = %event_id, "Neg-risk event removed: {event_id}");
+        }
+
+        for event in fresh.into_iter().filter(|e| !current_ids.contains(&e.id)) {
+            if let Err(e) = persist_event(&self.pool, &event).await {
+                warn!("DB insert failed for event {}: {e}", event.id);
+            }
+            let members = event.members.clone();
+            info!(event_id = %event.id, legs = members.len(), "Neg-risk event added: {}", event.title);
+            self.store.add_event(event);
+            if let Err(e) = self.control_tx.send(ControlMsg::Subscribe(members)).await {
+                warn!("Failed to send Subscribe for event members: {e}");
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Persist a neg-risk event and its member markets to the `markets` table.
+/// The event itself gets a row (id = Gamma event id) so its windows join like any market's.
+pub async fn persist_event(pool: &sqlx::SqlitePool, event: &NegRiskEvent) -> crate::error::Result<()> {
+    let created_at = now_ns() as i64;
+    let category = event.category.to_string();
+    let no_volume: Option<f64> = None;
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO markets (id, question, category, end_date_iso, total_volume, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        event.id,
+        event.title,
+        category,
+        event.end_date_iso,
+        no_volume,
+        created_at,
+    )
+    .execute(pool)
+    .await?;
+
+    for market in &event.members {
+        let category = market.category.to_string();
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO markets (id, question, category, end_date_iso, total_volume, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            market.id,
+            market.question,
+            category,
+            market.end_date_iso,
+            market.total_volume,
+            created_at,
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
 fn now_ns() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -158,6 +250,9 @@ struct KnownPinned {
 /// - Pre-subscribes the *next* market 30 seconds before the current one expires.
 /// - Unsubscribes and removes markets after they expire (60s grace period).
 /// - Re-fetches from Gamma every 30s to discover newly-created markets.
+/// - Persists the fetched series to `pinned_markets` and restores it on startup,
+///   so the current market is resubscribed at once and handoffs continue while
+///   Gamma is unreachable.
 ///
 /// Ticks every 10 seconds for responsive handoff timing.
 pub struct PinnedMarketWatcher {
@@ -205,6 +300,19 @@ impl PinnedMarketWatcher {
             return;
         }
 
+        if self.cfg.checkpoint.enabled() {
+            match self.restore_known().await {
+                Ok(0) => {}
+                Ok(restored) => {
+                    info!("Pinned watcher restored {restored} series markets from the checkpoint");
+                    if let Err(e) = self.manage_subscriptions(now_secs()).await {
+                        error!("Pinned watcher resubscribe failed: {e}");
+                    }
+                }
+                Err(e) => warn!("Pinned watcher checkpoint restore failed: {e}"),
+            }
+        }
+
         let mut ticker = interval(Duration::from_secs(WATCHER_TICK_SECS));
 
         loop {
@@ -218,15 +326,22 @@ impl PinnedMarketWatcher {
     async fn tick(&mut self) -> crate::error::Result<()> {
         let now = now_secs();
 
-        // Re-fetch from Gamma periodically to pick up newly-created markets.
+        // Re-fetch from Gamma periodically to pick up newly-created markets. A failed
+        // fetch keeps the last known series so handoffs continue through an outage.
         if now.saturating_sub(self.last_fetch_secs) >= GAMMA_REFETCH_SECS {
-            self.fetch_known().await?;
-            self.last_fetch_secs = now;
+            match self.fetch_known().await {
+                Ok(()) => self.last_fetch_secs = now,
+                Err(e) => warn!("Pinned Gamma fetch failed, keeping {} known series markets: {e}", self.known_count()),
+            }
         }
 
         self.manage_subscriptions(now).await
     }
 
+    fn known_count(&self) -> usize {
+        self.known.values().map(Vec::len).sum()
+    }
+
     async fn fetch_known(&mut self) -> crate::error::Result<()> {
         let results = fetch_pinned_markets(&self.cfg, &self.cfg.pinned_slugs).await?;
 
@@ -243,9 +358,84 @@ impl PinnedMarketWatcher {
             markets.sort_by_key(|m| m.end_ts);
         }
 
+        if self.cfg.checkpoint.enabled() {
+            if let Err(e) = self.save_known().await {
+                warn!("Pinned watcher checkpoint save failed: {e}");
+            }
+        }
+
         Ok(())
     }
 
+    /// Replaces `pinned_markets` with the current known series.
+    async fn save_known(&self) -> crate::error::Result<()> {
+        let saved_at = now_ns() as i64;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!("DELETE FROM pinned_markets").execute(&mut *tx).await?;
+        for known in self.known.values().flatten() {
+            let market = &known.market;
+            let category = market.category.to_string();
+            let end_ts = known.end_ts as i64;
+            sqlx::query!(
+                r#"
+                INSERT OR REPLACE INTO pinned_markets (
+                    market_id, prefix, end_ts, question, category, end_date_iso, total_volume,
+                    yes_token_id, no_token_id, saved_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                market.id,
+                known.prefix,
+                end_ts,
+                market.question,
+                category,
+                market.end_date_iso,
+                market.total_volume,
+                market.yes_token_id,
+                market.no_token_id,
+                saved_at,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Loads the series saved by `save_known` for prefixes still in PINNED_SLUGS.
+    /// Returns the number of markets restored.
+    async fn restore_known(&mut self) -> crate::error::Result<usize> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT market_id as "market_id!", prefix, end_ts, question, category, end_date_iso, total_volume,
+                   yes_token_id, no_token_id
+            FROM pinned_markets
+            ORDER BY end_ts
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut restored = 0;
+        for r in rows.into_iter().filter(|r| self.cfg.pinned_slugs.contains(&r.prefix)) {
+            let market = Market {
+                id: r.market_id,
+                question: r.question,
+                category: parse_category_str(&r.category),
+                end_date_iso: r.end_date_iso,
+                total_volume: r.total_volume,
+                yes_token_id: r.yes_token_id,
+                no_token_id: r.no_token_id,
+            };
+            self.known
+                .entry(r.prefix.clone())
+                .or_default()
+                .push(KnownPinned { market, prefix: r.prefix, end_ts: r.end_ts as u64 });
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
     async fn manage_subscriptions(&mut self, now: u64) -> crate::error::Result<()> {
         let mut desired: HashSet<String> = HashSet::new();
 
@@ -274,7 +464,7 @@ impl PinnedMarketWatcher {
         }
 
         // Markets to subscribe: desired but not yet subscribed.
-        let to_subscribe: Vec<Market> = desired
+        let to_subscribe: Vec<(Market, String)> = desired
             .iter()
             .filter(|id| !self.subscribed.contains(*id))
             .filter_map(|id| {
@@ -282,7 +472,7 @@ impl PinnedMarketWatcher {
                     .values()
                     .flat_map(|v| v.iter())
                     .find(|m| &m.market.id == id)
-                    .map(|m| m.market.clone())
+                    .map(|m| (m.market.clone(), m.prefix.clone()))
             })
             .collect();
 
@@ -297,7 +487,7 @@ impl PinnedMarketWatcher {
         // --- Execute subscribes ---
         if !to_subscribe.is_empty() {
             let created_at = now_ns() as i64;
-            for market in &to_subscribe {
+            for (market, prefix) in &to_subscribe {
                 let category = market.category.to_string();
                 if let Err(e) = sqlx::query!(
                     r#"
@@ -317,7 +507,7 @@ impl PinnedMarketWatcher {
                     warn!("Pinned DB insert failed for {}: {e}", market.id);
                 }
                 self.store.add_market(market.clone());
-                self.store.pin_market(&market.id);
+                self.store.pin_market(&market.id, prefix);
                 self.subscribed.insert(market.id.clone());
                 info!(
                     market_id = %market.id,
@@ -326,7 +516,8 @@ impl PinnedMarketWatcher {
                     market.question,
                 );
             }
-            if let Err(e) = self.control_tx.send(ControlMsg::Subscribe(to_subscribe)).await {
+            let markets = to_subscribe.into_iter().map(|(m, _)| m).collect();
+            if let Err(e) = self.control_tx.send(ControlMsg::Subscribe(markets)).await {
                 warn!("Failed to send Subscribe for pinned markets: {e}");
             }
         }
@@ -0,0 +1,221 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tracing::info;
+
+use crate::api::health::HealthState;
+use crate::api::latency::{LatencyStats, ParseLatency};
+use crate::config::{DetectorConfig, CHANNEL_CAPACITY};
+use crate::db::writer::DbWriter;
+use crate::detector::ShardRouter;
+use crate::error::Result;
+use crate::state::MarketStore;
+use crate::ws::capture::{read_capture, CaptureRecord};
+use crate::ws::connection::FrameHandler;
+
+/// How fast `replay_capture` feeds records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Capture timestamps divided by this factor (1.0 = original speed).
+    Factor(f64),
+    /// No pacing.
+    Max,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplaySummary {
+    pub files: u64,
+    pub records: u64,
+    pub frames: u64,
+}
+
+/// Feeds capture files through `parse_ws_frame` → `MarketStore` → detector
+/// shards → `DbWriter` into `pool`, using captured receive timestamps.
+///
+/// Each frame is fully handled by the detector before the next one is applied,
+/// so windows come out the same at any speed. `detection_latency_us` reflects
+/// replay processing time rather than the live run's.
+pub async fn replay_capture(
+    files: &[PathBuf],
+    speed: ReplaySpeed,
+    pool: sqlx::SqlitePool,
+    detector_shards: usize,
+    detector_cfg: DetectorConfig,
+) -> Result<ReplaySummary> {
+    let store = MarketStore::new();
+    let health = Arc::new(HealthState::new());
+    let (window_tx, window_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let writer = DbWriter::new(pool, window_rx, Arc::clone(&health));
+    let writer_task = tokio::spawn(async move { writer.run().await });
+
+    let router = ShardRouter::spawn(
+        detector_shards,
+        Arc::clone(&store),
+        window_tx.clone(),
+        Arc::new(LatencyStats::new()),
+        detector_cfg,
+        &health,
+    );
+    let parse_latency = Arc::new(ParseLatency::new());
+    let handler = FrameHandler::new(Arc::clone(&store), router).with_parse_latency(Arc::clone(&parse_latency));
+
+    let mut summary = ReplaySummary::default();
+    let started = Instant::now();
+    let mut first_t = None;
+    for file in files {
+        info!("[REPLAY] {}", file.display());
+        summary.files += 1;
+        for record in read_capture(file)? {
+            let record = record?;
+            summary.records += 1;
+
+            if let ReplaySpeed::Factor(factor) = speed {
+                let t0 = *first_t.get_or_insert(record.t());
+                let offset = Duration::from_nanos(record.t().saturating_sub(t0)).div_f64(factor);
+                tokio::time::sleep_until((started + offset).into()).await;
+            }
+
+            match record {
+                CaptureRecord::Markets { markets, events, .. } => {
+                    store.add_markets(markets);
+                    for event in events {
+                        store.add_event(event);
+                    }
+                }
+                CaptureRecord::Unsubscribe { market_id, .. } => store.remove_market(&market_id),
+                CaptureRecord::Frame { t, frame } => {
+                    summary.frames += 1;
+                    handler.apply_frame(&frame, t).await;
+                }
+                // Store feed state is stamped with wall-clock time, so faults are
+                // applied at replay time. Sweeping straight away closes the faulted
+                // windows before the frames that follow the fault.
+                CaptureRecord::Disconnect { asset_ids, .. } => {
+                    store.invalidate_books(&asset_ids, now_ns());
+                    handler.sweep_faults().await;
+                }
+                CaptureRecord::Stale { market_id, .. } => {
+                    store.mark_stale(&market_id, now_ns());
+                    handler.sweep_faults().await;
+                }
+            }
+            // Let the detectors and the writer catch up before the next record.
+            while handler.detector_backlog() > 0 || window_tx.capacity() < CHANNEL_CAPACITY / 2 {
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    // Dropping the router closes the detector channels; the writer ends once every
+    // detector has exited and dropped its window sender.
+    drop(handler);
+    drop(window_tx);
+    let _ = writer_task.await;
+    let parse = parse_latency.summary();
+    info!(
+        "[REPLAY] parse time per frame: p50={}ns p99={}ns max={}ns",
+        parse.p50_ns.unwrap_or(0),
+        parse.p99_ns.unwrap_or(0),
+        parse.max_ns.unwrap_or(0),
+    );
+    Ok(summary)
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory_pool;
+    use crate::types::{Category, Market};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn frame(t: u64, frame: &str) -> CaptureRecord {
+        CaptureRecord::Frame { t, frame: frame.to_string() }
+    }
+
+    fn market_record() -> CaptureRecord {
+        CaptureRecord::Markets {
+            t: 1_000_000_000,
+            markets: vec![Market {
+                id: "m1".to_string(),
+                question: "Test".to_string(),
+                category: Category::Other,
+                end_date_iso: None,
+                total_volume: None,
+                yes_token_id: "yes1".to_string(),
+                no_token_id: "no1".to_string(),
+            }],
+            events: vec![],
+        }
+    }
+
+    /// Writes `records` to a capture file named after `name` and replays it at
+    /// max speed into a fresh in-memory database.
+    async fn replay_records(name: &str, records: &[CaptureRecord]) -> (ReplaySummary, sqlx::SqlitePool) {
+        let path = std::env::temp_dir().join(format!("replay-{name}-{}.jsonl.gz", std::process::id()));
+        let mut gz = GzEncoder::new(std::fs::File::create(&path).unwrap(), Compression::fast());
+        for record in records {
+            writeln!(gz, "{}", serde_json::to_string(record).unwrap()).unwrap();
+        }
+        gz.finish().unwrap();
+
+        let pool = memory_pool().await;
+        let summary = replay_capture(std::slice::from_ref(&path), ReplaySpeed::Max, pool.clone(), 2, DetectorConfig::default())
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+        (summary, pool)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn replay_writes_windows_at_captured_times() {
+        let records = vec![
+            market_record(),
+            frame(1_000_000_000, r#"[{"event_type":"book","asset_id":"yes1","asks":[{"price":"0.45","size":"100"}],"bids":[{"price":"0.44","size":"100"}]}]"#),
+            frame(1_100_000_000, r#"[{"event_type":"book","asset_id":"no1","asks":[{"price":"0.45","size":"100"}],"bids":[{"price":"0.44","size":"100"}]}]"#),
+            frame(1_200_000_000, r#"{"event_type":"price_change","market":"0xabc","price_changes":[{"asset_id":"no1","price":"0.44","size":"50","side":"SELL"}]}"#),
+            frame(1_500_000_000, r#"{"event_type":"price_change","market":"0xabc","price_changes":[{"asset_id":"yes1","price":"0.60","size":"100","side":"SELL"},{"asset_id":"yes1","price":"0.45","size":"0","side":"SELL"}]}"#),
+        ];
+        let (summary, pool) = replay_records("times", &records).await;
+        assert_eq!(summary, ReplaySummary { files: 1, records: 5, frames: 4 });
+
+        let rows: Vec<(String, i64, Option<i64>, String)> = sqlx::query_as(
+            "SELECT market_id, opened_at, closed_at, side FROM windows WHERE window_kind = 'binary' ORDER BY opened_at",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows, vec![("m1".to_string(), 1_100_000_000, Some(1_500_000_000), "buy".to_string())]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn replay_force_closes_windows_at_captured_disconnects() {
+        let records = vec![
+            market_record(),
+            frame(1_000_000_000, r#"[{"event_type":"book","asset_id":"yes1","asks":[{"price":"0.45","size":"100"}],"bids":[{"price":"0.44","size":"100"}]}]"#),
+            frame(1_100_000_000, r#"[{"event_type":"book","asset_id":"no1","asks":[{"price":"0.45","size":"100"}],"bids":[{"price":"0.44","size":"100"}]}]"#),
+            CaptureRecord::Disconnect { t: 1_200_000_000, asset_ids: vec!["yes1".to_string(), "no1".to_string()] },
+        ];
+        let started = Instant::now();
+        let (summary, pool) = replay_records("disconnect", &records).await;
+        assert_eq!(summary, ReplaySummary { files: 1, records: 4, frames: 2 });
+        assert!(started.elapsed() < Duration::from_secs(1), "faults are swept without waiting for the 1s tick");
+
+        let rows: Vec<(i64, Option<i64>, Option<String>)> =
+            sqlx::query_as("SELECT opened_at, closed_at, close_reason FROM windows WHERE window_kind = 'binary'")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(1_100_000_000, Some(1_100_000_000), Some("feed_interrupted".to_string()))]);
+    }
+}
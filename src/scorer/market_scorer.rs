@@ -1,19 +1,30 @@
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{error, info};
 
 use crate::config::SCORER_INTERVAL_SECS;
 use crate::error::Result;
+use crate::state::MarketStore;
+use crate::types::WindowSide;
 
 /// Background task that scores markets every 60 seconds.
 /// Reads from SQLite windows table, computes composite scores,
 /// and upserts into market_stats.
 pub struct MarketScorer {
     pool: sqlx::SqlitePool,
+    /// Live books to sample into market_stats; None when scoring offline.
+    store: Option<Arc<MarketStore>>,
 }
 
 impl MarketScorer {
     pub fn new(pool: sqlx::SqlitePool) -> Self {
-        Self { pool }
+        Self { pool, store: None }
+    }
+
+    /// Also samples each market's current `MarketBookMetrics` into market_stats.
+    pub fn with_store(mut self, store: Arc<MarketStore>) -> Self {
+        self.store = Some(store);
+        self
     }
 
     pub async fn run(self) {
@@ -28,7 +39,8 @@ impl MarketScorer {
         }
     }
 
-    async fn score_all_markets(&self) -> Result<()> {
+    /// Recomputes market_stats from the last 24h of windows.
+    pub async fn score_all_markets(&self) -> Result<()> {
         let now_ns = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -46,10 +58,11 @@ impl MarketScorer {
                 SUM(CASE WHEN opportunity_class = 1 THEN 1 ELSE 0 END) as p1_windows_24h,
                 SUM(CASE WHEN opportunity_class = 2 THEN 1 ELSE 0 END) as p2_windows_24h,
                 AVG(duration_ms) as avg_duration_ms,
-                AVG(spread_size) as avg_spread,
+                AVG(COALESCE(twa_spread, spread_size)) as avg_spread,
                 MAX(spread_size) as max_spread,
                 CAST(SUM(CASE WHEN open_duration_class = 'single_tick' THEN 1 ELSE 0 END) AS REAL)
-                    / CAST(COUNT(*) AS REAL) as noise_ratio
+                    / CAST(COUNT(*) AS REAL) as noise_ratio,
+                AVG(open_book_depth) as "avg_open_book_depth?: f64"
             FROM windows
             WHERE opened_at > ?
             GROUP BY market_id
@@ -72,6 +85,7 @@ impl MarketScorer {
                 avg_duration,
                 avg_spread,
                 noise,
+                row.avg_open_book_depth,
             );
 
             sqlx::query!(
@@ -79,8 +93,8 @@ impl MarketScorer {
                 INSERT INTO market_stats (
                     market_id, windows_24h, p1_windows_24h, p2_windows_24h,
                     avg_window_duration_ms, avg_spread_size, max_spread_size, noise_ratio,
-                    opportunity_score, last_updated
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    avg_open_book_depth, opportunity_score, last_updated
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 ON CONFLICT(market_id) DO UPDATE SET
                     windows_24h = excluded.windows_24h,
                     p1_windows_24h = excluded.p1_windows_24h,
@@ -89,6 +103,7 @@ impl MarketScorer {
                     avg_spread_size = excluded.avg_spread_size,
                     max_spread_size = excluded.max_spread_size,
                     noise_ratio = excluded.noise_ratio,
+                    avg_open_book_depth = excluded.avg_open_book_depth,
                     opportunity_score = excluded.opportunity_score,
                     last_updated = excluded.last_updated
                 "#,
@@ -100,6 +115,7 @@ impl MarketScorer {
                 row.avg_spread,
                 row.max_spread,
                 row.noise_ratio,
+                row.avg_open_book_depth,
                 score,
                 now_ns,
             )
@@ -107,13 +123,62 @@ impl MarketScorer {
             .await?;
         }
 
-        info!("Scorer updated stats for {} markets", rows.len());
+        let sampled = self.sample_books(now_ns).await?;
+        info!("Scorer updated stats for {} markets ({sampled} book samples)", rows.len());
         Ok(())
     }
+
+    /// Writes every hydrated market's live book metrics into market_stats.
+    async fn sample_books(&self, now_ns: i64) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let samples: Vec<_> = store
+            .all_market_ids()
+            .into_iter()
+            .filter_map(|id| store.market_book_metrics(&id).map(|m| (id, m)))
+            .filter(|(_, m)| m.yes.ask_levels + m.no.ask_levels + m.yes.bid_levels + m.no.bid_levels > 0)
+            .collect();
+
+        let mut tx = self.pool.begin().await?;
+        for (market_id, m) in &samples {
+            let ask_depth = m.paired_depth(WindowSide::Buy);
+            let bid_depth = m.paired_depth(WindowSide::Sell);
+            let levels = (m.yes.ask_levels + m.yes.bid_levels + m.no.ask_levels + m.no.bid_levels) as i64;
+            let update_rate = m.yes.update_rate + m.no.update_rate;
+            sqlx::query!(
+                r#"
+                INSERT INTO market_stats (
+                    market_id, book_ask_depth, book_bid_depth, book_imbalance, book_levels,
+                    book_update_rate, last_updated
+                ) VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(market_id) DO UPDATE SET
+                    book_ask_depth = excluded.book_ask_depth,
+                    book_bid_depth = excluded.book_bid_depth,
+                    book_imbalance = excluded.book_imbalance,
+                    book_levels = excluded.book_levels,
+                    book_update_rate = excluded.book_update_rate,
+                    last_updated = excluded.last_updated
+                "#,
+                market_id,
+                ask_depth,
+                bid_depth,
+                m.yes.imbalance,
+                levels,
+                update_rate,
+                now_ns,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(samples.len())
+    }
 }
 
 /// Composite opportunity score (higher = better market to watch).
-/// Factors: quality-weighted window frequency (P1=2x, P2=1.5x), duration, spread, noise.
+/// Factors: quality-weighted window frequency (P1=2x, P2=1.5x), duration, spread,
+/// paired book depth at window open, noise.
 fn compute_score(
     windows_24h: i64,
     p1_windows: i64,
@@ -121,6 +186,7 @@ fn compute_score(
     avg_duration_ms: f64,
     avg_spread: f64,
     noise_ratio: f64,
+    avg_open_book_depth: Option<f64>,
 ) -> f64 {
     // Quality-weighted frequency: P1 counts 2x, P2 counts 1.5x
     let other_windows = (windows_24h - p1_windows - p2_windows).max(0) as f64;
@@ -128,7 +194,23 @@ fn compute_score(
     let frequency_score = weighted_count.min(75.0) / 75.0 * 30.0;
     let duration_score = (avg_duration_ms / 2000.0).min(1.0) * 30.0;
     let spread_score = (avg_spread / 0.10).min(1.0) * 25.0;
+    // Windows opened against 500+ shares on both legs score in full.
+    let depth_score = (avg_open_book_depth.unwrap_or(0.0) / 500.0).min(1.0) * 15.0;
     let noise_penalty = noise_ratio * 15.0;
 
-    (frequency_score + duration_score + spread_score - noise_penalty).max(0.0)
+    (frequency_score + duration_score + spread_score + depth_score - noise_penalty).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_prefers_windows_opened_against_real_size() {
+        let thin = compute_score(20, 5, 5, 1500.0, 0.03, 0.2, Some(10.0));
+        let deep = compute_score(20, 5, 5, 1500.0, 0.03, 0.2, Some(800.0));
+        let unknown = compute_score(20, 5, 5, 1500.0, 0.03, 0.2, None);
+        assert!(deep > thin && thin > unknown, "deep={deep} thin={thin} unknown={unknown}");
+        assert!((deep - thin - 14.7).abs() < 1e-9);
+    }
 }
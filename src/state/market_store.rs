@@ -1,56 +1,53 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use dashmap::{DashMap, DashSet};
 
-use crate::types::Market;
+use crate::config::{BOOK_DEPTH_BANDS, BOOK_DIVERGENCE_LIMIT, BOOK_UPDATE_RATE_TAU_SECS, DEFAULT_TICK_SIZE};
+use crate::types::{BookMetrics, Category, CloseReason, Market, MarketBookMetrics, NegRiskEvent, Price};
 
 // ---------------------------------------------------------------------------
 // OrderBook
 // ---------------------------------------------------------------------------
 
-/// Per-token order book. Prices are stored as integer keys: `(price * 10_000).round() as u32`.
-/// This avoids floating-point map keys while supporting 4 decimal places of precision.
+/// `(price, size)` levels of one book side, best first.
+pub type Ladder = Vec<(Price, f64)>;
+
+/// Per-token order book, keyed by `Price` (exact 1/10_000ths), so levels never
+/// split or merge through floating-point error.
 ///
 /// For asks, `BTreeMap::keys().next()` is O(log n) and gives the minimum (best ask).
 /// For bids, `BTreeMap::keys().next_back()` gives the maximum (best bid).
 #[derive(Debug, Default)]
 struct OrderBook {
-    /// price_key → size. Sorted ascending; minimum key = best ask.
-    asks: BTreeMap<u32, f64>,
-    /// price_key → size. Sorted ascending; maximum key = best bid.
-    bids: BTreeMap<u32, f64>,
+    /// price → size. Sorted ascending; minimum key = best ask.
+    asks: BTreeMap<Price, f64>,
+    /// price → size. Sorted ascending; maximum key = best bid.
+    bids: BTreeMap<Price, f64>,
+    /// Refreshed after every snapshot or change batch; `update_rate` as of `metrics_at_ns`.
+    metrics: BookMetrics,
+    metrics_at_ns: u64,
 }
 
 impl OrderBook {
-    #[inline]
-    fn price_key(price: f64) -> u32 {
-        (price * 10_000.0).round() as u32
-    }
-
-    #[inline]
-    fn key_to_price(key: u32) -> f64 {
-        key as f64 / 10_000.0
-    }
-
-    fn apply_snapshot(&mut self, asks: &[(f64, f64)], bids: &[(f64, f64)]) {
+    fn apply_snapshot(&mut self, asks: &[(Price, f64)], bids: &[(Price, f64)]) {
         self.asks.clear();
-        for &(price, size) in asks {
+        for &(key, size) in asks {
             if size > 0.0 {
-                self.asks.insert(Self::price_key(price), size);
+                self.asks.insert(key, size);
             }
         }
         self.bids.clear();
-        for &(price, size) in bids {
+        for &(key, size) in bids {
             if size > 0.0 {
-                self.bids.insert(Self::price_key(price), size);
+                self.bids.insert(key, size);
             }
         }
     }
 
     /// `is_ask`: true = SELL side (ask), false = BUY side (bid).
-    fn apply_change(&mut self, price: f64, is_ask: bool, size: f64) {
-        let key = Self::price_key(price);
+    fn apply_change(&mut self, key: Price, is_ask: bool, size: f64) {
         let map = if is_ask { &mut self.asks } else { &mut self.bids };
         if size == 0.0 {
             map.remove(&key);
@@ -60,31 +57,157 @@ impl OrderBook {
     }
 
     /// Minimum ask price — O(log n).
-    fn best_ask(&self) -> Option<f64> {
-        self.asks.keys().next().map(|&k| Self::key_to_price(k))
+    fn best_ask(&self) -> Option<Price> {
+        self.asks.keys().next().copied()
     }
 
     /// Maximum bid price — O(log n).
-    fn best_bid(&self) -> Option<f64> {
-        self.bids.keys().next_back().map(|&k| Self::key_to_price(k))
+    fn best_bid(&self) -> Option<Price> {
+        self.bids.keys().next_back().copied()
+    }
+
+    /// Ask levels as `(price, size)`, best (lowest) first.
+    fn ask_levels(&self) -> Vec<(Price, f64)> {
+        self.asks.iter().map(|(&k, &s)| (k, s)).collect()
+    }
+
+    /// Bid levels as `(price, size)`, best (highest) first.
+    fn bid_levels(&self) -> Vec<(Price, f64)> {
+        self.bids.iter().rev().map(|(&k, &s)| (k, s)).collect()
+    }
+
+    /// Up to `depth` levels of each side as `(asks, bids)`, best first.
+    fn top_levels(&self, depth: usize) -> (Ladder, Ladder) {
+        let asks = self.asks.iter().take(depth).map(|(&k, &s)| (k, s)).collect();
+        let bids = self.bids.iter().rev().take(depth).map(|(&k, &s)| (k, s)).collect();
+        (asks, bids)
+    }
+
+    /// Size resting at `key` on one side (0 when the level is empty).
+    fn level_size(&self, key: Price, is_ask: bool) -> f64 {
+        let map = if is_ask { &self.asks } else { &self.bids };
+        map.get(&key).copied().unwrap_or(0.0)
+    }
+
+    /// Recomputes `metrics` after an update at `now_ns`. Only the levels inside the
+    /// widest depth band are visited; level counts come from the maps.
+    fn refresh_metrics(&mut self, tick_size: Price, now_ns: u64) {
+        let widest = BOOK_DEPTH_BANDS[BOOK_DEPTH_BANDS.len() - 1];
+        let tick = tick_size.ticks().max(1);
+        let mut ask_depth = [0.0; BOOK_DEPTH_BANDS.len()];
+        let mut bid_depth = [0.0; BOOK_DEPTH_BANDS.len()];
+        if let Some(best) = self.best_ask() {
+            for (&price, &size) in self.asks.range(best..best + tick_size * widest) {
+                add_to_bands(&mut ask_depth, (price - best).ticks() / tick, size);
+            }
+        }
+        if let Some(best) = self.best_bid() {
+            for (&price, &size) in self.bids.range(best - tick_size * widest..=best).rev() {
+                add_to_bands(&mut bid_depth, (best - price).ticks() / tick, size);
+            }
+        }
+        let band = BOOK_DEPTH_BANDS.len() - 1;
+        let total = ask_depth[band] + bid_depth[band];
+
+        self.metrics = BookMetrics {
+            ask_depth,
+            bid_depth,
+            imbalance: if total > 0.0 { (bid_depth[band] - ask_depth[band]) / total } else { 0.0 },
+            ask_levels: self.asks.len(),
+            bid_levels: self.bids.len(),
+            update_rate: decayed_rate(self.metrics.update_rate, self.metrics_at_ns, now_ns)
+                + 1.0 / BOOK_UPDATE_RATE_TAU_SECS,
+        };
+        self.metrics_at_ns = now_ns;
+    }
+
+    /// Current metrics with the update rate decayed to `now_ns`.
+    fn metrics(&self, now_ns: u64) -> BookMetrics {
+        BookMetrics {
+            update_rate: decayed_rate(self.metrics.update_rate, self.metrics_at_ns, now_ns),
+            ..self.metrics
+        }
+    }
+
+    /// Sizes resting at the best ask and best bid (0 when a side is empty).
+    fn top_sizes(&self) -> (f64, f64) {
+        let ask = self.asks.values().next().copied().unwrap_or(0.0);
+        let bid = self.bids.values().next_back().copied().unwrap_or(0.0);
+        (ask, bid)
+    }
+}
+
+/// Adds a level `distance` whole ticks from the best price to every band it falls in.
+fn add_to_bands(depth: &mut [f64; BOOK_DEPTH_BANDS.len()], distance: i64, size: f64) {
+    for (band, &ticks) in depth.iter_mut().zip(BOOK_DEPTH_BANDS.iter()) {
+        if distance < ticks {
+            *band += size;
+        }
     }
 }
 
+/// Exponential decay of an updates-per-second estimate last set at `at_ns`.
+fn decayed_rate(rate: f64, at_ns: u64, now_ns: u64) -> f64 {
+    let elapsed_secs = now_ns.saturating_sub(at_ns) as f64 / 1e9;
+    rate * (-elapsed_secs / BOOK_UPDATE_RATE_TAU_SECS).exp()
+}
+
 // ---------------------------------------------------------------------------
 // TokenState — cached best prices for O(1) hot-path reads by the detector
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone)]
 pub struct TokenState {
-    pub best_ask: f64,
-    pub best_bid: f64,
+    pub best_ask: Price,
+    pub best_bid: Price,
+}
+
+/// Feed activity for one token, used for silence detection.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenActivity {
+    /// When the token was added to the store (i.e. subscribed).
+    pub subscribed_at_ns: u64,
+    /// Last book snapshot or level change (0 = never).
+    pub last_update_ns: u64,
+    /// Last full book snapshot (0 = never).
+    pub last_snapshot_ns: u64,
+}
+
+/// Why `MarketStore::silent_tokens` flagged a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Silence {
+    /// Subscribed for longer than the threshold without ever receiving a `book` snapshot.
+    NoSnapshot,
+    /// Market has an open window, but the token has not updated within the threshold.
+    Silent,
+}
+
+/// Local-vs-server consistency of one token's book.
+#[derive(Debug, Clone, Default)]
+pub struct BookIntegrity {
+    /// Price changes whose server best prices were compared with the local book.
+    pub checks: u64,
+    /// Of those, how many disagreed by more than `PRICE_DIVERGENCE_TOLERANCE`.
+    pub divergences: u64,
+    /// Divergent checks in a row; reset by an agreeing check or a snapshot.
+    pub consecutive_divergences: u32,
+    /// Last book hash the server reported for this token.
+    pub server_hash: Option<String>,
+    /// Times an unchanged server hash contradicted the local book. The hash itself
+    /// is never recomputed locally, so drift that arrives alongside a new hash is
+    /// not counted here (see `record_change_hash`).
+    pub hash_mismatches: u64,
+    /// When the book was marked corrupt (0 = not corrupt); cleared by a fresh snapshot.
+    pub corrupt_since_ns: u64,
 }
 
-/// Maps token_id → Market for fast reverse lookup (asset_id → market).
+/// Maps token_id → Market for fast reverse lookup (asset_id → market). Both ids
+/// are interned once per subscription so the frame path can hand them on without
+/// allocating.
 #[derive(Debug, Clone)]
 pub struct TokenMarketRef {
-    pub market_id: String,
-    pub is_yes: bool,
+    pub asset_id: Arc<str>,
+    pub market_id: Arc<str>,
 }
 
 // ---------------------------------------------------------------------------
@@ -96,12 +219,33 @@ pub struct MarketStore {
     markets: DashMap<String, Market>,
     /// asset_id → cached (best_ask, best_bid) for the detector hot path
     token_state: DashMap<String, TokenState>,
-    /// asset_id → (market_id, is_yes)
+    /// asset_id → owning market_id
     token_to_market: DashMap<String, TokenMarketRef>,
+    /// market_id → its (yes, no) asset ids, interned with `token_to_market`
+    market_token_ids: DashMap<String, (Arc<str>, Arc<str>)>,
     /// asset_id → live order book (maintained from WS Book subscription)
     token_books: DashMap<String, OrderBook>,
-    /// market_ids that are pinned — never removed by the regular refresh cycle
-    pinned_ids: DashSet<String>,
+    /// pinned market_id → the PINNED_SLUGS prefix it matched — never removed by the regular refresh cycle
+    pinned_ids: DashMap<String, String>,
+    /// event_id → neg-risk event (member markets are also in `markets`)
+    events: DashMap<String, Arc<NegRiskEvent>>,
+    /// member market_id → event_id
+    market_to_event: DashMap<String, String>,
+    /// asset_id → feed activity timestamps
+    token_activity: DashMap<String, TokenActivity>,
+    /// market_id → number of detector windows (any side or basket) currently open on it
+    open_windows: DashMap<String, u32>,
+    /// market_id → ns when it was marked stale; cleared once every token updates again
+    stale_since: DashMap<String, u64>,
+    /// market_id → ns when its connection dropped; cleared once every token has a fresh snapshot
+    interrupted_since: DashMap<String, u64>,
+    /// asset_ids whose cached prices were restored from a checkpoint and not yet
+    /// replaced by a snapshot; they don't count towards hydration
+    restored_tokens: DashSet<String>,
+    /// asset_id → divergence and hash checks against the server's view of the book
+    token_integrity: DashMap<String, BookIntegrity>,
+    /// market_id → tick size from `tick_size_change`; absent means `DEFAULT_TICK_SIZE`
+    tick_sizes: DashMap<String, Price>,
 }
 
 impl MarketStore {
@@ -110,51 +254,125 @@ impl MarketStore {
             markets: DashMap::new(),
             token_state: DashMap::new(),
             token_to_market: DashMap::new(),
+            market_token_ids: DashMap::new(),
             token_books: DashMap::new(),
-            pinned_ids: DashSet::new(),
+            pinned_ids: DashMap::new(),
+            events: DashMap::new(),
+            market_to_event: DashMap::new(),
+            token_activity: DashMap::new(),
+            open_windows: DashMap::new(),
+            stale_since: DashMap::new(),
+            interrupted_since: DashMap::new(),
+            restored_tokens: DashSet::new(),
+            token_integrity: DashMap::new(),
+            tick_sizes: DashMap::new(),
         })
     }
 
     /// Mark a market as pinned so the regular refresh cycle never removes it.
-    pub fn pin_market(&self, market_id: &str) {
-        self.pinned_ids.insert(market_id.to_string());
+    pub fn pin_market(&self, market_id: &str, prefix: &str) {
+        self.pinned_ids.insert(market_id.to_string(), prefix.to_string());
     }
 
     pub fn is_pinned(&self, market_id: &str) -> bool {
-        self.pinned_ids.contains(market_id)
+        self.pinned_ids.contains_key(market_id)
     }
 
-    pub fn pinned_ids(&self) -> Vec<String> {
-        self.pinned_ids.iter().map(|r| r.key().clone()).collect()
+    /// The PINNED_SLUGS prefix a pinned market was fetched for.
+    pub fn pinned_prefix(&self, market_id: &str) -> Option<String> {
+        self.pinned_ids.get(market_id).map(|p| p.value().clone())
     }
 
     pub fn add_market(&self, market: Market) {
+        let market_id: Arc<str> = Arc::from(market.id.as_str());
+        let yes_token: Arc<str> = Arc::from(market.yes_token_id.as_str());
+        let no_token: Arc<str> = Arc::from(market.no_token_id.as_str());
         self.token_to_market.insert(
             market.yes_token_id.clone(),
-            TokenMarketRef { market_id: market.id.clone(), is_yes: true },
+            TokenMarketRef { asset_id: Arc::clone(&yes_token), market_id: Arc::clone(&market_id) },
         );
         self.token_to_market.insert(
             market.no_token_id.clone(),
-            TokenMarketRef { market_id: market.id.clone(), is_yes: false },
+            TokenMarketRef { asset_id: Arc::clone(&no_token), market_id },
         );
+        self.market_token_ids.insert(market.id.clone(), (yes_token, no_token));
         self.token_books.entry(market.yes_token_id.clone()).or_default();
         self.token_books.entry(market.no_token_id.clone()).or_default();
+        let subscribed_at_ns = now_ns();
+        for token in [&market.yes_token_id, &market.no_token_id] {
+            self.token_activity.entry(token.clone()).or_insert(TokenActivity {
+                subscribed_at_ns,
+                last_update_ns: 0,
+                last_snapshot_ns: 0,
+            });
+            self.token_integrity.entry(token.clone()).or_default();
+        }
         self.markets.insert(market.id.clone(), market);
     }
 
-    pub fn markets_contains(&self, market_id: &str) -> bool {
-        self.markets.contains_key(market_id)
-    }
-
     pub fn remove_market(&self, market_id: &str) {
         if let Some((_, market)) = self.markets.remove(market_id) {
             self.token_to_market.remove(&market.yes_token_id);
             self.token_to_market.remove(&market.no_token_id);
+            self.market_token_ids.remove(market_id);
             self.token_state.remove(&market.yes_token_id);
             self.token_state.remove(&market.no_token_id);
             self.token_books.remove(&market.yes_token_id);
             self.token_books.remove(&market.no_token_id);
+            self.token_activity.remove(&market.yes_token_id);
+            self.token_activity.remove(&market.no_token_id);
+            self.token_integrity.remove(&market.yes_token_id);
+            self.token_integrity.remove(&market.no_token_id);
+            self.stale_since.remove(market_id);
+            self.interrupted_since.remove(market_id);
+            self.restored_tokens.remove(&market.yes_token_id);
+            self.restored_tokens.remove(&market.no_token_id);
+            self.open_windows.remove(market_id);
+            self.tick_sizes.remove(market_id);
+        }
+    }
+
+    /// Register a neg-risk event and add all of its member markets.
+    pub fn add_event(&self, event: NegRiskEvent) {
+        for member in &event.members {
+            self.add_market(member.clone());
+            self.market_to_event.insert(member.id.clone(), event.id.clone());
         }
+        self.events.insert(event.id.clone(), Arc::new(event));
+    }
+
+    /// Remove a neg-risk event and its member markets.
+    pub fn remove_event(&self, event_id: &str) {
+        if let Some((_, event)) = self.events.remove(event_id) {
+            for member in &event.members {
+                self.market_to_event.remove(&member.id);
+                self.remove_market(&member.id);
+            }
+        }
+    }
+
+    /// The neg-risk event a market belongs to, if any.
+    pub fn event(&self, event_id: &str) -> Option<Arc<NegRiskEvent>> {
+        self.events.get(event_id).map(|e| Arc::clone(e.value()))
+    }
+
+    pub fn event_for_market(&self, market_id: &str) -> Option<Arc<NegRiskEvent>> {
+        let event_id = self.market_to_event.get(market_id)?;
+        self.events.get(event_id.value()).map(|e| Arc::clone(e.value()))
+    }
+
+    /// Member market ids of an event, used for unsubscription.
+    pub fn event_member_ids(&self, event_id: &str) -> Option<Vec<String>> {
+        let event = self.events.get(event_id)?;
+        Some(event.members.iter().map(|m| m.id.clone()).collect())
+    }
+
+    pub fn is_event_member(&self, market_id: &str) -> bool {
+        self.market_to_event.contains_key(market_id)
+    }
+
+    pub fn event_ids(&self) -> Vec<String> {
+        self.events.iter().map(|e| e.key().clone()).collect()
     }
 
     /// Apply a full book snapshot for a token and update the cached best prices.
@@ -164,19 +382,28 @@ impl MarketStore {
     pub fn apply_book_snapshot(
         &self,
         asset_id: &str,
-        asks: &[(f64, f64)],
-        bids: &[(f64, f64)],
-    ) -> Option<(f64, f64)> {
-        if !self.token_to_market.contains_key(asset_id) {
-            return None;
+        asks: &[(Price, f64)],
+        bids: &[(Price, f64)],
+    ) -> Option<(Price, Price)> {
+        let tick_size = self.tick_size(&self.token_to_market.get(asset_id)?.market_id);
+        let now = now_ns();
+        if let Some(mut activity) = self.token_activity.get_mut(asset_id) {
+            activity.last_update_ns = now;
+            activity.last_snapshot_ns = now;
+        }
+        if let Some(mut integrity) = self.token_integrity.get_mut(asset_id) {
+            integrity.consecutive_divergences = 0;
+            integrity.corrupt_since_ns = 0;
         }
+        self.restored_tokens.remove(asset_id);
         let mut book = self.token_books.entry(asset_id.to_string()).or_default();
         book.apply_snapshot(asks, bids);
-        let best_ask = book.best_ask().unwrap_or(0.0);
-        let best_bid = book.best_bid().unwrap_or(0.0);
+        book.refresh_metrics(tick_size, now);
+        let best_ask = book.best_ask().unwrap_or(Price::ZERO);
+        let best_bid = book.best_bid().unwrap_or(Price::ZERO);
         drop(book);
 
-        if best_ask > 0.0 || best_bid > 0.0 {
+        if best_ask.is_positive() || best_bid.is_positive() {
             self.token_state.insert(
                 asset_id.to_string(),
                 TokenState { best_ask, best_bid },
@@ -194,22 +421,25 @@ impl MarketStore {
     pub fn apply_book_changes(
         &self,
         asset_id: &str,
-        changes: &[(f64, bool, f64)],
-    ) -> Option<(f64, f64)> {
-        if !self.token_to_market.contains_key(asset_id) {
-            return None;
+        changes: &[(Price, bool, f64)],
+    ) -> Option<(Price, Price)> {
+        let tick_size = self.tick_size(&self.token_to_market.get(asset_id)?.market_id);
+        let now = now_ns();
+        if let Some(mut activity) = self.token_activity.get_mut(asset_id) {
+            activity.last_update_ns = now;
         }
         let mut book = self.token_books.entry(asset_id.to_string()).or_default();
         for &(price, is_ask, size) in changes {
             book.apply_change(price, is_ask, size);
         }
-        let best_ask = book.best_ask().unwrap_or(0.0);
-        let best_bid = book.best_bid().unwrap_or(0.0);
+        book.refresh_metrics(tick_size, now);
+        let best_ask = book.best_ask().unwrap_or(Price::ZERO);
+        let best_bid = book.best_bid().unwrap_or(Price::ZERO);
         drop(book);
 
         // Only update cached state if we have a real ask price.
         // best_ask=0 means the ask side is empty — don't poison the cache.
-        if best_ask > 0.0 || best_bid > 0.0 {
+        if best_ask.is_positive() || best_bid.is_positive() {
             self.token_state.insert(
                 asset_id.to_string(),
                 TokenState { best_ask, best_bid },
@@ -219,7 +449,7 @@ impl MarketStore {
     }
 
     /// Directly update cached prices without touching the order book.
-    pub fn update_token_price(&self, asset_id: &str, best_ask: f64, best_bid: f64) {
+    pub fn update_token_price(&self, asset_id: &str, best_ask: Price, best_bid: Price) {
         self.token_state.insert(
             asset_id.to_string(),
             TokenState { best_ask, best_bid },
@@ -227,24 +457,59 @@ impl MarketStore {
     }
 
     /// Read current cached best prices for a token. Returns `(best_ask, best_bid)`.
-    pub fn best_prices(&self, asset_id: &str) -> Option<(f64, f64)> {
+    pub fn best_prices(&self, asset_id: &str) -> Option<(Price, Price)> {
         let ts = self.token_state.get(asset_id)?;
         Some((ts.best_ask, ts.best_bid))
     }
 
+    /// Full ask ladder for a token as `(price, size)` pairs, best (lowest) first.
+    /// Returns None if the token has no book.
+    pub fn ask_ladder(&self, asset_id: &str) -> Option<Vec<(Price, f64)>> {
+        self.token_books.get(asset_id).map(|book| book.ask_levels())
+    }
+
+    /// Size currently resting at `price` on one side of a token's book.
+    pub fn level_size(&self, asset_id: &str, price: Price, is_ask: bool) -> Option<f64> {
+        self.token_books.get(asset_id).map(|book| book.level_size(price, is_ask))
+    }
+
+    /// (best ask size, best bid size) for a token.
+    pub fn top_sizes(&self, asset_id: &str) -> Option<(f64, f64)> {
+        self.token_books.get(asset_id).map(|book| book.top_sizes())
+    }
+
+    /// Full bid ladder for a token as `(price, size)` pairs, best (highest) first.
+    pub fn bid_ladder(&self, asset_id: &str) -> Option<Vec<(Price, f64)>> {
+        self.token_books.get(asset_id).map(|book| book.bid_levels())
+    }
+
+    /// Top `depth` levels of a token's book as `(asks, bids)`, best first.
+    pub fn book_depth(&self, asset_id: &str, depth: usize) -> Option<(Ladder, Ladder)> {
+        self.token_books.get(asset_id).map(|book| book.top_levels(depth))
+    }
+
+    /// Depth, imbalance, level counts and update rate of a token's book.
+    pub fn book_metrics(&self, asset_id: &str) -> Option<BookMetrics> {
+        self.token_books.get(asset_id).map(|book| book.metrics(now_ns()))
+    }
+
+    /// `book_metrics` of both tokens of a binary market.
+    pub fn market_book_metrics(&self, market_id: &str) -> Option<MarketBookMetrics> {
+        let (yes_token, no_token) = self.market_tokens(market_id)?;
+        Some(MarketBookMetrics { yes: self.book_metrics(&yes_token)?, no: self.book_metrics(&no_token)? })
+    }
+
     /// Returns spread inputs for the market that owns `asset_id`:
     /// `(market_id, yes_ask, no_ask, yes_bid, no_bid)`.
     /// Returns None if either side is missing or has no real ask.
-    pub fn get_spread_inputs(&self, asset_id: &str) -> Option<(String, f64, f64, f64, f64)> {
-        let token_ref = self.token_to_market.get(asset_id)?;
-        let market_id = token_ref.market_id.clone();
-        drop(token_ref);
+    pub fn get_spread_inputs(&self, asset_id: &str) -> Option<(String, Price, Price, Price, Price)> {
+        let market_id = self.token_to_market.get(asset_id)?.market_id.to_string();
 
         let market = self.markets.get(&market_id)?;
         let yes_state = self.token_state.get(&market.yes_token_id)?;
         let no_state = self.token_state.get(&market.no_token_id)?;
 
-        if yes_state.best_ask <= 0.0 || no_state.best_ask <= 0.0 {
+        if !yes_state.best_ask.is_positive() || !no_state.best_ask.is_positive() {
             return None;
         }
 
@@ -254,13 +519,42 @@ impl MarketStore {
     /// Returns (market_id, yes_token_id, no_token_id) for the market that owns `asset_id`,
     /// without reading any prices. Used by the detector's local price cache.
     pub fn get_market_for_token(&self, asset_id: &str) -> Option<(String, String, String)> {
-        let token_ref = self.token_to_market.get(asset_id)?;
-        let market_id = token_ref.market_id.clone();
-        drop(token_ref);
+        let market_id = self.token_to_market.get(asset_id)?.market_id.to_string();
         let market = self.markets.get(&market_id)?;
         Some((market_id, market.yes_token_id.clone(), market.no_token_id.clone()))
     }
 
+    /// Interned `(asset_id, market_id)` for a token in the store.
+    pub fn token_ref(&self, asset_id: &str) -> Option<(Arc<str>, Arc<str>)> {
+        self.token_to_market
+            .get(asset_id)
+            .map(|r| (Arc::clone(&r.asset_id), Arc::clone(&r.market_id)))
+    }
+
+    /// Interned (yes_token_id, no_token_id) for a market.
+    pub fn market_tokens(&self, market_id: &str) -> Option<(Arc<str>, Arc<str>)> {
+        self.market_token_ids
+            .get(market_id)
+            .map(|ids| (Arc::clone(&ids.0), Arc::clone(&ids.1)))
+    }
+
+    /// Records a `tick_size_change` for the market owning `asset_id`. Returns the
+    /// market id, or None for a token not in the store.
+    pub fn set_tick_size(&self, asset_id: &str, tick_size: Price) -> Option<String> {
+        let market_id = self.token_to_market.get(asset_id)?.market_id.to_string();
+        self.tick_sizes.insert(market_id.clone(), tick_size);
+        Some(market_id)
+    }
+
+    /// Minimum price increment of a market.
+    pub fn tick_size(&self, market_id: &str) -> Price {
+        self.tick_sizes.get(market_id).map(|t| *t).unwrap_or(DEFAULT_TICK_SIZE)
+    }
+
+    pub fn market_category(&self, market_id: &str) -> Option<Category> {
+        self.markets.get(market_id).map(|m| m.category)
+    }
+
     pub fn get_market(&self, market_id: &str) -> Option<Market> {
         self.markets.get(market_id).map(|m| m.clone())
     }
@@ -271,14 +565,19 @@ impl MarketStore {
 
     /// Count of markets where both yes and no token prices have been received.
     pub fn hydrated_market_count(&self) -> usize {
-        self.markets
+        self.markets.iter().filter(|entry| self.market_hydrated(entry.value())).count()
+    }
+
+    /// Whether both of a market's token prices have been received. Prices restored
+    /// from a checkpoint don't count until a snapshot replaces them.
+    pub fn is_hydrated(&self, market_id: &str) -> bool {
+        self.markets.get(market_id).is_some_and(|m| self.market_hydrated(&m))
+    }
+
+    fn market_hydrated(&self, market: &Market) -> bool {
+        [&market.yes_token_id, &market.no_token_id]
             .iter()
-            .filter(|entry| {
-                let m = entry.value();
-                self.token_state.contains_key(&m.yes_token_id)
-                    && self.token_state.contains_key(&m.no_token_id)
-            })
-            .count()
+            .all(|t| self.token_state.contains_key(*t) && !self.restored_tokens.contains(*t))
     }
 
     pub fn all_asset_ids(&self) -> Vec<String> {
@@ -300,6 +599,274 @@ impl MarketStore {
     pub fn all_market_ids(&self) -> Vec<String> {
         self.markets.iter().map(|e| e.key().clone()).collect()
     }
+
+    // -----------------------------------------------------------------------
+    // Feed silence and staleness
+    // -----------------------------------------------------------------------
+
+    pub fn token_activity(&self, asset_id: &str) -> Option<TokenActivity> {
+        self.token_activity.get(asset_id).map(|a| *a)
+    }
+
+    /// Called by the detector when a window opens on `market_id`.
+    pub fn note_window_opened(&self, market_id: &str) {
+        *self.open_windows.entry(market_id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Called by the detector when a window on `market_id` closes.
+    pub fn note_window_closed(&self, market_id: &str) {
+        if let Some(mut count) = self.open_windows.get_mut(market_id) {
+            *count = count.saturating_sub(1);
+        }
+        self.open_windows.remove_if(market_id, |_, count| *count == 0);
+    }
+
+    pub fn has_open_window(&self, market_id: &str) -> bool {
+        self.open_windows.contains_key(market_id)
+    }
+
+    /// Tokens that have gone quiet as of `now_ns`, with their market. Only markets
+    /// with an open window are checked for silence: a quiet book is normal, a quiet
+    /// book holding a window open is not.
+    pub fn silent_tokens(&self, now_ns: u64, threshold_ns: u64) -> Vec<(String, String, Silence)> {
+        self.token_activity
+            .iter()
+            .filter_map(|entry| {
+                let a = entry.value();
+                let market_id = self.token_to_market.get(entry.key())?.market_id.to_string();
+                let silence = if a.last_snapshot_ns == 0 {
+                    (now_ns.saturating_sub(a.subscribed_at_ns) > threshold_ns).then_some(Silence::NoSnapshot)
+                } else {
+                    let quiet = now_ns.saturating_sub(a.last_update_ns) > threshold_ns;
+                    (quiet && self.has_open_window(&market_id)).then_some(Silence::Silent)
+                }?;
+                Some((entry.key().clone(), market_id, silence))
+            })
+            .collect()
+    }
+
+    /// Marks a market stale: the detector closes its windows and opens none until
+    /// every token of the market has updated again. Returns true if newly marked.
+    pub fn mark_stale(&self, market_id: &str, now_ns: u64) -> bool {
+        let was_stale = self.is_stale(market_id);
+        self.stale_since.insert(market_id.to_string(), now_ns);
+        !was_stale
+    }
+
+    /// Whether a market is stale. Clears the mark once both tokens have updated since it was set.
+    pub fn is_stale(&self, market_id: &str) -> bool {
+        let Some(since) = self.stale_since.get(market_id).map(|s| *s) else {
+            return false;
+        };
+        let refreshed = self.market_token_ids.get(market_id).is_some_and(|ids| {
+            [&ids.0, &ids.1].iter().all(|t| self.token_activity(t).is_some_and(|a| a.last_update_ns > since))
+        });
+        if refreshed {
+            self.stale_since.remove(market_id);
+        }
+        !refreshed
+    }
+
+    pub fn stale_market_count(&self) -> usize {
+        let ids: Vec<String> = self.stale_since.iter().map(|e| e.key().clone()).collect();
+        ids.iter().filter(|id| self.is_stale(id)).count()
+    }
+
+    /// Drops the books of `asset_ids` after their connection went down: cached
+    /// prices are removed (the markets read as unhydrated) and their markets are
+    /// marked interrupted until every token receives a fresh `book` snapshot.
+    /// Returns the number of markets marked.
+    pub fn invalidate_books<'a>(&self, asset_ids: impl IntoIterator<Item = &'a String>, now_ns: u64) -> usize {
+        let mut marked = 0;
+        for asset_id in asset_ids {
+            let Some(market_id) = self.token_to_market.get(asset_id).map(|r| r.market_id.to_string()) else {
+                continue;
+            };
+            if let Some(mut book) = self.token_books.get_mut(asset_id) {
+                *book = OrderBook::default();
+            }
+            self.token_state.remove(asset_id);
+            if let Some(mut activity) = self.token_activity.get_mut(asset_id) {
+                activity.subscribed_at_ns = now_ns;
+                activity.last_snapshot_ns = 0;
+            }
+            // The emptied book no longer matches the last hash the server reported.
+            if let Some(mut integrity) = self.token_integrity.get_mut(asset_id) {
+                integrity.server_hash = None;
+                integrity.consecutive_divergences = 0;
+            }
+            if self.interrupted_since.insert(market_id, now_ns).is_none() {
+                marked += 1;
+            }
+        }
+        marked
+    }
+
+    /// Seeds a market's cached prices with last-known values from a checkpoint.
+    /// The books stay empty and the market is marked interrupted until both
+    /// tokens receive a fresh `book` snapshot, so the prices are informational only
+    /// and the market doesn't count as hydrated.
+    pub fn restore_prices(&self, market_id: &str, yes: Option<(Price, Price)>, no: Option<(Price, Price)>, now_ns: u64) {
+        let Some((yes_token, no_token)) = self.market_tokens(market_id) else {
+            return;
+        };
+        for (asset_id, prices) in [(yes_token, yes), (no_token, no)] {
+            if let Some((best_ask, best_bid)) = prices {
+                self.update_token_price(&asset_id, best_ask, best_bid);
+                self.restored_tokens.insert(asset_id.to_string());
+            }
+        }
+        self.interrupted_since.insert(market_id.to_string(), now_ns);
+    }
+
+    /// Whether a market's books were invalidated by a disconnect. Clears the mark
+    /// once both tokens have been snapshotted since.
+    pub fn is_interrupted(&self, market_id: &str) -> bool {
+        let Some(since) = self.interrupted_since.get(market_id).map(|s| *s) else {
+            return false;
+        };
+        let resnapshotted = self.market_token_ids.get(market_id).is_some_and(|ids| {
+            [&ids.0, &ids.1].iter().all(|t| self.token_activity(t).is_some_and(|a| a.last_snapshot_ns > since))
+        });
+        if resnapshotted {
+            self.interrupted_since.remove(market_id);
+        }
+        !resnapshotted
+    }
+
+    pub fn interrupted_market_count(&self) -> usize {
+        let ids: Vec<String> = self.interrupted_since.iter().map(|e| e.key().clone()).collect();
+        ids.iter().filter(|id| self.is_interrupted(id)).count()
+    }
+
+    // -----------------------------------------------------------------------
+    // Book integrity
+    // -----------------------------------------------------------------------
+
+    pub fn book_integrity(&self, asset_id: &str) -> Option<BookIntegrity> {
+        self.token_integrity.get(asset_id).map(|i| i.clone())
+    }
+
+    /// Records one comparison of the local best prices with the server's. Marks the
+    /// book corrupt after `BOOK_DIVERGENCE_LIMIT` divergent checks in a row.
+    /// Returns true if the book was newly marked.
+    pub fn record_price_check(&self, asset_id: &str, diverged: bool, now_ns: u64) -> bool {
+        let Some(mut integrity) = self.token_integrity.get_mut(asset_id) else {
+            return false;
+        };
+        integrity.checks += 1;
+        if !diverged {
+            integrity.consecutive_divergences = 0;
+            return false;
+        }
+        integrity.divergences += 1;
+        integrity.consecutive_divergences += 1;
+        integrity.consecutive_divergences >= BOOK_DIVERGENCE_LIMIT && Self::mark_corrupt(&mut integrity, now_ns)
+    }
+
+    /// Records the server's book hash after a level change. The hash is opaque —
+    /// the server's hashing scheme isn't reproduced locally — so it is only used as
+    /// a change marker: the server hash only changes when its book does, so an
+    /// unchanged hash alongside a change that moved the local book means the two
+    /// disagree and the book is marked corrupt. A change that moves both books but
+    /// leaves them different goes unnoticed; the best-price checks cover that.
+    /// Returns true if the book was newly marked.
+    pub fn record_change_hash(&self, asset_id: &str, hash: &str, local_changed: bool, now_ns: u64) -> bool {
+        let Some(mut integrity) = self.token_integrity.get_mut(asset_id) else {
+            return false;
+        };
+        let unchanged = integrity.server_hash.as_deref() == Some(hash);
+        if !unchanged {
+            integrity.server_hash = Some(hash.to_string());
+        }
+        if unchanged && local_changed {
+            integrity.hash_mismatches += 1;
+            return Self::mark_corrupt(&mut integrity, now_ns);
+        }
+        false
+    }
+
+    /// Records the hash of an incoming `book` snapshot, before it is applied. A
+    /// snapshot carrying the hash the server last reported describes the state the
+    /// local book should already be in; returns false (and counts a mismatch) if it
+    /// isn't. The snapshot then corrects the book, so nothing is marked corrupt;
+    /// callers close the windows priced off the drifted book instead.
+    pub fn record_snapshot_hash(&self, asset_id: &str, hash: &str, asks: &[(Price, f64)], bids: &[(Price, f64)]) -> bool {
+        let Some(mut integrity) = self.token_integrity.get_mut(asset_id) else {
+            return true;
+        };
+        if integrity.server_hash.as_deref() != Some(hash) {
+            integrity.server_hash = Some(hash.to_string());
+            return true;
+        }
+        let mut expected = OrderBook::default();
+        expected.apply_snapshot(asks, bids);
+        let matches = self
+            .token_books
+            .get(asset_id)
+            .is_some_and(|book| book.asks == expected.asks && book.bids == expected.bids);
+        if !matches {
+            integrity.hash_mismatches += 1;
+        }
+        matches
+    }
+
+    fn mark_corrupt(integrity: &mut BookIntegrity, now_ns: u64) -> bool {
+        if integrity.corrupt_since_ns != 0 {
+            return false;
+        }
+        integrity.corrupt_since_ns = now_ns.max(1);
+        true
+    }
+
+    /// Whether a token's book is marked corrupt (until its next snapshot).
+    pub fn is_book_corrupt(&self, asset_id: &str) -> bool {
+        self.token_integrity.get(asset_id).is_some_and(|i| i.corrupt_since_ns != 0)
+    }
+
+    /// Asset ids whose books are currently marked corrupt.
+    pub fn corrupt_tokens(&self) -> Vec<String> {
+        self.token_integrity
+            .iter()
+            .filter(|e| e.corrupt_since_ns != 0)
+            .map(|e| e.key().clone())
+            .collect()
+    }
+
+    /// `(checks, divergences, hash_mismatches)` summed over every token.
+    pub fn integrity_totals(&self) -> (u64, u64, u64) {
+        self.token_integrity.iter().fold((0, 0, 0), |(c, d, h), e| {
+            (c + e.checks, d + e.divergences, h + e.hash_mismatches)
+        })
+    }
+
+    /// Why a market's feed can't be trusted right now, as the close reason for its
+    /// open windows: a disconnect takes precedence over a corrupt book, and a
+    /// corrupt book over silence.
+    pub fn feed_fault(&self, market_id: &str) -> Option<CloseReason> {
+        // Reads the interned token ids in place: this runs on every detector tick.
+        let corrupt = || {
+            self.market_token_ids
+                .get(market_id)
+                .is_some_and(|ids| self.is_book_corrupt(&ids.0) || self.is_book_corrupt(&ids.1))
+        };
+        if self.is_interrupted(market_id) {
+            Some(CloseReason::FeedInterrupted)
+        } else if corrupt() {
+            Some(CloseReason::BookCorrupt)
+        } else if self.is_stale(market_id) {
+            Some(CloseReason::FeedStale)
+        } else {
+            None
+        }
+    }
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
 }
 
 impl Default for MarketStore {
@@ -308,8 +875,18 @@ impl Default for MarketStore {
             markets: DashMap::new(),
             token_state: DashMap::new(),
             token_to_market: DashMap::new(),
+            market_token_ids: DashMap::new(),
             token_books: DashMap::new(),
-            pinned_ids: DashSet::new(),
+            pinned_ids: DashMap::new(),
+            events: DashMap::new(),
+            market_to_event: DashMap::new(),
+            token_activity: DashMap::new(),
+            open_windows: DashMap::new(),
+            stale_since: DashMap::new(),
+            interrupted_since: DashMap::new(),
+            restored_tokens: DashSet::new(),
+            token_integrity: DashMap::new(),
+            tick_sizes: DashMap::new(),
         }
     }
 }
@@ -321,7 +898,10 @@ impl Default for MarketStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{Category, Market};
+
+    fn ticks(n: i64) -> Price {
+        Price::from_ticks(n)
+    }
 
     fn test_market() -> Market {
         Market {
@@ -342,13 +922,54 @@ mod tests {
 
         let result = store.apply_book_snapshot(
             "yes1",
-            &[(0.55, 100.0), (0.60, 50.0)],
-            &[(0.54, 200.0), (0.50, 75.0)],
+            &[(ticks(5500), 100.0), (ticks(6000), 50.0)],
+            &[(ticks(5400), 200.0), (ticks(5000), 75.0)],
         );
         assert!(result.is_some());
         let (best_ask, best_bid) = result.unwrap();
-        assert!((best_ask - 0.55).abs() < 1e-6, "best_ask={best_ask}");
-        assert!((best_bid - 0.54).abs() < 1e-6, "best_bid={best_bid}");
+        assert_eq!(best_ask, ticks(5500));
+        assert_eq!(best_bid, ticks(5400));
+    }
+
+    #[test]
+    fn book_metrics_track_bands_imbalance_and_levels_incrementally() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        store.apply_book_snapshot(
+            "yes1",
+            &[(ticks(5000), 100.0), (ticks(5100), 50.0), (ticks(5400), 20.0), (ticks(5500), 999.0)],
+            &[(ticks(4900), 30.0), (ticks(4700), 70.0)],
+        );
+        let m = store.book_metrics("yes1").unwrap();
+        assert_eq!(m.ask_depth, [100.0, 150.0, 170.0], "0.55 is 5 ticks out, past the widest band");
+        assert_eq!(m.bid_depth, [30.0, 30.0, 100.0]);
+        assert!((m.imbalance - (100.0 - 170.0) / 270.0).abs() < 1e-12);
+        assert_eq!((m.ask_levels, m.bid_levels), (4, 2));
+        assert!(m.update_rate > 0.0);
+
+        // Lifting the best ask re-anchors the bands on 0.51.
+        store.apply_book_changes("yes1", &[(ticks(5000), true, 0.0)]);
+        let m = store.book_metrics("yes1").unwrap();
+        assert_eq!(m.ask_depth, [50.0, 50.0, 1069.0]);
+        assert_eq!(m.ask_levels, 3);
+        assert_eq!(store.market_book_metrics("market1").unwrap().no, BookMetrics::default());
+    }
+
+    #[test]
+    fn book_depth_returns_top_levels_best_first() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        store.apply_book_snapshot(
+            "yes1",
+            &[(ticks(6000), 50.0), (ticks(5500), 100.0), (ticks(6500), 10.0)],
+            &[(ticks(5000), 75.0), (ticks(5400), 200.0)],
+        );
+
+        let (asks, bids) = store.book_depth("yes1", 2).unwrap();
+        assert_eq!(asks, vec![(ticks(5500), 100.0), (ticks(6000), 50.0)]);
+        assert_eq!(bids, vec![(ticks(5400), 200.0), (ticks(5000), 75.0)]);
+        assert_eq!(store.book_depth("no1", 2), Some((vec![], vec![])), "subscribed but not yet snapshotted");
+        assert!(store.book_depth("unknown", 2).is_none());
     }
 
     #[test]
@@ -357,13 +978,13 @@ mod tests {
         store.add_market(test_market());
 
         // Seed book: asks at 0.55 and 0.60
-        store.apply_book_snapshot("yes1", &[(0.55, 100.0), (0.60, 50.0)], &[]);
+        store.apply_book_snapshot("yes1", &[(ticks(5500), 100.0), (ticks(6000), 50.0)], &[]);
 
         // Remove the best ask (size=0 means cancelled)
-        let result = store.apply_book_changes("yes1", &[(0.55, true, 0.0)]);
+        let result = store.apply_book_changes("yes1", &[(ticks(5500), true, 0.0)]);
         assert!(result.is_some());
         let (best_ask, _) = result.unwrap();
-        assert!((best_ask - 0.60).abs() < 1e-6, "best_ask should have moved to 0.60, got {best_ask}");
+        assert_eq!(best_ask, ticks(6000), "best_ask should have moved to 0.60");
     }
 
     #[test]
@@ -371,7 +992,7 @@ mod tests {
         let store = MarketStore::new();
         store.add_market(test_market());
 
-        let result = store.apply_book_snapshot("unknown_token", &[(0.55, 100.0)], &[]);
+        let result = store.apply_book_snapshot("unknown_token", &[(ticks(5500), 100.0)], &[]);
         assert!(result.is_none());
     }
 
@@ -381,15 +1002,167 @@ mod tests {
         store.add_market(test_market());
 
         // Only one side populated — should return None
-        store.apply_book_snapshot("yes1", &[(0.55, 100.0)], &[]);
+        store.apply_book_snapshot("yes1", &[(ticks(5500), 100.0)], &[]);
         assert!(store.get_spread_inputs("yes1").is_none());
 
         // Both sides populated — should return Some
-        store.apply_book_snapshot("no1", &[(0.46, 100.0)], &[]);
+        store.apply_book_snapshot("no1", &[(ticks(4600), 100.0)], &[]);
         let result = store.get_spread_inputs("yes1");
         assert!(result.is_some());
         let (_, yes_ask, no_ask, _, _) = result.unwrap();
-        assert!((yes_ask - 0.55).abs() < 1e-6);
-        assert!((no_ask - 0.46).abs() < 1e-6);
+        assert_eq!((yes_ask, no_ask), (ticks(5500), ticks(4600)));
+    }
+
+    #[test]
+    fn ladders_are_sorted_best_first() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+
+        store.apply_book_snapshot(
+            "yes1",
+            &[(ticks(6000), 50.0), (ticks(5500), 100.0), (ticks(5800), 0.0)],
+            &[(ticks(5000), 10.0), (ticks(5400), 20.0)],
+        );
+        let ladder = store.ask_ladder("yes1").unwrap();
+        assert_eq!(ladder.len(), 2, "size=0 levels must be skipped");
+        assert_eq!((ladder[0].0, ladder[1].0), (ticks(5500), ticks(6000)));
+        let bids = store.bid_ladder("yes1").unwrap();
+        assert_eq!((bids[0].0, bids[1].0), (ticks(5400), ticks(5000)));
+        assert!(store.ask_ladder("unknown_token").is_none());
+    }
+
+    #[test]
+    fn event_membership_and_removal() {
+        let store = MarketStore::new();
+        let mut second = test_market();
+        second.id = "market2".to_string();
+        second.yes_token_id = "yes2".to_string();
+        second.no_token_id = "no2".to_string();
+        store.add_event(NegRiskEvent {
+            id: "event1".to_string(),
+            title: "Who wins?".to_string(),
+            category: Category::Politics,
+            end_date_iso: None,
+            members: vec![test_market(), second],
+        });
+
+        assert_eq!(store.market_count(), 2);
+        assert!(store.is_event_member("market2"));
+        assert_eq!(store.event_for_market("market1").unwrap().id, "event1");
+
+        store.remove_event("event1");
+        assert_eq!(store.market_count(), 0);
+        assert!(store.event_for_market("market1").is_none());
+        assert!(store.event_ids().is_empty());
+    }
+
+    #[test]
+    fn silent_tokens_and_stale_marks_clear_on_update() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        let added = now_ns();
+        let threshold = 5_000_000_000;
+
+        let mut silent = store.silent_tokens(added + 2 * threshold, threshold);
+        silent.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            silent,
+            vec![
+                ("no1".to_string(), "market1".to_string(), Silence::NoSnapshot),
+                ("yes1".to_string(), "market1".to_string(), Silence::NoSnapshot),
+            ]
+        );
+
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        store.apply_book_snapshot("no1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        let later = now_ns() + 2 * threshold;
+        assert!(store.silent_tokens(later, threshold).is_empty(), "quiet books without a window are fine");
+
+        store.note_window_opened("market1");
+        assert_eq!(store.silent_tokens(later, threshold).len(), 2);
+        assert!(store.mark_stale("market1", now_ns()));
+        assert!(!store.mark_stale("market1", now_ns()), "already stale");
+        assert!(store.is_stale("market1"));
+        assert_eq!(store.stale_market_count(), 1);
+
+        store.apply_book_changes("yes1", &[(ticks(4500), true, 5.0)]);
+        assert!(store.is_stale("market1"), "one leg updated is not enough");
+        store.apply_book_changes("no1", &[(ticks(4500), true, 5.0)]);
+        assert!(!store.is_stale("market1"));
+        assert_eq!(store.stale_market_count(), 0);
+
+        store.note_window_closed("market1");
+        assert!(!store.has_open_window("market1"));
+    }
+
+    #[test]
+    fn invalidated_books_stay_interrupted_until_both_legs_resnapshot() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        store.apply_book_snapshot("no1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        assert_eq!(store.hydrated_market_count(), 1);
+
+        let assets = ["yes1".to_string(), "no1".to_string()];
+        assert_eq!(store.invalidate_books(&assets, now_ns()), 1);
+        assert_eq!(store.hydrated_market_count(), 0);
+        assert!(store.best_prices("yes1").is_none());
+        assert!(store.ask_ladder("yes1").unwrap_or_default().is_empty());
+        assert_eq!(store.feed_fault("market1"), Some(CloseReason::FeedInterrupted));
+
+        // Level changes alone don't end the interruption; snapshots do.
+        store.apply_book_changes("yes1", &[(ticks(4500), true, 5.0)]);
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        assert!(store.is_interrupted("market1"));
+        store.apply_book_snapshot("no1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        assert!(!store.is_interrupted("market1"));
+        assert_eq!(store.feed_fault("market1"), None);
+        assert_eq!(store.interrupted_market_count(), 0);
+    }
+
+    #[test]
+    fn persistent_divergence_marks_book_corrupt_until_resnapshot() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        store.apply_book_snapshot("no1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+
+        for _ in 0..BOOK_DIVERGENCE_LIMIT - 1 {
+            assert!(!store.record_price_check("yes1", true, now_ns()));
+        }
+        assert!(!store.record_price_check("yes1", false, now_ns()), "an agreeing check resets the run");
+        for _ in 0..BOOK_DIVERGENCE_LIMIT - 1 {
+            assert!(!store.record_price_check("yes1", true, now_ns()));
+        }
+        assert!(store.record_price_check("yes1", true, now_ns()));
+        assert!(!store.record_price_check("yes1", true, now_ns()), "already corrupt");
+        assert_eq!(store.integrity_totals(), (2 * BOOK_DIVERGENCE_LIMIT as u64 + 1, 2 * BOOK_DIVERGENCE_LIMIT as u64, 0));
+        assert_eq!(store.corrupt_tokens(), vec!["yes1".to_string()]);
+        assert_eq!(store.feed_fault("market1"), Some(CloseReason::BookCorrupt));
+
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[(ticks(4000), 10.0)]);
+        assert!(!store.is_book_corrupt("yes1"));
+        assert_eq!(store.feed_fault("market1"), None);
+    }
+
+    #[test]
+    fn unchanged_server_hash_must_match_local_book() {
+        let store = MarketStore::new();
+        store.add_market(test_market());
+        assert!(store.record_snapshot_hash("yes1", "h1", &[(ticks(5000), 10.0)], &[]));
+        store.apply_book_snapshot("yes1", &[(ticks(5000), 10.0)], &[]);
+        assert!(store.record_snapshot_hash("yes1", "h1", &[(ticks(5000), 10.0)], &[]), "same hash, same book");
+        assert!(!store.record_snapshot_hash("yes1", "h1", &[(ticks(5000), 12.0)], &[]));
+        assert!(!store.is_book_corrupt("yes1"), "the snapshot corrects the book, so it isn't marked");
+
+        assert!(!store.record_change_hash("yes1", "h2", true, now_ns()), "new hash, book moved");
+        assert!(!store.record_change_hash("yes1", "h2", false, now_ns()), "same hash, book unchanged");
+        assert!(store.record_change_hash("yes1", "h2", true, now_ns()));
+        assert!(store.is_book_corrupt("yes1"));
+        assert_eq!(store.book_integrity("yes1").unwrap().hash_mismatches, 2);
+
+        // A reconnect empties the book, so the old hash no longer describes it.
+        store.invalidate_books(&["yes1".to_string()], now_ns());
+        assert!(store.record_snapshot_hash("yes1", "h2", &[(ticks(5000), 10.0)], &[]));
     }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Instant;
 
 // ---------------------------------------------------------------------------
@@ -16,7 +17,7 @@ pub struct Market {
     pub no_token_id: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Category {
     Sports,
@@ -27,6 +28,18 @@ pub enum Category {
     Other,
 }
 
+/// A multi-outcome neg-risk Gamma event. Each member is a binary market and
+/// exactly one member resolves YES, so the member set forms a complete basket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegRiskEvent {
+    /// Gamma event id.
+    pub id: String,
+    pub title: String,
+    pub category: Category,
+    pub end_date_iso: Option<String>,
+    pub members: Vec<Market>,
+}
+
 impl std::fmt::Display for Category {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -41,6 +54,158 @@ impl std::fmt::Display for Category {
     }
 }
 
+impl Category {
+    /// Parses a category name as written by `Display`, ignoring case.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "sports" => Some(Category::Sports),
+            "weather" => Some(Category::Weather),
+            "crypto" => Some(Category::Crypto),
+            "politics" => Some(Category::Politics),
+            "economics" => Some(Category::Economics),
+            "other" => Some(Category::Other),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Fixed-point prices
+// ---------------------------------------------------------------------------
+
+/// A price in ticks of 1/10_000th of a dollar, the finest increment Polymarket
+/// quotes. Sums, spreads and comparisons are exact, so equal prices compare equal
+/// and book levels key and hash reliably. Signed, so spreads (which go negative)
+/// are `Price`s too. Serialises as a decimal number (`0.55`) for the API and as
+/// REAL for SQLite, the same as the `f64` prices it replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(i64);
+
+impl Price {
+    /// Ticks per dollar.
+    pub const SCALE: i64 = 10_000;
+    pub const ZERO: Price = Price(0);
+    pub const ONE: Price = Price(Self::SCALE);
+
+    pub const fn from_ticks(ticks: i64) -> Self {
+        Price(ticks)
+    }
+
+    pub const fn ticks(self) -> i64 {
+        self.0
+    }
+
+    /// Rounds to the nearest tick.
+    pub fn from_f64(price: f64) -> Self {
+        Price((price * Self::SCALE as f64).round() as i64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+
+    /// Parses a decimal price string such as `"0.55"` without going through `f64`
+    /// for plain decimals of up to 4 places. Anything else (more places,
+    /// exponents) falls back to `from_f64(s.parse::<f64>())`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if int.len() > 5 || frac.len() > 4 || (int.is_empty() && frac.is_empty()) || !digits(int) || !digits(frac) {
+            return s.parse::<f64>().ok().filter(|p| p.is_finite()).map(Self::from_f64);
+        }
+        let mut ticks = 0i64;
+        for b in int.bytes().chain(frac.bytes()).chain(std::iter::repeat_n(b'0', 4 - frac.len())) {
+            ticks = ticks * 10 + i64::from(b - b'0');
+        }
+        Some(Price(ticks))
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn abs(self) -> Self {
+        Price(self.0.abs())
+    }
+
+    /// Rounds to the nearest multiple of `tick` (halves round up); a non-positive
+    /// `tick` leaves the price as is.
+    pub fn round_to(self, tick: Price) -> Self {
+        if tick.0 <= 0 {
+            return self;
+        }
+        let rem = self.0.rem_euclid(tick.0);
+        Price(if 2 * rem >= tick.0 { self.0 - rem + tick.0 } else { self.0 - rem })
+    }
+}
+
+impl std::ops::Add for Price {
+    type Output = Price;
+    fn add(self, rhs: Price) -> Price {
+        Price(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Sub for Price {
+    type Output = Price;
+    fn sub(self, rhs: Price) -> Price {
+        Price(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Mul<i64> for Price {
+    type Output = Price;
+    fn mul(self, rhs: i64) -> Price {
+        Price(self.0 * rhs)
+    }
+}
+
+impl std::ops::AddAssign for Price {
+    fn add_assign(&mut self, rhs: Price) {
+        self.0 += rhs.0;
+    }
+}
+
+impl std::iter::Sum for Price {
+    fn sum<I: Iterator<Item = Price>>(iter: I) -> Price {
+        Price(iter.map(|p| p.0).sum())
+    }
+}
+
+/// Formats as a decimal, honouring precision: `format!("{:.4}", p)`.
+impl std::fmt::Display for Price {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_f64(), f)
+    }
+}
+
+impl Serialize for Price {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Price::from_f64)
+    }
+}
+
+impl sqlx::Type<sqlx::Sqlite> for Price {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <f64 as sqlx::Type<sqlx::Sqlite>>::type_info()
+    }
+}
+
+impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for Price {
+    fn encode_by_ref(
+        &self,
+        buf: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'q>>,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <f64 as sqlx::Encode<'q, sqlx::Sqlite>>::encode(self.to_f64(), buf)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Spread classification
 // ---------------------------------------------------------------------------
@@ -48,9 +213,9 @@ impl std::fmt::Display for Category {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SpreadCategory {
-    /// spread < $0.02 — below viable threshold
+    /// spread < 2 ticks ($0.02 at a 0.01 tick) — below viable threshold
     Noise,
-    /// spread $0.02–$0.05
+    /// spread 2 ticks–$0.05
     Small,
     /// spread $0.05–$0.10
     Medium,
@@ -59,13 +224,16 @@ pub enum SpreadCategory {
 }
 
 impl SpreadCategory {
-    pub fn from_spread(spread: f64) -> Self {
+    /// Categorises a spread on a market quoted in `tick_size` increments. The noise
+    /// floor is measured in ticks; the dollar thresholds above it never sit below it.
+    pub fn from_spread(spread: Price, tick_size: Price) -> Self {
         use crate::config::spread_thresholds::*;
-        if spread < NOISE_MAX {
+        let noise_max = tick_size * NOISE_MAX_TICKS;
+        if spread < noise_max {
             SpreadCategory::Noise
-        } else if spread < SMALL_MAX {
+        } else if spread < SMALL_MAX.max(noise_max) {
             SpreadCategory::Small
-        } else if spread < MEDIUM_MAX {
+        } else if spread < MEDIUM_MAX.max(noise_max) {
             SpreadCategory::Medium
         } else {
             SpreadCategory::Large
@@ -89,13 +257,91 @@ impl std::fmt::Display for SpreadCategory {
 // Window classification
 // ---------------------------------------------------------------------------
 
+/// Which basket a window prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    /// YES + NO of one binary market; pays $1.
+    Binary,
+    /// YES on every member of a neg-risk event; pays $1.
+    NegRiskYes,
+    /// NO on every member of a neg-risk event; pays N-1 dollars.
+    NegRiskNo,
+}
+
+impl std::fmt::Display for WindowKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            WindowKind::Binary => "binary",
+            WindowKind::NegRiskYes => "neg_risk_yes",
+            WindowKind::NegRiskNo => "neg_risk_no",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Which side of the book a window trades against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowSide {
+    /// Buy both legs at the asks: `yes_ask + no_ask < 1`.
+    Buy,
+    /// Sell both legs at the bids: `yes_bid + no_bid > 1`. Profitable for
+    /// inventory holders, or by splitting $1 of collateral into a YES+NO pair.
+    Sell,
+}
+
+impl std::fmt::Display for WindowSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            WindowSide::Buy => "buy",
+            WindowSide::Sell => "sell",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Confirmation thresholds a window must meet before it counts as real (multi_tick).
+/// Both conditions must hold; resolved per market by `config::ConfirmationRules`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfirmationRule {
+    /// Minimum consecutive arb ticks (including the opening tick).
+    pub min_ticks: u32,
+    /// Minimum time since the opening tick, in milliseconds.
+    pub min_elapsed_ms: u64,
+    /// Where the rule came from: `default`, `category:<name>` or `pinned:<prefix>`.
+    pub source: String,
+}
+
+impl ConfirmationRule {
+    pub fn is_met(&self, tick_count: u32, elapsed_ms: f64) -> bool {
+        tick_count >= self.min_ticks && elapsed_ms >= self.min_elapsed_ms as f64
+    }
+}
+
+impl Default for ConfirmationRule {
+    fn default() -> Self {
+        Self {
+            min_ticks: crate::config::MIN_ARB_TICKS,
+            min_elapsed_ms: 0,
+            source: "default".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfirmationRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ticks>={} elapsed>={}ms", self.source, self.min_ticks, self.min_elapsed_ms)
+    }
+}
+
 /// Dimension 1 — was this a real order?
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum OpenDurationClass {
-    /// Opened and closed within 1 tick — stale order noise.
+    /// Closed before meeting its confirmation rule — stale order noise.
     SingleTick,
-    /// Survived 2+ consecutive ticks — real opportunity.
+    /// Met its confirmation rule (ticks and/or elapsed time) — real opportunity.
     MultiTick,
 }
 
@@ -120,6 +366,16 @@ pub enum CloseReason {
     PriceDrift,
     /// Order disappeared, no trade, no drift. Priority 4 — manually cancelled.
     OrderVanished,
+    /// Force-closed because a leg's feed went silent. Priority 0 — the window's
+    /// end was never observed.
+    FeedStale,
+    /// Force-closed because the leg's WebSocket connection dropped. Priority 0 —
+    /// the reconnect gap is not window time.
+    FeedInterrupted,
+    /// Force-closed because a leg's local book disagreed with the server (persistent
+    /// best-price divergence or a contradicting book hash). Priority 0 — the window
+    /// may never have been real.
+    BookCorrupt,
 }
 
 impl std::fmt::Display for CloseReason {
@@ -129,15 +385,32 @@ impl std::fmt::Display for CloseReason {
             CloseReason::VolumeSpikeInstant => "volume_spike_instant",
             CloseReason::PriceDrift => "price_drift",
             CloseReason::OrderVanished => "order_vanished",
+            CloseReason::FeedStale => "feed_stale",
+            CloseReason::FeedInterrupted => "feed_interrupted",
+            CloseReason::BookCorrupt => "book_corrupt",
         };
         write!(f, "{s}")
     }
 }
 
+impl CloseReason {
+    /// Parses a close reason the detector sets itself rather than the classifier
+    /// (feed faults). Reclassification keeps these as stored.
+    pub fn forced_from_str(s: &str) -> Option<Self> {
+        match s {
+            "feed_stale" => Some(CloseReason::FeedStale),
+            "feed_interrupted" => Some(CloseReason::FeedInterrupted),
+            "book_corrupt" => Some(CloseReason::BookCorrupt),
+            _ => None,
+        }
+    }
+}
+
 /// Combined opportunity priority (1=best, 4=lowest, 0=noise/ignore).
 pub fn opportunity_class(open_class: OpenDurationClass, close_reason: Option<CloseReason>) -> u8 {
     match (open_class, close_reason) {
         (OpenDurationClass::SingleTick, _) => 0,
+        (_, Some(CloseReason::FeedStale | CloseReason::FeedInterrupted | CloseReason::BookCorrupt)) => 0,
         (OpenDurationClass::MultiTick, Some(CloseReason::VolumeSpikeGradual)) => 1,
         (OpenDurationClass::MultiTick, Some(CloseReason::PriceDrift)) => 2,
         (OpenDurationClass::MultiTick, Some(CloseReason::VolumeSpikeInstant)) => 3,
@@ -150,15 +423,128 @@ pub fn opportunity_class(open_class: OpenDurationClass, close_reason: Option<Clo
 // Raw observables stored alongside every window
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct WindowObservables {
     pub tick_count: u32,
+    /// Milliseconds from the opening tick to the closing tick.
+    pub elapsed_ms: f64,
     /// True if a last_trade_price event fired while this window was open.
     pub trade_event_fired: bool,
     /// Number of ticks the volume change spanned (0 if no trade event).
     pub volume_change_ticks: u32,
     /// True if ask price moved gradually before close.
     pub price_shifted: bool,
+    /// Widest spread on any arb tick, and when it was seen.
+    pub peak_spread: Price,
+    pub peak_spread_at_ns: u64,
+    /// Spread on the last arb tick before close.
+    pub closing_spread: Price,
+    /// Spread averaged over the window's lifetime, each arb tick held until the next.
+    pub twa_spread: f64,
+    /// Per-leg ask range over the arb ticks (summed asks for neg-risk baskets).
+    pub min_yes_ask: Price,
+    pub max_yes_ask: Price,
+    pub min_no_ask: Price,
+    pub max_no_ask: Price,
+}
+
+// ---------------------------------------------------------------------------
+// Executable depth — how much of a window can actually be filled
+// ---------------------------------------------------------------------------
+
+/// Result of walking both ladders of a window's side
+/// (see `detector::depth::walk_ask_ladders` / `walk_bid_ladders`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ArbDepth {
+    /// Largest paired YES+NO size (shares) whose combined price is still on the profitable side of $1.00.
+    pub size: f64,
+    /// Volume-weighted combined price per pair across all filled levels
+    /// (cost when buying, proceeds when selling).
+    pub vwap_cost: f64,
+    /// Expected dollar profit if the full size is filled: `size * |1 - vwap_cost|`.
+    pub profit: f64,
+    /// `profit` after taker fees and fixed per-trade costs (see `config::FeeModel`).
+    pub net_profit: f64,
+}
+
+/// Liquidity shape of one token's book (see `MarketStore::book_metrics`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct BookMetrics {
+    /// Size resting within each of `config::BOOK_DEPTH_BANDS` ticks of the best
+    /// ask / bid (1 tick = the best level alone).
+    pub ask_depth: [f64; 3],
+    pub bid_depth: [f64; 3],
+    /// `(bid - ask) / (bid + ask)` over the widest band: +1 only bids, -1 only asks.
+    pub imbalance: f64,
+    pub ask_levels: usize,
+    pub bid_levels: usize,
+    /// Book updates (snapshots and change batches) per second, decayed to the read time.
+    pub update_rate: f64,
+}
+
+/// `BookMetrics` of both tokens of a binary market at one moment.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MarketBookMetrics {
+    pub yes: BookMetrics,
+    pub no: BookMetrics,
+}
+
+impl MarketBookMetrics {
+    /// Size both legs rest on `side` within the widest depth band. Only paired
+    /// size is executable, so this is the thinner leg's.
+    pub fn paired_depth(&self, side: WindowSide) -> f64 {
+        let band = self.yes.ask_depth.len() - 1;
+        match side {
+            WindowSide::Buy => self.yes.ask_depth[band].min(self.no.ask_depth[band]),
+            WindowSide::Sell => self.yes.bid_depth[band].min(self.no.bid_depth[band]),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tick trace — how a window evolved
+// ---------------------------------------------------------------------------
+
+/// What produced a `WindowTick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowTickKind {
+    /// A price update for the window's market (or any neg-risk member).
+    Price,
+    /// A last_trade_price event on one of the window's tokens.
+    Trade,
+}
+
+impl std::fmt::Display for WindowTickKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowTickKind::Price => write!(f, "price"),
+            WindowTickKind::Trade => write!(f, "trade"),
+        }
+    }
+}
+
+/// One entry of a window's tick trace.
+/// Neg-risk windows carry summed member prices and no sizes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowTick {
+    /// Nanosecond UTC epoch the tick's frame was received.
+    pub at_ns: u64,
+    pub kind: WindowTickKind,
+    pub yes_ask: Price,
+    pub no_ask: Price,
+    pub yes_bid: Price,
+    pub no_bid: Price,
+    /// Top-of-book sizes from the store at the time of the tick.
+    pub yes_ask_size: Option<f64>,
+    pub no_ask_size: Option<f64>,
+    pub yes_bid_size: Option<f64>,
+    pub no_bid_size: Option<f64>,
+    /// Gross spread for the window's side at this tick.
+    pub spread: Price,
+    /// Trade ticks only: which token traded and at what price.
+    pub trade_asset_id: Option<String>,
+    pub trade_price: Option<Price>,
 }
 
 // ---------------------------------------------------------------------------
@@ -167,32 +553,74 @@ pub struct WindowObservables {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct WindowOpenEvent {
+    /// Market id for binary windows, Gamma event id for neg-risk windows.
     pub market_id: String,
-    pub yes_ask: f64,
-    pub no_ask: f64,
-    pub spread: f64,
+    pub kind: WindowKind,
+    pub side: WindowSide,
+    pub yes_ask: Price,
+    pub no_ask: Price,
+    pub yes_bid: Price,
+    pub no_bid: Price,
+    /// Basket cost (buy side) or proceeds (sell side) at the opening tick.
+    pub combined_cost: Price,
+    /// Gross spread: `1.0 - (yes_ask + no_ask)` for buy, `(yes_bid + no_bid) - 1.0` for sell.
+    pub spread: Price,
+    /// Spread after taker fees and amortised fixed costs.
+    pub net_spread: f64,
     pub spread_category: SpreadCategory,
+    /// Executable depth at the opening tick. None if the books were not available.
+    pub depth: Option<ArbDepth>,
+    /// Both legs' book metrics when the window's first arb tick arrived (binary windows only).
+    pub open_book: Option<MarketBookMetrics>,
+    /// Confirmation rule in force for this window.
+    pub confirmation: ConfirmationRule,
     /// Nanosecond UTC epoch timestamp.
     pub opened_at_ns: u64,
-    /// For latency measurement — not sent over channel.
-    #[serde(skip)]
-    pub detected_at: Instant,
+    /// Exchange timestamp (ms) of the message that opened the window, when sent.
+    pub exchange_opened_at_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct WindowCloseEvent {
     pub market_id: String,
-    pub yes_ask: f64,
-    pub no_ask: f64,
-    pub spread: f64,
+    pub kind: WindowKind,
+    pub side: WindowSide,
+    pub yes_ask: Price,
+    pub no_ask: Price,
+    pub yes_bid: Price,
+    pub no_bid: Price,
+    /// Basket cost (buy side) or proceeds (sell side) at the opening tick.
+    pub combined_cost: Price,
+    /// Gross spread: `1.0 - (yes_ask + no_ask)` for buy, `(yes_bid + no_bid) - 1.0` for sell.
+    pub spread: Price,
+    /// Spread after taker fees and amortised fixed costs.
+    pub net_spread: f64,
     pub spread_category: SpreadCategory,
+    /// Most profitable executable depth seen on any arb tick of the window.
+    pub depth: Option<ArbDepth>,
+    /// Both legs' book metrics at the first arb tick and at close (binary windows
+    /// only). Boxed to keep the close variant of `WindowEvent` near the open one.
+    pub open_book: Option<Box<MarketBookMetrics>>,
+    pub close_book: Option<Box<MarketBookMetrics>>,
+    /// Confirmation rule in force for this window.
+    pub confirmation: ConfirmationRule,
     pub opened_at_ns: u64,
     pub closed_at_ns: u64,
+    /// Exchange timestamps (ms) of the opening and closing messages, when sent.
+    /// Forced closes use the last arb tick's.
+    pub exchange_opened_at_ms: Option<u64>,
+    pub exchange_closed_at_ms: Option<u64>,
     pub duration_ms: f64,
     pub open_duration_class: OpenDurationClass,
     pub close_reason: Option<CloseReason>,
     pub opportunity_class: u8,
     pub observables: WindowObservables,
+    /// `WindowClassifier::id()` that produced the classes above, e.g. `confirmation@v1`.
+    pub classifier_version: String,
+    /// Every tick observed while the window was active, opening tick first and
+    /// closing tick last. Persisted to `window_ticks`; not broadcast.
+    #[serde(skip)]
+    pub ticks: Vec<WindowTick>,
     /// Microseconds from WS receive to spread computation for the closing tick.
     pub detection_latency_us: u64,
 }
@@ -207,14 +635,25 @@ pub enum WindowEvent {
 // Channel message types
 // ---------------------------------------------------------------------------
 
-/// Routed from WS manager to the spread detector.
+/// Best prices of one token after a WS frame was applied to the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenQuote {
+    pub asset_id: Arc<str>,
+    pub best_ask: Price,
+    pub best_bid: Price,
+}
+
+/// Routed from WS manager to the spread detector: one coalesced update per market
+/// touched by a WS frame, sent only after the whole frame has been applied.
 #[derive(Debug, Clone)]
 pub struct PriceChangeMsg {
-    pub asset_id: String,
-    pub best_ask: f64,
-    pub best_bid: f64,
-    /// Nanosecond UTC epoch of when message was received.
+    pub market_id: Arc<str>,
+    /// Post-frame quotes for the market's tokens that the frame changed.
+    pub quotes: Vec<TokenQuote>,
+    /// Nanosecond UTC epoch of when the frame was received.
     pub received_at_ns: u64,
+    /// Latest exchange timestamp (ms) among the frame's messages for this market.
+    pub exchange_ts_ms: Option<u64>,
     pub received_at: Instant,
 }
 
@@ -222,13 +661,27 @@ pub struct PriceChangeMsg {
 #[derive(Debug, Clone)]
 pub struct TradeMsg {
     pub asset_id: String,
-    pub price: f64,
+    pub price: Price,
     pub received_at_ns: u64,
 }
 
+/// Routed to detector shards to force-close windows ahead of their once-a-second
+/// feed fault sweep.
+#[derive(Debug, Clone)]
+pub enum FaultSweep {
+    /// Every window on a market the store currently reports a feed fault for.
+    All,
+    /// Every window priced off `market_id`, whatever the store reports now — e.g.
+    /// after a snapshot corrected a book that had drifted from the server's.
+    Market { market_id: Arc<str>, reason: CloseReason },
+}
+
 /// Control messages for dynamic market subscription management.
 #[derive(Debug)]
 pub enum ControlMsg {
     Subscribe(Vec<Market>),
     Unsubscribe(String),
+    /// Unsubscribe and resubscribe these asset ids on their owning connection to
+    /// force a fresh `book` snapshot.
+    Resubscribe(Vec<String>),
 }
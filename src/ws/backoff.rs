@@ -0,0 +1,167 @@
+//! Reconnect pacing for one WS connection: jittered exponential backoff with a
+//! circuit breaker that stops hammering an endpoint that keeps failing.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use crate::config::ReconnectConfig;
+
+/// Circuit breaker state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BreakerState {
+    /// Reconnecting with exponential backoff.
+    Closed = 0,
+    /// Too many consecutive failures: waiting out the cooldown before a probe.
+    Open = 1,
+    /// The cooldown has passed and one probe attempt is in flight.
+    HalfOpen = 2,
+}
+
+impl BreakerState {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => BreakerState::Open,
+            2 => BreakerState::HalfOpen,
+            _ => BreakerState::Closed,
+        }
+    }
+}
+
+impl std::fmt::Display for BreakerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Delay before each reconnect attempt. A failure is any session that ends
+/// before staying up for `WS_STABLE_CONNECTION_SECS`, including a failed connect.
+#[derive(Debug)]
+pub struct Backoff {
+    cfg: ReconnectConfig,
+    /// Failed sessions in a row.
+    failures: u32,
+    state: BreakerState,
+    /// xorshift64 state for jitter.
+    rng: u64,
+}
+
+impl Backoff {
+    pub fn new(cfg: ReconnectConfig) -> Self {
+        Self { cfg, failures: 0, state: BreakerState::Closed, rng: RandomState::new().hash_one(0u64) | 1 }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Called before each attempt: an open breaker lets one probe through.
+    pub fn on_attempt(&mut self) {
+        if self.state == BreakerState::Open {
+            self.state = BreakerState::HalfOpen;
+        }
+    }
+
+    /// Called once the socket is up. Closes a half-open breaker; returns true if it did.
+    pub fn on_connected(&mut self) -> bool {
+        let closed = self.state == BreakerState::HalfOpen;
+        if closed {
+            self.state = BreakerState::Closed;
+        }
+        closed
+    }
+
+    /// Called after the session ends; returns the delay before the next attempt.
+    /// A failed probe, or `breaker_failures` failures in a row, opens the breaker
+    /// for the cooldown instead of the backoff step.
+    pub fn on_disconnected(&mut self, stable: bool) -> Duration {
+        if stable {
+            self.failures = 0;
+            return self.jittered(self.cfg.base);
+        }
+        self.failures = self.failures.saturating_add(1);
+        let trip = self.state == BreakerState::HalfOpen
+            || (self.cfg.breaker_failures > 0 && self.failures >= self.cfg.breaker_failures);
+        if trip {
+            self.state = BreakerState::Open;
+            return self.jittered(self.cfg.breaker_cooldown);
+        }
+        let step = self.cfg.base.saturating_mul(1u32 << (self.failures - 1).min(20));
+        self.jittered(step.min(self.cfg.max))
+    }
+
+    /// `delay` less up to `jitter` of itself, so pooled connections spread out.
+    fn jittered(&mut self, delay: Duration) -> Duration {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let unit = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+        delay.mul_f64(1.0 - self.cfg.jitter * unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> ReconnectConfig {
+        ReconnectConfig {
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(1_000),
+            jitter: 0.0,
+            breaker_failures: 6,
+            breaker_cooldown: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_to_the_ceiling_and_resets_after_a_stable_session() {
+        let mut backoff = Backoff::new(cfg());
+        let delays: Vec<u128> = (0..5).map(|_| backoff.on_disconnected(false).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000]);
+        assert_eq!(backoff.on_disconnected(true), Duration::from_millis(100));
+        assert_eq!(backoff.consecutive_failures(), 0);
+
+        let mut jittered = Backoff::new(ReconnectConfig { jitter: 0.5, ..cfg() });
+        for _ in 0..20 {
+            let d = jittered.on_disconnected(true);
+            assert!(d >= Duration::from_millis(50) && d <= Duration::from_millis(100), "{d:?}");
+        }
+    }
+
+    #[test]
+    fn breaker_opens_after_repeated_failures_and_closes_when_a_probe_connects() {
+        let mut backoff = Backoff::new(cfg());
+        for _ in 0..5 {
+            backoff.on_attempt();
+            backoff.on_disconnected(false);
+        }
+        assert_eq!(backoff.state(), BreakerState::Closed);
+        backoff.on_attempt();
+        assert_eq!(backoff.on_disconnected(false), Duration::from_secs(60));
+        assert_eq!(backoff.state(), BreakerState::Open);
+
+        // A failed probe reopens straight away.
+        backoff.on_attempt();
+        assert_eq!(backoff.state(), BreakerState::HalfOpen);
+        assert_eq!(backoff.on_disconnected(false), Duration::from_secs(60));
+        assert_eq!(backoff.state(), BreakerState::Open);
+
+        // A probe that connects closes it; staying up resets the failure count.
+        backoff.on_attempt();
+        assert!(backoff.on_connected());
+        assert_eq!(backoff.state(), BreakerState::Closed);
+        assert_eq!(backoff.on_disconnected(true), Duration::from_millis(100));
+        assert_eq!(backoff.consecutive_failures(), 0);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::config::CAPTURE_CHANNEL_CAPACITY;
+use crate::error::Result;
+use crate::state::market_store::MarketStore;
+use crate::types::{Market, NegRiskEvent};
+
+/// One line of a capture file. `t` is the receive (or event) time in ns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CaptureRecord {
+    /// Markets (and the neg-risk events they belong to) added to the store. Every
+    /// capture file starts with one covering the whole store.
+    Markets { t: u64, markets: Vec<Market>, events: Vec<NegRiskEvent> },
+    /// A market removed from the store.
+    Unsubscribe { t: u64, market_id: String },
+    /// One raw WS text frame, as received.
+    Frame { t: u64, frame: String },
+    /// A connection dropped and these assets' books were invalidated.
+    Disconnect { t: u64, asset_ids: Vec<String> },
+    /// The silence monitor marked a market stale.
+    Stale { t: u64, market_id: String },
+}
+
+impl CaptureRecord {
+    pub fn t(&self) -> u64 {
+        match self {
+            CaptureRecord::Markets { t, .. }
+            | CaptureRecord::Unsubscribe { t, .. }
+            | CaptureRecord::Frame { t, .. }
+            | CaptureRecord::Disconnect { t, .. }
+            | CaptureRecord::Stale { t, .. } => *t,
+        }
+    }
+}
+
+/// Handle for recording into the capture. Cheap to clone; recording never blocks —
+/// records are dropped (and counted) if the writer falls behind.
+#[derive(Clone)]
+pub struct FrameCapture {
+    tx: mpsc::Sender<CaptureRecord>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl FrameCapture {
+    /// Starts a writer thread producing `capture-<t>.jsonl.gz` files in `dir`,
+    /// rotating after `rotate_bytes` uncompressed bytes.
+    pub fn spawn(dir: impl Into<PathBuf>, rotate_bytes: u64, store: Arc<MarketStore>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        let (tx, rx) = mpsc::channel(CAPTURE_CHANNEL_CAPACITY);
+        let writer = CaptureWriter { dir, rotate_bytes, store, file: None, written: 0 };
+        tokio::task::spawn_blocking(move || writer.run(rx));
+        Ok(Self { tx, dropped: Arc::new(AtomicU64::new(0)) })
+    }
+
+    pub fn record(&self, record: CaptureRecord) {
+        if self.tx.try_send(record).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!("[CAPTURE] writer behind, {dropped} record(s) dropped so far");
+            }
+        }
+    }
+
+    pub fn frame(&self, t: u64, frame: &str) {
+        self.record(CaptureRecord::Frame { t, frame: frame.to_string() });
+    }
+}
+
+struct CaptureWriter {
+    dir: PathBuf,
+    rotate_bytes: u64,
+    store: Arc<MarketStore>,
+    file: Option<GzEncoder<BufWriter<File>>>,
+    /// Uncompressed bytes written to the current file.
+    written: u64,
+}
+
+impl CaptureWriter {
+    fn run(mut self, mut rx: mpsc::Receiver<CaptureRecord>) {
+        while let Some(record) = rx.blocking_recv() {
+            if let Err(e) = self.write(&record) {
+                error!("[CAPTURE] write failed: {e}");
+            }
+            // Flush whenever the queue drains so a crash loses little.
+            if rx.is_empty() {
+                if let Some(file) = self.file.as_mut() {
+                    if let Err(e) = file.flush() {
+                        error!("[CAPTURE] flush failed: {e}");
+                    }
+                }
+            }
+        }
+        if let Some(file) = self.file.take() {
+            if let Err(e) = file.finish().and_then(|mut w| w.flush()) {
+                error!("[CAPTURE] finishing capture file failed: {e}");
+            }
+        }
+    }
+
+    fn write(&mut self, record: &CaptureRecord) -> std::io::Result<()> {
+        if self.file.is_none() || self.written >= self.rotate_bytes {
+            self.rotate(record.t())?;
+        }
+        self.write_line(record)
+    }
+
+    fn write_line(&mut self, record: &CaptureRecord) -> std::io::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Finishes the current file and opens the next one, starting with a snapshot
+    /// of the store's markets so each file replays on its own.
+    fn rotate(&mut self, t: u64) -> std::io::Result<()> {
+        if let Some(file) = self.file.take() {
+            file.finish()?.flush()?;
+        }
+        let path = self.dir.join(format!("capture-{t}.jsonl.gz"));
+        let file = File::create(&path)?;
+        self.file = Some(GzEncoder::new(BufWriter::new(file), Compression::fast()));
+        self.written = 0;
+        info!("[CAPTURE] writing {}", path.display());
+
+        let markets = self
+            .store
+            .all_market_ids()
+            .iter()
+            .filter(|id| !self.store.is_event_member(id))
+            .filter_map(|id| self.store.get_market(id))
+            .collect();
+        let events = self
+            .store
+            .event_ids()
+            .iter()
+            .filter_map(|id| self.store.event(id))
+            .map(|e| (*e).clone())
+            .collect();
+        self.write_line(&CaptureRecord::Markets { t, markets, events })
+    }
+}
+
+/// Capture files under `path` in recording order: `path` itself if it is a file,
+/// else every `*.jsonl.gz` in the directory sorted by name.
+pub fn capture_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.to_string_lossy().ends_with(".jsonl.gz"))
+        .collect();
+    // Names embed the first record's ns timestamp; same digit count for centuries.
+    files.sort();
+    Ok(files)
+}
+
+/// Streams the records of one capture file.
+pub fn read_capture(path: &Path) -> Result<impl Iterator<Item = Result<CaptureRecord>>> {
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    Ok(reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(serde_json::from_str(&line).map_err(Into::into)),
+        Err(e) => Some(Err(e.into())),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Category;
+
+    #[test]
+    fn capture_rotates_and_reads_back_in_order() {
+        let dir = std::env::temp_dir().join(format!("capture-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "m1".to_string(),
+            question: "Test".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+
+        // Rotate after every record; run the writer inline until the channel closes.
+        let (tx, rx) = mpsc::channel(8);
+        for t in 1..=3u64 {
+            let frame = r#"[{"event_type":"book"}]"#.to_string();
+            tx.try_send(CaptureRecord::Frame { t: t * 1_000_000_000, frame }).unwrap();
+        }
+        drop(tx);
+        CaptureWriter { dir: dir.clone(), rotate_bytes: 1, store, file: None, written: 0 }.run(rx);
+
+        let files = capture_files(&dir).unwrap();
+        assert_eq!(files.len(), 3);
+        let mut frames = Vec::new();
+        for file in &files {
+            let records: Vec<CaptureRecord> = read_capture(file).unwrap().map(|r| r.unwrap()).collect();
+            match &records[0] {
+                CaptureRecord::Markets { markets, .. } => assert_eq!(markets[0].id, "m1"),
+                other => panic!("file must start with a market snapshot, got {other:?}"),
+            }
+            frames.extend(records[1..].iter().map(CaptureRecord::t));
+        }
+        assert_eq!(frames, vec![1_000_000_000, 2_000_000_000, 3_000_000_000]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -7,101 +8,146 @@ use tokio::time::interval;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 
-use crate::api::health::HealthState;
-use crate::config::{RECONNECT_BACKOFF_MS, WS_PING_INTERVAL_SECS, WS_SUBSCRIBE_CHUNK_SIZE};
+use crate::api::health::{ConnectionAttempt, WsConnectionHealth};
+use crate::api::latency::{FeedLatency, ParseLatency};
+use crate::config::{
+    ReconnectConfig, PRICE_DIVERGENCE_TOLERANCE, WS_PING_INTERVAL_SECS, WS_STABLE_CONNECTION_SECS,
+    WS_SUBSCRIBE_CHUNK_SIZE,
+};
+use crate::detector::ShardRouter;
 use crate::error::Result;
 use crate::state::market_store::MarketStore;
-use crate::types::{ControlMsg, PriceChangeMsg, TradeMsg};
-use crate::ws::messages::{ParsedFrame, parse_ws_frame};
+use crate::types::{CloseReason, Price, PriceChangeMsg, TokenQuote, TradeMsg};
+use crate::ws::backoff::{Backoff, BreakerState};
+use crate::ws::capture::{CaptureRecord, FrameCapture};
+use crate::ws::messages::{LevelChange, ParsedFrame, parse_ws_frame};
 
-/// Manages the single persistent WebSocket connection to Polymarket's CLOB feed.
-pub struct WsManager {
+/// Commands from the pool to one connection, applied to its partition.
+#[derive(Debug)]
+pub enum ConnCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    /// Unsubscribe then subscribe owned assets again, prompting fresh snapshots.
+    Resubscribe(Vec<String>),
+}
+
+/// One market-channel WebSocket owning a partition of asset ids. Reconnects on
+/// its own, paced by `Backoff`, and resubscribes its whole partition each time.
+pub struct WsConnection {
     ws_url: String,
-    store: Arc<MarketStore>,
-    price_tx: mpsc::Sender<PriceChangeMsg>,
-    trade_tx: mpsc::Sender<TradeMsg>,
-    control_rx: mpsc::Receiver<ControlMsg>,
-    health: Arc<HealthState>,
-    /// Total WS frames received since process start (for flow diagnostics).
-    frames_received: Arc<AtomicU64>,
-    /// Total price events routed to the detector.
-    price_msgs_routed: Arc<AtomicU64>,
-    /// Per-event-type counters for diagnostics.
-    book_snapshots: Arc<AtomicU64>,
-    price_changes: Arc<AtomicU64>,
-    trade_events: Arc<AtomicU64>,
+    /// Asset ids this connection is (or will be, once connected) subscribed to.
+    assets: HashSet<String>,
+    handler: Arc<FrameHandler>,
+    command_rx: mpsc::Receiver<ConnCommand>,
+    health: Arc<WsConnectionHealth>,
+    backoff: Backoff,
 }
 
-impl WsManager {
+impl WsConnection {
     pub fn new(
         ws_url: String,
-        store: Arc<MarketStore>,
-        price_tx: mpsc::Sender<PriceChangeMsg>,
-        trade_tx: mpsc::Sender<TradeMsg>,
-        control_rx: mpsc::Receiver<ControlMsg>,
-        health: Arc<HealthState>,
+        assets: HashSet<String>,
+        handler: Arc<FrameHandler>,
+        command_rx: mpsc::Receiver<ConnCommand>,
+        health: Arc<WsConnectionHealth>,
+        reconnect: ReconnectConfig,
     ) -> Self {
-        Self {
-            ws_url,
-            store,
-            price_tx,
-            trade_tx,
-            control_rx,
-            health,
-            frames_received: Arc::new(AtomicU64::new(0)),
-            price_msgs_routed: Arc::new(AtomicU64::new(0)),
-            book_snapshots: Arc::new(AtomicU64::new(0)),
-            price_changes: Arc::new(AtomicU64::new(0)),
-            trade_events: Arc::new(AtomicU64::new(0)),
-        }
+        health.subscribed_assets.store(assets.len() as u64, Ordering::Relaxed);
+        Self { ws_url, assets, handler, command_rx, health, backoff: Backoff::new(reconnect) }
     }
 
     pub async fn run(mut self) {
-        let mut backoff_idx = 0usize;
+        let conn = self.health.index;
 
         loop {
-            info!("WS connecting to {}", self.ws_url);
-            match self.connect_once().await {
-                Ok(()) => {
-                    info!("WS connection closed cleanly");
-                    backoff_idx = 0;
-                }
-                Err(e) => {
-                    error!("WS connection error: {e}");
-                }
+            self.backoff.on_attempt();
+            self.health.set_breaker(self.backoff.state());
+            info!("[WS:{conn}] connecting to {} ({} assets)", self.ws_url, self.assets.len());
+            let mut attempt = ConnectionAttempt { started_at_ns: now_ns(), ..ConnectionAttempt::default() };
+            let result = self.connect_once(&mut attempt).await;
+
+            let ended_at = now_ns();
+            attempt.duration_ms = ended_at.saturating_sub(attempt.connected_at_ns.unwrap_or(attempt.started_at_ns)) / 1_000_000;
+            let stable = attempt.connected_at_ns.is_some_and(|t| ended_at.saturating_sub(t) >= WS_STABLE_CONNECTION_SECS * 1_000_000_000);
+            match &result {
+                Ok(true) => info!("[WS:{conn}] connection closed (code {:?}) after {}ms", attempt.close_code, attempt.duration_ms),
+                Ok(false) => info!("[WS:{conn}] pool dropped its command channel, shutting down"),
+                // Only the first failure in a row is an error; the rest are expected
+                // while the endpoint is down.
+                Err(e) if self.backoff.consecutive_failures() == 0 => error!("[WS:{conn}] connection error: {e}"),
+                Err(e) => debug!("[WS:{conn}] connection error: {e}"),
             }
+            if let Err(e) = &result {
+                attempt.error = Some(e.to_string());
+            }
+            self.health.record_attempt(attempt);
+            if matches!(result, Ok(false)) {
+                return;
+            }
+            self.invalidate_partition();
 
-            let delay_ms = RECONNECT_BACKOFF_MS
-                .get(backoff_idx)
-                .copied()
-                .unwrap_or(*RECONNECT_BACKOFF_MS.last().unwrap());
-            backoff_idx = (backoff_idx + 1).min(RECONNECT_BACKOFF_MS.len() - 1);
+            let was_open = self.backoff.state() == BreakerState::Open;
+            let delay = self.backoff.on_disconnected(stable);
+            let failures = self.backoff.consecutive_failures();
+            self.health.consecutive_failures.store(u64::from(failures), Ordering::Relaxed);
+            self.health.set_breaker(self.backoff.state());
+            if self.backoff.state() == BreakerState::Open {
+                if !was_open {
+                    self.health.breaker_opens.fetch_add(1, Ordering::Relaxed);
+                }
+                warn!("[WS:{conn}] circuit breaker open after {failures} consecutive failures; probing in {:.0}s", delay.as_secs_f64());
+            } else if failures <= 1 {
+                warn!("[WS:{conn}] reconnecting in {}ms", delay.as_millis());
+            } else {
+                debug!("[WS:{conn}] reconnecting in {}ms ({failures} consecutive failures)", delay.as_millis());
+            }
+            tokio::time::sleep(delay).await;
+            self.health.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
 
-            warn!("WS reconnecting in {delay_ms}ms");
-            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    /// Called after the socket drops: starts the outage clock and, the first time,
+    /// invalidates the partition's books so nothing trades on pre-disconnect state.
+    fn invalidate_partition(&self) {
+        let now = now_ns();
+        if !self.health.mark_disconnected(now) {
+            return;
+        }
+        let marked = self.handler.store.invalidate_books(&self.assets, now);
+        if let Some(capture) = self.handler.capture() {
+            capture.record(CaptureRecord::Disconnect { t: now, asset_ids: self.assets.iter().cloned().collect() });
+        }
+        self.health.books_invalidated.fetch_add(marked as u64, Ordering::Relaxed);
+        if marked > 0 {
+            warn!("[WS:{conn}] disconnected; {marked} market(s) unhydrated until resnapshot", conn = self.health.index);
         }
     }
 
-    async fn connect_once(&mut self) -> Result<()> {
-        self.health.set_ws_connected(false);
+    /// Runs one socket until it closes, recording when it connected and how it
+    /// closed into `attempt`. `Ok(false)` means the pool has shut down.
+    async fn connect_once(&mut self, attempt: &mut ConnectionAttempt) -> Result<bool> {
+        let conn = self.health.index;
+        self.health.set_connected(false);
         let (ws_stream, _) = connect_async(&self.ws_url).await?;
         let (mut write, mut read) = ws_stream.split();
-        self.health.set_ws_connected(true);
+        self.health.set_connected(true);
+        attempt.connected_at_ns = Some(now_ns());
+        if self.backoff.on_connected() {
+            self.health.set_breaker(BreakerState::Closed);
+            info!("[WS:{conn}] probe connected; circuit breaker closed");
+        }
+        if let Some(gap_ns) = self.health.mark_reconnected(now_ns()) {
+            info!(
+                "[WS:{conn}] reconnected after {:.1}s disconnected; awaiting fresh snapshots",
+                gap_ns as f64 / 1e9,
+            );
+        }
 
         // Initial subscription: send in chunks to avoid server-side frame size limits.
-        let asset_ids = self.store.all_asset_ids();
+        let asset_ids: Vec<String> = self.assets.iter().cloned().collect();
         if !asset_ids.is_empty() {
-            let chunks: Vec<_> = asset_ids.chunks(WS_SUBSCRIBE_CHUNK_SIZE).collect();
-            let total_chunks = chunks.len();
-            for (i, chunk) in chunks.into_iter().enumerate() {
-                let sub_msg = build_subscribe_msg(chunk);
-                write.send(Message::Text(sub_msg.into())).await?;
-                if total_chunks > 1 {
-                    tokio::time::sleep(Duration::from_millis(50)).await;
-                }
-                debug!("WS subscribe chunk {}/{} ({} ids)", i + 1, total_chunks, chunk.len());
-            }
-            info!("WS subscribed to {} asset_ids in {} chunk(s)", asset_ids.len(), total_chunks);
+            let total_chunks = send_chunked(&mut write, &asset_ids, build_subscribe_msg).await?;
+            info!("[WS:{conn}] subscribed to {} asset_ids in {} chunk(s)", asset_ids.len(), total_chunks);
         }
 
         let mut ping_interval = interval(Duration::from_secs(WS_PING_INTERVAL_SECS));
@@ -112,17 +158,27 @@ impl WsManager {
                 msg = read.next() => {
                     match msg {
                         Some(Ok(Message::Text(text))) => {
-                            self.handle_frame(&text).await;
+                            self.health.frames_received.fetch_add(1, Ordering::Relaxed);
+                            self.health.last_frame_at_ns.store(now_ns(), Ordering::Relaxed);
+                            self.handler.handle_frame(&text).await;
                         }
                         Some(Ok(Message::Ping(data))) => {
                             write.send(Message::Pong(data)).await?;
                         }
-                        Some(Ok(Message::Close(_))) | None => {
-                            self.health.set_ws_connected(false);
-                            return Ok(());
+                        Some(Ok(Message::Close(frame))) => {
+                            if let Some(frame) = frame {
+                                attempt.close_code = Some(u16::from(frame.code));
+                                attempt.close_reason = Some(frame.reason.into_owned()).filter(|r| !r.is_empty());
+                            }
+                            self.health.set_connected(false);
+                            return Ok(true);
+                        }
+                        None => {
+                            self.health.set_connected(false);
+                            return Ok(true);
                         }
                         Some(Err(e)) => {
-                            self.health.set_ws_connected(false);
+                            self.health.set_connected(false);
                             return Err(e.into());
                         }
                         Some(Ok(_)) => {}
@@ -130,173 +186,336 @@ impl WsManager {
                 }
 
                 _ = ping_interval.tick() => {
-                    debug!("WS ping");
-                    write.send(Message::Ping(vec![].into())).await?;
+                    debug!("[WS:{conn}] ping");
+                    write.send(Message::Ping(vec![])).await?;
                 }
 
-                ctrl = self.control_rx.recv() => {
-                    match ctrl {
-                        Some(ControlMsg::Subscribe(markets)) => {
-                            let new_ids: Vec<String> = markets.iter()
-                                .flat_map(|m| [m.yes_token_id.clone(), m.no_token_id.clone()])
-                                .collect();
-                            let sub_msg = build_subscribe_msg(&new_ids);
-                            write.send(Message::Text(sub_msg.into())).await?;
-                            info!("WS dynamically subscribed to {} new asset_ids", new_ids.len());
+                cmd = self.command_rx.recv() => {
+                    match cmd {
+                        Some(ConnCommand::Subscribe(ids)) => {
+                            let new_ids: Vec<String> =
+                                ids.into_iter().filter(|id| self.assets.insert(id.clone())).collect();
+                            if !new_ids.is_empty() {
+                                send_chunked(&mut write, &new_ids, build_subscribe_msg).await?;
+                                info!("[WS:{conn}] dynamically subscribed to {} new asset_ids", new_ids.len());
+                            }
                         }
-                        Some(ControlMsg::Unsubscribe(market_id)) => {
-                            if let Some(ids) = self.store.token_ids_for_market(&market_id) {
-                                let unsub_msg = build_unsubscribe_msg(&ids);
-                                write.send(Message::Text(unsub_msg.into())).await?;
-                                info!("WS unsubscribed market {market_id}");
+                        Some(ConnCommand::Unsubscribe(ids)) => {
+                            let old_ids: Vec<String> =
+                                ids.into_iter().filter(|id| self.assets.remove(id)).collect();
+                            if !old_ids.is_empty() {
+                                send_chunked(&mut write, &old_ids, build_unsubscribe_msg).await?;
+                                info!("[WS:{conn}] unsubscribed {} asset_ids", old_ids.len());
+                            }
+                        }
+                        Some(ConnCommand::Resubscribe(ids)) => {
+                            let owned: Vec<String> =
+                                ids.into_iter().filter(|id| self.assets.contains(id)).collect();
+                            if !owned.is_empty() {
+                                send_chunked(&mut write, &owned, build_unsubscribe_msg).await?;
+                                send_chunked(&mut write, &owned, build_subscribe_msg).await?;
+                                info!("[WS:{conn}] resubscribed {} asset_ids", owned.len());
                             }
                         }
                         None => {
-                            // Control channel dropped — shut down
-                            self.health.set_ws_connected(false);
-                            return Ok(());
+                            self.health.set_connected(false);
+                            return Ok(false);
                         }
                     }
+                    self.health.subscribed_assets.store(self.assets.len() as u64, Ordering::Relaxed);
                 }
             }
         }
     }
+}
 
-    async fn handle_frame(&self, text: &str) {
-        let received_at = std::time::Instant::now();
+/// Parses frames, applies them to the store and routes the post-frame updates to
+/// the detector shards. Shared by every pooled connection.
+pub struct FrameHandler {
+    store: Arc<MarketStore>,
+    /// Routes coalesced price updates and trades to the detector shard owning each market.
+    router: ShardRouter,
+    /// Total WS frames received since process start, across all connections.
+    frames_received: AtomicU64,
+    /// Total coalesced per-market price updates routed to the detector.
+    price_msgs_routed: AtomicU64,
+    /// Per-event-type counters for diagnostics.
+    book_snapshots: AtomicU64,
+    price_changes: AtomicU64,
+    trade_events: AtomicU64,
+    tick_size_changes: AtomicU64,
+    /// Raw feed capture, when CAPTURE_DIR is set.
+    capture: Option<FrameCapture>,
+    /// Exchange timestamp → receive latency, one sample per timestamped frame.
+    feed_latency: Arc<FeedLatency>,
+    /// Time spent parsing each frame.
+    parse_latency: Arc<ParseLatency>,
+}
+
+impl FrameHandler {
+    pub fn new(store: Arc<MarketStore>, router: ShardRouter) -> Self {
+        Self {
+            store,
+            router,
+            frames_received: AtomicU64::new(0),
+            price_msgs_routed: AtomicU64::new(0),
+            book_snapshots: AtomicU64::new(0),
+            price_changes: AtomicU64::new(0),
+            trade_events: AtomicU64::new(0),
+            tick_size_changes: AtomicU64::new(0),
+            capture: None,
+            feed_latency: Arc::new(FeedLatency::new()),
+            parse_latency: Arc::new(ParseLatency::new()),
+        }
+    }
+
+    /// Records exchange → receive latency into a shared histogram.
+    pub fn with_feed_latency(mut self, feed_latency: Arc<FeedLatency>) -> Self {
+        self.feed_latency = feed_latency;
+        self
+    }
+
+    /// Records per-frame parse time into a shared histogram.
+    pub fn with_parse_latency(mut self, parse_latency: Arc<ParseLatency>) -> Self {
+        self.parse_latency = parse_latency;
+        self
+    }
+
+    /// Records every frame (and the market and feed-fault events needed to replay
+    /// it) into `capture`.
+    pub fn with_capture(mut self, capture: FrameCapture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    pub fn capture(&self) -> Option<&FrameCapture> {
+        self.capture.as_ref()
+    }
+
+    /// Detector messages routed but not yet handled, across all shards.
+    pub fn detector_backlog(&self) -> u64 {
+        self.router.backlog()
+    }
+
+    /// Has every detector shard force-close windows on faulted markets now.
+    pub async fn sweep_faults(&self) {
+        self.router.sweep_faults().await;
+    }
+
+    pub async fn handle_frame(&self, text: &str) {
         let received_at_ns = now_ns();
+        if let Some(capture) = &self.capture {
+            capture.frame(received_at_ns, text);
+        }
+        self.apply_frame(text, received_at_ns).await;
+    }
+
+    /// Applies one frame as if received at `received_at_ns`. Replay drives this
+    /// directly with captured timestamps.
+    pub async fn apply_frame(&self, text: &str, received_at_ns: u64) {
+        let received_at = std::time::Instant::now();
 
         let total_frames = self.frames_received.fetch_add(1, Ordering::Relaxed) + 1;
-        if total_frames % 500 == 0 {
+        if total_frames.is_multiple_of(500) {
             let price_routed = self.price_msgs_routed.load(Ordering::Relaxed);
             let snaps = self.book_snapshots.load(Ordering::Relaxed);
             let pchg = self.price_changes.load(Ordering::Relaxed);
             let trades = self.trade_events.load(Ordering::Relaxed);
+            let ticks = self.tick_size_changes.load(Ordering::Relaxed);
             info!(
                 frames = total_frames,
                 price_msgs = price_routed,
                 snapshots = snaps,
                 price_changes = pchg,
                 trades = trades,
-                "[WS] {total_frames} frames | routed={price_routed} | snap={snaps} pchg={pchg} trade={trades}"
+                tick_size_changes = ticks,
+                "[WS] {total_frames} frames | routed={price_routed} | snap={snaps} pchg={pchg} trade={trades} tick={ticks}"
             );
         }
 
-        for event in parse_ws_frame(text) {
+        // Apply every entry in the frame to the store first; the detector only sees
+        // the post-frame state so it never evaluates a half-updated pair of books.
+        let mut touched = FrameTouches::default();
+        let parse_started = std::time::Instant::now();
+        let events = parse_ws_frame(text);
+        self.parse_latency.record(parse_started.elapsed());
+        if let Some(exchange_ts_ms) = events.iter().filter_map(ParsedFrame::exchange_ts_ms).max() {
+            self.feed_latency.record(received_at_ns, exchange_ts_ms);
+        }
+        for event in events {
+            let exchange_ts_ms = event.exchange_ts_ms();
             match event {
-                ParsedFrame::BookSnapshot { asset_id, asks, bids } => {
+                ParsedFrame::BookSnapshot { asset_id, asks, bids, hash, .. } => {
                     self.book_snapshots.fetch_add(1, Ordering::Relaxed);
-                    // Parse level strings into (price, size) pairs.
-                    let parsed_asks: Vec<(f64, f64)> = asks.iter()
-                        .filter_map(|l| {
-                            let p = l.price.parse::<f64>().ok()?;
-                            let s = l.size.parse::<f64>().ok()?;
-                            Some((p, s))
-                        })
-                        .collect();
-                    let parsed_bids: Vec<(f64, f64)> = bids.iter()
-                        .filter_map(|l| {
-                            let p = l.price.parse::<f64>().ok()?;
-                            let s = l.size.parse::<f64>().ok()?;
-                            Some((p, s))
-                        })
-                        .collect();
-
-                    if let Some((best_ask, best_bid)) =
-                        self.store.apply_book_snapshot(&asset_id, &parsed_asks, &parsed_bids)
-                    {
-                        debug!(asset_id = %asset_id, best_ask, best_bid, "book snapshot applied");
-                        if best_ask > 0.0 {
-                            self.route_price_msg(
-                                asset_id,
-                                best_ask,
-                                best_bid,
-                                received_at_ns,
-                                received_at,
-                            );
+                    let hash_mismatch = hash.as_deref().is_some_and(|hash| !self.store.record_snapshot_hash(&asset_id, hash, &asks, &bids));
+                    // The snapshot corrects the book, but windows priced off the drifted
+                    // one are closed as corrupt.
+                    if hash_mismatch {
+                        warn!(asset = %asset_id, "[BOOK INTEGRITY] snapshot with unchanged hash differs from local book; closing its windows");
+                        if let Some((_, market_id)) = self.store.token_ref(&asset_id) {
+                            self.router.close_market_windows(market_id, CloseReason::BookCorrupt);
                         }
                     }
+
+                    if let Some((best_ask, best_bid)) = self.store.apply_book_snapshot(&asset_id, &asks, &bids) {
+                        debug!(asset_id = %asset_id, best_ask = %best_ask, best_bid = %best_bid, "book snapshot applied");
+                        touched.insert(&self.store, &asset_id, exchange_ts_ms);
+                    }
                 }
 
-                ParsedFrame::BookPriceChange { asset_id, change, best_bid: server_bid, best_ask: server_ask } => {
+                ParsedFrame::BookPriceChange { asset_id, change, best_bid: server_bid, best_ask: server_ask, hash, .. } => {
                     self.price_changes.fetch_add(1, Ordering::Relaxed);
-                    // Apply the individual level change to the local order book,
-                    // then use the LOCAL book's computed best prices.
+                    // Apply the individual level change to the local order book;
+                    // the LOCAL book's computed best prices are routed at frame end.
                     // This matches the TS bot approach — the local book is the
                     // source of truth, not server-provided best_ask/best_bid.
-                    let (ba, bb) = if let (Ok(p), Ok(s)) = (change.price.parse::<f64>(), change.size.parse::<f64>()) {
-                        let is_ask = change.side == "SELL";
-                        match self.store.apply_book_changes(&asset_id, &[(p, is_ask, s)]) {
-                            Some((a, b)) if a > 0.0 => (a, b),
+                    let (ba, bb) = if let Some(LevelChange { price, is_ask, size }) = change {
+                        if let Some(hash) = &hash {
+                            let local_changed = self.store.level_size(&asset_id, price, is_ask).is_some_and(|prev| prev != size);
+                            if self.store.record_change_hash(&asset_id, hash, local_changed, received_at_ns) {
+                                warn!(asset = %asset_id, "[BOOK INTEGRITY] server hash unchanged but local book moved; marking corrupt");
+                            }
+                        }
+                        match self.store.apply_book_changes(&asset_id, &[(price, is_ask, size)]) {
+                            Some((a, b)) if a.is_positive() => (a, b),
                             _ => continue,
                         }
                     } else {
                         match self.store.best_prices(&asset_id) {
-                            Some((a, b)) if a > 0.0 => (a, b),
+                            Some((a, b)) if a.is_positive() => (a, b),
                             _ => continue,
                         }
                     };
 
-                    // Log divergence between local book and server-provided prices.
+                    // Compare the local book with server-provided prices; persistent
+                    // divergence marks the book corrupt until a fresh snapshot.
                     if let (Some(sa), Some(sb)) = (server_ask, server_bid) {
                         let ask_diff = (ba - sa).abs();
                         let bid_diff = (bb - sb).abs();
-                        if ask_diff > 0.001 || bid_diff > 0.001 {
+                        let diverged = ask_diff > PRICE_DIVERGENCE_TOLERANCE || bid_diff > PRICE_DIVERGENCE_TOLERANCE;
+                        if diverged {
                             debug!(
                                 asset = %asset_id,
-                                local_ask = ba,
-                                server_ask = sa,
-                                local_bid = bb,
-                                server_bid = sb,
+                                local_ask = %ba,
+                                server_ask = %sa,
+                                local_bid = %bb,
+                                server_bid = %sb,
                                 "[PRICE DIVERGENCE] local vs server: ask_diff={ask_diff:.4} bid_diff={bid_diff:.4}"
                             );
                         }
+                        if self.store.record_price_check(&asset_id, diverged, received_at_ns) {
+                            warn!(asset = %asset_id, "[BOOK INTEGRITY] local book diverged from server best prices; marking corrupt");
+                        }
                     }
 
-                    self.route_price_msg(
-                        asset_id,
-                        ba,
-                        bb,
-                        received_at_ns,
-                        received_at,
-                    );
+                    touched.insert(&self.store, &asset_id, exchange_ts_ms);
                 }
 
-                ParsedFrame::LastTradePrice { asset_id, price } => {
+                ParsedFrame::LastTradePrice { asset_id, price, .. } => {
                     self.trade_events.fetch_add(1, Ordering::Relaxed);
-                    let trade_msg = TradeMsg {
-                        asset_id,
+                    self.router.route_trade(TradeMsg {
+                        asset_id: asset_id.into_owned(),
                         price,
                         received_at_ns,
-                    };
-                    if let Err(e) = self.trade_tx.try_send(trade_msg) {
-                        warn!("trade channel full, dropping message: {e}");
+                    });
+                }
+
+                ParsedFrame::TickSizeChange { asset_id, old_tick_size, new_tick_size, .. } => {
+                    self.tick_size_changes.fetch_add(1, Ordering::Relaxed);
+                    if let Some(market_id) = self.store.set_tick_size(&asset_id, new_tick_size) {
+                        info!(market = %market_id, old = ?old_tick_size.map(Price::to_f64), new = %new_tick_size, "[WS] tick size changed");
                     }
                 }
             }
         }
+
+        for market in touched.markets {
+            self.route_price_msg(market.market_id, &market.asset_ids, (received_at_ns, market.exchange_ts_ms), received_at);
+        }
     }
 
+    /// Sends one coalesced update for a market with the post-frame best prices of
+    /// every token the frame touched. Tokens without a live ask are left out, as
+    /// are markets with no such tokens.
     fn route_price_msg(
         &self,
-        asset_id: String,
-        best_ask: f64,
-        best_bid: f64,
-        received_at_ns: u64,
+        market_id: Arc<str>,
+        asset_ids: &[Arc<str>],
+        (received_at_ns, exchange_ts_ms): (u64, Option<u64>),
         received_at: std::time::Instant,
     ) {
+        let quotes: Vec<TokenQuote> = asset_ids
+            .iter()
+            .filter_map(|asset_id| {
+                let (best_ask, best_bid) = self.store.best_prices(asset_id)?;
+                best_ask.is_positive().then(|| TokenQuote { asset_id: Arc::clone(asset_id), best_ask, best_bid })
+            })
+            .collect();
+        if quotes.is_empty() {
+            return;
+        }
+
         let msg = PriceChangeMsg {
-            asset_id,
-            best_ask,
-            best_bid,
+            market_id,
+            quotes,
             received_at_ns,
+            exchange_ts_ms,
             received_at,
         };
         self.price_msgs_routed.fetch_add(1, Ordering::Relaxed);
-        if let Err(e) = self.price_tx.try_send(msg) {
-            warn!("price channel full, dropping message: {e}");
+        self.router.route_price(msg);
+    }
+}
+
+/// Tokens touched by one WS frame, grouped by market in first-seen order. Ids
+/// are the store's interned copies, so building the detector updates doesn't
+/// allocate them.
+#[derive(Default)]
+struct FrameTouches {
+    markets: Vec<TouchedMarket>,
+}
+
+struct TouchedMarket {
+    market_id: Arc<str>,
+    asset_ids: Vec<Arc<str>>,
+    /// Latest exchange timestamp among the market's messages.
+    exchange_ts_ms: Option<u64>,
+}
+
+impl FrameTouches {
+    fn insert(&mut self, store: &MarketStore, asset_id: &str, exchange_ts_ms: Option<u64>) {
+        let Some((asset_id, market_id)) = store.token_ref(asset_id) else {
+            debug!(asset_id = %asset_id, "frame touched a token not in store");
+            return;
+        };
+        match self.markets.iter_mut().find(|m| m.market_id == market_id) {
+            Some(market) => {
+                if !market.asset_ids.contains(&asset_id) {
+                    market.asset_ids.push(asset_id);
+                }
+                market.exchange_ts_ms = market.exchange_ts_ms.max(exchange_ts_ms);
+            }
+            None => self.markets.push(TouchedMarket { market_id, asset_ids: vec![asset_id], exchange_ts_ms }),
+        }
+    }
+}
+
+/// Sends `asset_ids` as chunked (un)subscribe frames; returns the chunk count.
+async fn send_chunked<S>(write: &mut S, asset_ids: &[String], build: fn(&[String]) -> String) -> Result<usize>
+where
+    S: SinkExt<Message> + Unpin,
+    crate::error::AppError: From<S::Error>,
+{
+    let chunks: Vec<_> = asset_ids.chunks(WS_SUBSCRIBE_CHUNK_SIZE).collect();
+    let total_chunks = chunks.len();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        write.send(Message::Text(build(chunk))).await?;
+        if total_chunks > 1 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
         }
+        debug!("WS (un)subscribe chunk {}/{} ({} ids)", i + 1, total_chunks, chunk.len());
     }
+    Ok(total_chunks)
 }
 
 /// Build a market-channel subscription message.
@@ -323,3 +542,84 @@ fn now_ns() -> u64 {
         .unwrap_or_default()
         .as_nanos() as u64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Category, FaultSweep, Market};
+
+    #[tokio::test]
+    async fn frame_is_applied_before_one_update_per_market() {
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "market1".to_string(),
+            question: "Test market".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+        let (price_tx, mut price_rx) = mpsc::channel(16);
+        let (trade_tx, _trade_rx) = mpsc::channel(16);
+        let (sweep_tx, _sweep_rx) = mpsc::channel(1);
+        let mut router = ShardRouter::new(store.clone());
+        router.add_shard(price_tx, trade_tx, sweep_tx);
+        let ws = FrameHandler::new(store.clone(), router);
+
+        ws.handle_frame(r#"[
+            {"event_type":"book","asset_id":"yes1","asks":[{"price":"0.60","size":"10"}],"bids":[]},
+            {"event_type":"book","asset_id":"no1","asks":[{"price":"0.45","size":"10"}],"bids":[]}
+        ]"#).await;
+        let snap = price_rx.try_recv().expect("one update for the snapshot frame");
+        assert_eq!(snap.quotes.len(), 2);
+        assert!(price_rx.try_recv().is_err());
+
+        // YES ask drops to 0.45 while NO's 0.45 level is pulled in the same frame.
+        ws.handle_frame(r#"{"event_type":"price_change","market":"0xabc","timestamp":"1757908892351","price_changes":[
+            {"asset_id":"yes1","price":"0.45","size":"10","side":"SELL"},
+            {"asset_id":"no1","price":"0.58","size":"10","side":"SELL"},
+            {"asset_id":"no1","price":"0.45","size":"0","side":"SELL"}
+        ]}"#).await;
+        let msg = price_rx.try_recv().expect("coalesced update");
+        assert!(price_rx.try_recv().is_err(), "exactly one update per market per frame");
+        assert_eq!(&*msg.market_id, "market1");
+        assert_eq!(msg.quotes.len(), 2);
+        assert_eq!(msg.exchange_ts_ms, Some(1757908892351));
+        assert_eq!(ws.feed_latency.summary().sample_count, 1, "only timestamped frames are sampled");
+        let no = msg.quotes.iter().find(|q| &*q.asset_id == "no1").unwrap();
+        assert_eq!(no.best_ask, Price::from_ticks(5800), "routes post-frame best ask");
+    }
+
+    #[tokio::test]
+    async fn contradicted_snapshot_closes_windows_without_gating_the_book() {
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "market1".to_string(),
+            question: "Test market".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+        let (price_tx, _price_rx) = mpsc::channel(16);
+        let (trade_tx, _trade_rx) = mpsc::channel(16);
+        let (sweep_tx, mut sweep_rx) = mpsc::channel(16);
+        let mut router = ShardRouter::new(store.clone());
+        router.add_shard(price_tx, trade_tx, sweep_tx);
+        let ws = FrameHandler::new(store.clone(), router);
+
+        ws.handle_frame(r#"{"event_type":"book","asset_id":"yes1","hash":"h1","asks":[{"price":"0.60","size":"10"}],"bids":[]}"#).await;
+        assert!(sweep_rx.try_recv().is_err());
+
+        // Same hash, different book: the local book had drifted.
+        ws.handle_frame(r#"{"event_type":"book","asset_id":"yes1","hash":"h1","asks":[{"price":"0.60","size":"12"}],"bids":[]}"#).await;
+        let Ok(FaultSweep::Market { market_id, reason }) = sweep_rx.try_recv() else {
+            panic!("expected a market sweep");
+        };
+        assert_eq!((&*market_id, reason), ("market1", CloseReason::BookCorrupt));
+        assert!(!store.is_book_corrupt("yes1"), "the snapshot corrected the book");
+        assert_eq!(store.book_integrity("yes1").unwrap().hash_mismatches, 1);
+    }
+}
@@ -1,76 +1,266 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde::Deserialize;
 use tracing::warn;
 
+use crate::types::Price;
+
 static PARSE_FAILURES: AtomicU64 = AtomicU64::new(0);
 
-/// A single price level in a book snapshot.
-#[derive(Debug, Deserialize, Clone)]
-pub struct BookLevel {
-    pub price: String,
-    pub size: String,
-}
+/// event_type → messages seen with a type we don't handle.
+static UNKNOWN_EVENT_TYPES: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Distinct unknown event types tracked by name; any beyond this are counted under `"other"`.
+const MAX_UNKNOWN_EVENT_TYPES: usize = 32;
+
+/// A single price level in a book snapshot, as `(price, size)`.
+pub type BookLevel = (Price, f64);
 
 /// A single order-level change in a price_change message.
-#[derive(Debug, Deserialize, Clone)]
-pub struct BookChange {
-    pub price: String,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelChange {
+    pub price: Price,
     /// "SELL" = ask side, "BUY" = bid side.
-    pub side: String,
-    pub size: String,
+    pub is_ask: bool,
+    pub size: f64,
+}
+
+/// One level of a `book` snapshot. Fields borrow from the frame; strings with
+/// escapes (never sent for numbers) fall back to owned.
+#[derive(Debug, Deserialize)]
+struct RawLevel<'a> {
+    #[serde(borrow)]
+    price: Cow<'a, str>,
+    #[serde(borrow)]
+    size: Cow<'a, str>,
 }
 
 /// One entry inside the `price_changes` array (September 2025+ format).
-#[derive(Debug, Deserialize, Clone)]
-pub struct PriceChangeEntry {
-    pub asset_id: String,
-    pub price: String,
-    pub size: String,
-    pub side: String,
-    pub best_bid: Option<String>,
-    pub best_ask: Option<String>,
+#[derive(Debug, Deserialize)]
+struct RawPriceChange<'a> {
+    #[serde(borrow)]
+    asset_id: Cow<'a, str>,
+    #[serde(borrow)]
+    price: Cow<'a, str>,
+    #[serde(borrow)]
+    size: Cow<'a, str>,
+    #[serde(borrow)]
+    side: Cow<'a, str>,
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    best_bid: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    best_ask: Option<Cow<'a, str>>,
+    /// Server hash of the token's book after this change.
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    hash: Option<Cow<'a, str>>,
 }
 
-/// Raw deserializable shape covering all market-channel WS messages.
-/// Fields are optional because different event types carry different subsets.
+/// Raw deserializable shape covering all market-channel WS messages, borrowing
+/// from the frame text. Fields are optional because different event types carry
+/// different subsets.
 #[derive(Debug, Deserialize)]
-struct RawBookMsg {
-    pub event_type: Option<String>,
+struct RawBookMsg<'a> {
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    event_type: Option<Cow<'a, str>>,
     /// Present on `book` and `last_trade_price`; absent on new `price_change` format.
-    pub asset_id: Option<String>,
-    pub asks: Option<Vec<BookLevel>>,
-    pub bids: Option<Vec<BookLevel>>,
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    asset_id: Option<Cow<'a, str>>,
+    /// `book` only; parsed straight to keyed levels, skipping any that don't parse.
+    #[serde(default, deserialize_with = "deserialize_levels")]
+    asks: Vec<BookLevel>,
+    #[serde(default, deserialize_with = "deserialize_levels")]
+    bids: Vec<BookLevel>,
     /// New `price_change` format (September 2025+): array of per-asset change entries.
-    pub price_changes: Option<Vec<PriceChangeEntry>>,
+    #[serde(borrow)]
+    price_changes: Option<Vec<RawPriceChange<'a>>>,
     /// `last_trade_price` only.
-    pub price: Option<String>,
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    price: Option<Cow<'a, str>>,
+    /// `book` only: server hash of the snapshotted book.
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    hash: Option<Cow<'a, str>>,
+    /// `tick_size_change` only.
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    old_tick_size: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "borrowed_opt")]
+    new_tick_size: Option<Cow<'a, str>>,
+    /// Exchange-side event time, ms since epoch. Sent as a string; accepted as a
+    /// number too, and ignored if it is anything else.
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    timestamp: Option<u64>,
+}
+
+/// `Option<Cow<str>>` that borrows from the frame; serde's own impl for it
+/// always allocates.
+fn borrowed_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed<'de>>::deserialize(deserializer)?.map(|b| b.0))
+}
+
+/// Builds the level list in place while the array is read; levels whose price
+/// or size don't parse are dropped.
+fn deserialize_levels<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BookLevel>, D::Error> {
+    struct Levels;
+
+    impl<'de> Visitor<'de> for Levels {
+        type Value = Vec<BookLevel>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of price levels")
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut levels = Vec::with_capacity(seq.size_hint().unwrap_or(16));
+            while let Some(level) = seq.next_element::<RawLevel<'de>>()? {
+                if let (Some(price), Ok(size)) = (Price::parse(&level.price), level.size.parse::<f64>()) {
+                    levels.push((price, size));
+                }
+            }
+            Ok(levels)
+        }
+    }
+
+    deserializer.deserialize_any(Levels)
 }
 
-/// Parsed event from a single WS message object.
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    struct Timestamp;
+
+    impl<'de> Visitor<'de> for Timestamp {
+        type Value = Option<u64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a timestamp")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(v.parse().ok())
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(u64::try_from(v).ok())
+        }
+
+        fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            while seq.next_element::<IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(Timestamp)
+}
+
+/// Parsed event from a single WS message object. Ids and hashes borrow from the
+/// frame text; prices are already `Price`s.
 #[derive(Debug)]
-pub enum ParsedFrame {
+pub enum ParsedFrame<'a> {
     /// Full order book snapshot for one token.
     BookSnapshot {
-        asset_id: String,
+        asset_id: Cow<'a, str>,
         asks: Vec<BookLevel>,
         bids: Vec<BookLevel>,
+        hash: Option<Cow<'a, str>>,
+        exchange_ts_ms: Option<u64>,
     },
-    /// Incremental order-level change for one token.
-    /// `best_bid`/`best_ask` are provided directly by the server when available.
+    /// Incremental order-level change for one token; `change` is None when the
+    /// level's price or size didn't parse.
+    /// `best_bid`/`best_ask` and the post-change book `hash` are provided directly
+    /// by the server when available.
     BookPriceChange {
-        asset_id: String,
-        change: BookChange,
-        best_bid: Option<f64>,
-        best_ask: Option<f64>,
+        asset_id: Cow<'a, str>,
+        change: Option<LevelChange>,
+        best_bid: Option<Price>,
+        best_ask: Option<Price>,
+        hash: Option<Cow<'a, str>>,
+        exchange_ts_ms: Option<u64>,
     },
     /// A trade executed; used for volume spike classification.
     LastTradePrice {
-        asset_id: String,
-        price: f64,
+        asset_id: Cow<'a, str>,
+        price: Price,
+        exchange_ts_ms: Option<u64>,
+    },
+    /// The market's minimum price increment changed (sent as prices near 0 or 1).
+    TickSizeChange {
+        asset_id: Cow<'a, str>,
+        old_tick_size: Option<Price>,
+        new_tick_size: Price,
+        exchange_ts_ms: Option<u64>,
     },
 }
 
+impl ParsedFrame<'_> {
+    /// Exchange-side timestamp of the message this entry came from, ms since epoch.
+    pub fn exchange_ts_ms(&self) -> Option<u64> {
+        match self {
+            ParsedFrame::BookSnapshot { exchange_ts_ms, .. }
+            | ParsedFrame::BookPriceChange { exchange_ts_ms, .. }
+            | ParsedFrame::LastTradePrice { exchange_ts_ms, .. }
+            | ParsedFrame::TickSizeChange { exchange_ts_ms, .. } => *exchange_ts_ms,
+        }
+    }
+}
+
+/// Frames that failed to parse at all since process start.
+pub fn parse_failures() -> u64 {
+    PARSE_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Messages per unhandled `event_type` since process start, by name. Messages
+/// without an `event_type` are counted under `"none"`.
+pub fn unknown_event_counts() -> BTreeMap<String, u64> {
+    UNKNOWN_EVENT_TYPES.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+fn count_unknown_event(event_type: Option<&str>) {
+    let Ok(mut counts) = UNKNOWN_EVENT_TYPES.lock() else {
+        return;
+    };
+    let name = event_type.unwrap_or("none");
+    let key = if counts.contains_key(name) || counts.len() < MAX_UNKNOWN_EVENT_TYPES {
+        name
+    } else {
+        "other"
+    };
+    let count = counts.entry(key.to_string()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        warn!("[WS PARSE] unhandled event_type {name:?}");
+    }
+}
+
 /// Parse a raw WebSocket text frame into zero or more events.
 ///
 /// Polymarket market-channel messages arrive as either:
@@ -80,7 +270,10 @@ pub enum ParsedFrame {
 /// The `price_change` format (September 2025+) nests per-asset data inside a
 /// `price_changes` array, each entry carrying `asset_id`, the changed level,
 /// and the resulting `best_bid`/`best_ask`.
-pub fn parse_ws_frame(raw: &str) -> Vec<ParsedFrame> {
+///
+/// Ids and hashes borrow from `raw` and prices are parsed to `Price`s while
+/// the JSON is read, so the only allocations are the message and level lists.
+pub fn parse_ws_frame(raw: &str) -> Vec<ParsedFrame<'_>> {
     let msgs: Vec<RawBookMsg> = if raw.trim_start().starts_with('[') {
         serde_json::from_str(raw).unwrap_or_default()
     } else {
@@ -92,14 +285,14 @@ pub fn parse_ws_frame(raw: &str) -> Vec<ParsedFrame> {
 
     if msgs.is_empty() {
         let count = PARSE_FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
-        if count <= 10 || count % 1000 == 0 {
+        if count <= 10 || count.is_multiple_of(1000) {
             let sample = &raw[..500.min(raw.len())];
             warn!(count, "[WS PARSE] unrecognized frame: {sample}");
         }
         return vec![];
     }
 
-    let mut frames = Vec::new();
+    let mut frames = Vec::with_capacity(msgs.len());
     for msg in msgs {
         expand_raw_msg(msg, &mut frames);
     }
@@ -109,14 +302,17 @@ pub fn parse_ws_frame(raw: &str) -> Vec<ParsedFrame> {
 /// Expands one raw message into zero or more `ParsedFrame`s.
 /// `price_change` messages can contain multiple entries (one per asset) so a
 /// single raw message may produce multiple frames.
-fn expand_raw_msg(msg: RawBookMsg, out: &mut Vec<ParsedFrame>) {
+fn expand_raw_msg<'a>(msg: RawBookMsg<'a>, out: &mut Vec<ParsedFrame<'a>>) {
+    let exchange_ts_ms = msg.timestamp;
     match msg.event_type.as_deref() {
         Some("book") => {
             if let Some(asset_id) = msg.asset_id {
                 out.push(ParsedFrame::BookSnapshot {
                     asset_id,
-                    asks: msg.asks.unwrap_or_default(),
-                    bids: msg.bids.unwrap_or_default(),
+                    asks: msg.asks,
+                    bids: msg.bids,
+                    hash: msg.hash,
+                    exchange_ts_ms,
                 });
             }
         }
@@ -125,30 +321,41 @@ fn expand_raw_msg(msg: RawBookMsg, out: &mut Vec<ParsedFrame>) {
                 Some(e) if !e.is_empty() => e,
                 _ => return,
             };
+            out.reserve(entries.len());
             for entry in entries {
-                let best_bid = entry.best_bid.as_deref().and_then(|s| s.parse::<f64>().ok());
-                let best_ask = entry.best_ask.as_deref().and_then(|s| s.parse::<f64>().ok());
-                let change = BookChange {
-                    price: entry.price,
-                    side: entry.side,
-                    size: entry.size,
+                let best_bid = entry.best_bid.as_deref().and_then(Price::parse);
+                let best_ask = entry.best_ask.as_deref().and_then(Price::parse);
+                let change = match (Price::parse(&entry.price), entry.size.parse::<f64>()) {
+                    (Some(price), Ok(size)) => Some(LevelChange { price, is_ask: entry.side == "SELL", size }),
+                    _ => None,
                 };
                 out.push(ParsedFrame::BookPriceChange {
                     asset_id: entry.asset_id,
                     change,
                     best_bid,
                     best_ask,
+                    hash: entry.hash,
+                    exchange_ts_ms,
                 });
             }
         }
         Some("last_trade_price") => {
             if let (Some(asset_id), Some(price_str)) = (msg.asset_id, msg.price.as_deref()) {
-                if let Ok(price) = price_str.parse::<f64>() {
-                    out.push(ParsedFrame::LastTradePrice { asset_id, price });
+                if let Some(price) = Price::parse(price_str) {
+                    out.push(ParsedFrame::LastTradePrice { asset_id, price, exchange_ts_ms });
                 }
             }
         }
-        _ => {}
+        Some("tick_size_change") => {
+            let new_tick_size = msg.new_tick_size.as_deref().and_then(Price::parse);
+            if let (Some(asset_id), Some(new_tick_size)) = (msg.asset_id, new_tick_size) {
+                if new_tick_size.is_positive() {
+                    let old_tick_size = msg.old_tick_size.as_deref().and_then(Price::parse);
+                    out.push(ParsedFrame::TickSizeChange { asset_id, old_tick_size, new_tick_size, exchange_ts_ms });
+                }
+            }
+        }
+        other => count_unknown_event(other),
     }
 }
 
@@ -162,12 +369,38 @@ mod tests {
         let frames = parse_ws_frame(raw);
         assert_eq!(frames.len(), 1);
         match &frames[0] {
-            ParsedFrame::BookSnapshot { asset_id, asks, bids } => {
+            ParsedFrame::BookSnapshot { asset_id, asks, bids, .. } => {
                 assert_eq!(asset_id, "tok1");
                 assert_eq!(asks.len(), 1);
-                assert_eq!(asks[0].price, "0.55");
+                assert_eq!(asks[0], (Price::from_ticks(5500), 100.0));
                 assert_eq!(bids.len(), 1);
-                assert_eq!(bids[0].price, "0.54");
+                assert_eq!(bids[0], (Price::from_ticks(5400), 200.0));
+            }
+            other => panic!("expected BookSnapshot, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn prices_parse_exactly_and_ids_borrow_from_frame() {
+        for (s, ticks) in [("0.55", 5500), ("0.001", 10), (".5", 5000), ("1", 10_000), ("0.12345", 1235), ("5e-1", 5000)] {
+            assert_eq!(Price::parse(s), Some(Price::from_ticks(ticks)), "{s}");
+            assert_eq!(Price::parse(s), s.parse::<f64>().ok().map(Price::from_f64), "{s} matches the f64 path");
+        }
+        assert_eq!(Price::parse("abc"), None);
+        assert_eq!(Price::parse("NaN"), None);
+        // Exact where f64 is not: 0.1 + 0.2 == 0.3, and sums snap to the tick grid.
+        let (a, b) = (Price::parse("0.1").unwrap(), Price::parse("0.2").unwrap());
+        assert_eq!(a + b, Price::parse("0.3").unwrap());
+        assert_eq!(Price::parse("0.995").unwrap().round_to(Price::from_ticks(100)), Price::ONE);
+        assert_eq!(serde_json::to_string(&Price::parse("0.55").unwrap()).unwrap(), "0.55");
+
+        let raw = r#"{"event_type":"book","asset_id":"tok1","hash":"ab12","asks":[{"price":"0.55","size":"1"},{"price":"x","size":"1"},{"price":"0.56","size":"y"}],"bids":null}"#;
+        match &parse_ws_frame(raw)[0] {
+            ParsedFrame::BookSnapshot { asset_id, asks, bids, hash, .. } => {
+                assert!(matches!(asset_id, Cow::Borrowed("tok1")));
+                assert!(matches!(hash, Some(Cow::Borrowed("ab12"))));
+                assert_eq!(asks, &[(Price::from_ticks(5500), 1.0)], "unparseable levels are skipped");
+                assert!(bids.is_empty());
             }
             other => panic!("expected BookSnapshot, got {other:?}"),
         }
@@ -175,17 +408,17 @@ mod tests {
 
     #[test]
     fn parses_new_price_change_format() {
-        let raw = r#"{"event_type":"price_change","market":"0xabc","timestamp":"1757908892351","price_changes":[{"asset_id":"tok1","price":"0.55","size":"200","side":"SELL","best_bid":"0.52","best_ask":"0.55"}]}"#;
+        let raw = r#"{"event_type":"price_change","market":"0xabc","timestamp":"1757908892351","price_changes":[{"asset_id":"tok1","price":"0.55","size":"200","side":"SELL","best_bid":"0.52","best_ask":"0.55","hash":"0f2a"}]}"#;
         let frames = parse_ws_frame(raw);
         assert_eq!(frames.len(), 1);
         match &frames[0] {
-            ParsedFrame::BookPriceChange { asset_id, change, best_bid, best_ask } => {
+            ParsedFrame::BookPriceChange { asset_id, change, best_bid, best_ask, hash, exchange_ts_ms } => {
                 assert_eq!(asset_id, "tok1");
-                assert_eq!(change.side, "SELL");
-                assert_eq!(change.price, "0.55");
-                assert_eq!(change.size, "200");
-                assert!((best_bid.unwrap() - 0.52).abs() < 1e-9);
-                assert!((best_ask.unwrap() - 0.55).abs() < 1e-9);
+                assert_eq!(hash.as_deref(), Some("0f2a"));
+                assert_eq!(*exchange_ts_ms, Some(1757908892351));
+                assert_eq!(*change, Some(LevelChange { price: Price::from_ticks(5500), is_ask: true, size: 200.0 }));
+                assert_eq!(*best_bid, Some(Price::from_ticks(5200)));
+                assert_eq!(*best_ask, Some(Price::from_ticks(5500)));
             }
             other => panic!("expected BookPriceChange, got {other:?}"),
         }
@@ -199,15 +432,14 @@ mod tests {
         match &frames[0] {
             ParsedFrame::BookPriceChange { asset_id, change, .. } => {
                 assert_eq!(asset_id, "tok1");
-                assert_eq!(change.side, "SELL");
-                assert_eq!(change.size, "0");
+                assert!(change.is_some_and(|c| c.is_ask && c.size == 0.0));
             }
             other => panic!("expected BookPriceChange, got {other:?}"),
         }
         match &frames[1] {
             ParsedFrame::BookPriceChange { asset_id, change, .. } => {
                 assert_eq!(asset_id, "tok2");
-                assert_eq!(change.side, "BUY");
+                assert!(change.is_some_and(|c| !c.is_ask));
             }
             other => panic!("expected BookPriceChange, got {other:?}"),
         }
@@ -226,19 +458,36 @@ mod tests {
         let frames = parse_ws_frame(raw);
         assert_eq!(frames.len(), 1);
         match &frames[0] {
-            ParsedFrame::LastTradePrice { asset_id, price } => {
+            ParsedFrame::LastTradePrice { asset_id, price, .. } => {
                 assert_eq!(asset_id, "tok1");
-                assert!((price - 0.57).abs() < 1e-9);
+                assert_eq!(*price, Price::from_ticks(5700));
             }
             other => panic!("expected LastTradePrice, got {other:?}"),
         }
     }
 
     #[test]
-    fn unknown_event_type_returns_empty() {
+    fn parses_tick_size_change() {
+        let raw = r#"{"event_type":"tick_size_change","asset_id":"tok1","market":"0xabc","old_tick_size":"0.01","new_tick_size":"0.001","timestamp":"100000000"}"#;
+        let frames = parse_ws_frame(raw);
+        assert_eq!(frames.len(), 1);
+        match &frames[0] {
+            ParsedFrame::TickSizeChange { asset_id, old_tick_size, new_tick_size, exchange_ts_ms } => {
+                assert_eq!(asset_id, "tok1");
+                assert_eq!(*old_tick_size, Some(Price::from_ticks(100)));
+                assert_eq!(*new_tick_size, Price::from_ticks(10));
+                assert_eq!(*exchange_ts_ms, Some(100000000));
+            }
+            other => panic!("expected TickSizeChange, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_event_type_returns_empty_and_is_counted() {
         let raw = r#"{"event_type":"some_other_event","asset_id":"tok1"}"#;
         let frames = parse_ws_frame(raw);
         assert!(frames.is_empty());
+        assert!(unknown_event_counts().get("some_other_event").is_some_and(|&n| n >= 1));
     }
 
     #[test]
@@ -1,4 +1,9 @@
+pub mod backoff;
+pub mod capture;
 pub mod connection;
 pub mod messages;
+pub mod pool;
+pub mod silence;
 
-pub use connection::WsManager;
+pub use pool::WsManager;
+pub use silence::SilenceMonitor;
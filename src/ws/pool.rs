@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::api::health::{HealthState, WsConnectionHealth};
+use crate::api::latency::{FeedLatency, ParseLatency};
+use crate::config::{ReconnectConfig, CHANNEL_CAPACITY, WS_REBALANCE_SLACK};
+use crate::detector::ShardRouter;
+use crate::state::market_store::MarketStore;
+use crate::types::{ControlMsg, Market, NegRiskEvent};
+use crate::ws::capture::{CaptureRecord, FrameCapture};
+use crate::ws::connection::{ConnCommand, FrameHandler, WsConnection};
+
+/// Market → connection assignment. Markets are placed whole (both tokens on one
+/// connection, so a frame still covers the market atomically) on the connection
+/// holding the fewest markets.
+#[derive(Debug)]
+struct Partitions {
+    /// market_id → (connection index, asset ids)
+    markets: HashMap<String, (usize, Vec<String>)>,
+    /// Markets per connection.
+    loads: Vec<usize>,
+}
+
+impl Partitions {
+    fn new(connections: usize) -> Self {
+        Self { markets: HashMap::new(), loads: vec![0; connections.max(1)] }
+    }
+
+    fn least_loaded(&self) -> usize {
+        (0..self.loads.len()).min_by_key(|&i| self.loads[i]).unwrap_or(0)
+    }
+
+    fn most_loaded(&self) -> usize {
+        (0..self.loads.len()).rev().max_by_key(|&i| self.loads[i]).unwrap_or(0)
+    }
+
+    /// Assigns a market to the least-loaded connection. None if it is already assigned.
+    fn assign(&mut self, market_id: String, asset_ids: Vec<String>) -> Option<usize> {
+        if self.markets.contains_key(&market_id) {
+            return None;
+        }
+        let conn = self.least_loaded();
+        self.loads[conn] += 1;
+        self.markets.insert(market_id, (conn, asset_ids));
+        Some(conn)
+    }
+
+    /// Connection currently holding a market.
+    fn owner(&self, market_id: &str) -> Option<usize> {
+        self.markets.get(market_id).map(|(conn, _)| *conn)
+    }
+
+    /// Removes a market, returning the connection that held it and its asset ids.
+    fn release(&mut self, market_id: &str) -> Option<(usize, Vec<String>)> {
+        let (conn, asset_ids) = self.markets.remove(market_id)?;
+        self.loads[conn] -= 1;
+        Some((conn, asset_ids))
+    }
+
+    /// Moves markets from the fullest to the emptiest connection until no two
+    /// connections differ by more than `slack` markets. Returns `(asset_ids, from, to)`.
+    fn rebalance(&mut self, slack: usize) -> Vec<(Vec<String>, usize, usize)> {
+        let mut moves = Vec::new();
+        loop {
+            let (from, to) = (self.most_loaded(), self.least_loaded());
+            if self.loads[from] - self.loads[to] <= slack.max(1) {
+                return moves;
+            }
+            let Some((_, entry)) = self.markets.iter_mut().find(|(_, (conn, _))| *conn == from) else {
+                return moves;
+            };
+            entry.0 = to;
+            self.loads[from] -= 1;
+            self.loads[to] += 1;
+            moves.push((entry.1.clone(), from, to));
+        }
+    }
+
+    fn assets_for(&self, conn: usize) -> HashSet<String> {
+        self.markets
+            .values()
+            .filter(|(c, _)| *c == conn)
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect()
+    }
+}
+
+/// Pool of market-channel WebSocket connections, each owning a partition of the
+/// subscribed markets. Routes `ControlMsg` subscriptions to the owning connection
+/// and rebalances partitions as markets come and go.
+pub struct WsManager {
+    ws_url: String,
+    store: Arc<MarketStore>,
+    handler: FrameHandler,
+    control_rx: mpsc::Receiver<ControlMsg>,
+    health: Arc<HealthState>,
+    connections: usize,
+    reconnect: ReconnectConfig,
+}
+
+impl WsManager {
+    pub fn new(
+        ws_url: String,
+        store: Arc<MarketStore>,
+        router: ShardRouter,
+        control_rx: mpsc::Receiver<ControlMsg>,
+        health: Arc<HealthState>,
+        connections: usize,
+    ) -> Self {
+        let handler = FrameHandler::new(Arc::clone(&store), router);
+        Self {
+            ws_url,
+            store,
+            handler,
+            control_rx,
+            health,
+            connections: connections.max(1),
+            reconnect: ReconnectConfig::default(),
+        }
+    }
+
+    /// Reconnect backoff and circuit breaker settings for every connection.
+    pub fn with_reconnect(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Records exchange → receive latency into `feed_latency`.
+    pub fn with_feed_latency(mut self, feed_latency: Arc<FeedLatency>) -> Self {
+        self.handler = self.handler.with_feed_latency(feed_latency);
+        self
+    }
+
+    /// Records per-frame parse time into `parse_latency`.
+    pub fn with_parse_latency(mut self, parse_latency: Arc<ParseLatency>) -> Self {
+        self.handler = self.handler.with_parse_latency(parse_latency);
+        self
+    }
+
+    /// Captures every frame, plus subscription changes, into `capture`.
+    pub fn with_capture(mut self, capture: FrameCapture) -> Self {
+        self.handler = self.handler.with_capture(capture);
+        self
+    }
+
+    pub async fn run(self) {
+        let Self { ws_url, store, handler, mut control_rx, health, connections, reconnect } = self;
+        let handler = Arc::new(handler);
+
+        let mut partitions = Partitions::new(connections);
+        for market_id in store.all_market_ids() {
+            if let Some(asset_ids) = store.token_ids_for_market(&market_id) {
+                partitions.assign(market_id, asset_ids);
+            }
+        }
+
+        let mut command_txs = Vec::with_capacity(connections);
+        for index in 0..connections {
+            let (command_tx, command_rx) = mpsc::channel(CHANNEL_CAPACITY);
+            let conn_health = Arc::new(WsConnectionHealth::new(index));
+            health.add_ws_connection(Arc::clone(&conn_health));
+            let conn = WsConnection::new(
+                ws_url.clone(),
+                partitions.assets_for(index),
+                Arc::clone(&handler),
+                command_rx,
+                conn_health,
+                reconnect.clone(),
+            );
+            tokio::spawn(async move { conn.run().await });
+            command_txs.push(command_tx);
+        }
+        info!(
+            "[WS POOL] {} connection(s), {} markets partitioned {:?}",
+            connections,
+            partitions.markets.len(),
+            partitions.loads,
+        );
+
+        while let Some(ctrl) = control_rx.recv().await {
+            match ctrl {
+                ControlMsg::Subscribe(markets) => {
+                    if let Some(capture) = handler.capture() {
+                        capture.record(subscribed_record(&store, &markets));
+                    }
+                    let mut by_conn: HashMap<usize, Vec<String>> = HashMap::new();
+                    for m in markets {
+                        let asset_ids = vec![m.yes_token_id, m.no_token_id];
+                        if let Some(conn) = partitions.assign(m.id, asset_ids.clone()) {
+                            by_conn.entry(conn).or_default().extend(asset_ids);
+                        }
+                    }
+                    for (conn, asset_ids) in by_conn {
+                        send_command(&command_txs[conn], ConnCommand::Subscribe(asset_ids)).await;
+                    }
+                }
+                ControlMsg::Unsubscribe(market_id) => {
+                    if let Some(capture) = handler.capture() {
+                        let record = CaptureRecord::Unsubscribe { t: now_ns(), market_id: market_id.clone() };
+                        capture.record(record);
+                    }
+                    if let Some((conn, asset_ids)) = partitions.release(&market_id) {
+                        send_command(&command_txs[conn], ConnCommand::Unsubscribe(asset_ids)).await;
+                    }
+                }
+                ControlMsg::Resubscribe(asset_ids) => {
+                    let mut by_conn: HashMap<usize, Vec<String>> = HashMap::new();
+                    for asset_id in asset_ids {
+                        let owner = store
+                            .get_market_for_token(&asset_id)
+                            .and_then(|(market_id, _, _)| partitions.owner(&market_id));
+                        if let Some(conn) = owner {
+                            by_conn.entry(conn).or_default().push(asset_id);
+                        }
+                    }
+                    for (conn, asset_ids) in by_conn {
+                        send_command(&command_txs[conn], ConnCommand::Resubscribe(asset_ids)).await;
+                    }
+                }
+            }
+
+            let moves = partitions.rebalance(WS_REBALANCE_SLACK);
+            if !moves.is_empty() {
+                info!("[WS POOL] rebalancing {} market(s), loads now {:?}", moves.len(), partitions.loads);
+            }
+            // Subscribe on the new connection before dropping the old one so the
+            // market is never unwatched.
+            for (asset_ids, from, to) in moves {
+                send_command(&command_txs[to], ConnCommand::Subscribe(asset_ids.clone())).await;
+                send_command(&command_txs[from], ConnCommand::Unsubscribe(asset_ids)).await;
+            }
+        }
+
+        info!("[WS POOL] control channel closed, shutting down connections");
+    }
+}
+
+/// Capture record for newly subscribed markets: binary markets as-is, event
+/// members via their (already stored) neg-risk event.
+fn subscribed_record(store: &MarketStore, markets: &[Market]) -> CaptureRecord {
+    let mut events: Vec<NegRiskEvent> = Vec::new();
+    let mut binary = Vec::new();
+    for m in markets {
+        match store.event_for_market(&m.id) {
+            Some(event) if !events.iter().any(|e| e.id == event.id) => events.push((*event).clone()),
+            Some(_) => {}
+            None => binary.push(m.clone()),
+        }
+    }
+    CaptureRecord::Markets { t: now_ns(), markets: binary, events }
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+async fn send_command(tx: &mpsc::Sender<ConnCommand>, cmd: ConnCommand) {
+    if let Err(e) = tx.send(cmd).await {
+        warn!("[WS POOL] connection task gone, dropping command: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use polymarket_mock_ws::{frames, MockConfig, MockServer};
+
+    use crate::types::{Category, Price, PriceChangeMsg};
+
+    fn ids(market_id: &str) -> Vec<String> {
+        vec![format!("{market_id}-yes"), format!("{market_id}-no")]
+    }
+
+    #[test]
+    fn markets_fill_least_loaded_and_rebalance_after_removals() {
+        let mut partitions = Partitions::new(3);
+        for i in 0..9 {
+            let market_id = format!("m{i}");
+            partitions.assign(market_id.clone(), ids(&market_id));
+        }
+        assert_eq!(partitions.loads, vec![3, 3, 3]);
+        assert!(partitions.assign("m0".to_string(), ids("m0")).is_none(), "no double assignment");
+        assert_eq!(partitions.assets_for(0).len(), 6);
+
+        // Drain connection 0 entirely.
+        let on_zero: Vec<String> = partitions
+            .markets
+            .iter()
+            .filter(|(_, (conn, _))| *conn == 0)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &on_zero {
+            assert_eq!(partitions.release(id).map(|(conn, _)| conn), Some(0));
+        }
+        assert_eq!(partitions.loads, vec![0, 3, 3]);
+
+        let moves = partitions.rebalance(1);
+        assert_eq!(moves.len(), 2);
+        assert!(moves.iter().all(|(assets, from, to)| assets.len() == 2 && *from != 0 && *to == 0));
+        assert_eq!(partitions.loads, vec![2, 2, 2]);
+        assert!(partitions.rebalance(1).is_empty());
+    }
+
+    async fn next_update(rx: &mut mpsc::Receiver<PriceChangeMsg>) -> PriceChangeMsg {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.expect("update within 5s").expect("router alive")
+    }
+
+    fn best_ask(msg: &PriceChangeMsg, asset_id: &str) -> Option<Price> {
+        msg.quotes.iter().find(|q| &*q.asset_id == asset_id).map(|q| q.best_ask)
+    }
+
+    #[tokio::test]
+    async fn pool_streams_from_mock_server_and_resnapshots_after_a_close() {
+        let server = MockServer::start(MockConfig::default()).await.unwrap();
+        server.set_book("yes1", &[("0.60", "10")], &[("0.55", "5")]);
+        server.set_book("no1", &[("0.45", "10")], &[("0.40", "5")]);
+
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "market1".to_string(),
+            question: "Test market".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+        let (price_tx, mut price_rx) = mpsc::channel(64);
+        let (trade_tx, _trade_rx) = mpsc::channel(64);
+        let (sweep_tx, _sweep_rx) = mpsc::channel(1);
+        let mut router = ShardRouter::new(store.clone());
+        router.add_shard(price_tx, trade_tx, sweep_tx);
+        let health = Arc::new(HealthState::new());
+        let (_control_tx, control_rx) = mpsc::channel(8);
+        let reconnect = ReconnectConfig {
+            base: Duration::from_millis(10),
+            max: Duration::from_millis(50),
+            jitter: 0.0,
+            ..ReconnectConfig::default()
+        };
+        let pool = WsManager::new(server.url(), store.clone(), router, control_rx, Arc::clone(&health), 1)
+            .with_reconnect(reconnect);
+        tokio::spawn(pool.run());
+
+        let snapshot = next_update(&mut price_rx).await;
+        assert_eq!(snapshot.quotes.len(), 2, "both books arrive in one subscribe answer");
+        assert_eq!(best_ask(&snapshot, "yes1"), Some(Price::from_f64(0.60)));
+
+        let failures_before = crate::ws::messages::parse_failures();
+        server.send_raw("{not json");
+        server.send(&frames::price_change("market1", &[("yes1", "0.50", "10", "SELL")]));
+        let update = next_update(&mut price_rx).await;
+        assert_eq!(best_ask(&update, "yes1"), Some(Price::from_f64(0.50)));
+        assert!(crate::ws::messages::parse_failures() > failures_before);
+
+        server.disconnect_all(1001, "going away");
+        assert!(server.wait_for_connections(2, Duration::from_secs(5)).await, "reconnects");
+        let resnapshot = next_update(&mut price_rx).await;
+        assert_eq!(resnapshot.quotes.len(), 2);
+        assert_eq!(best_ask(&resnapshot, "yes1"), Some(Price::from_f64(0.60)), "book replaced by the fresh snapshot");
+        assert!(!store.is_interrupted("market1"));
+
+        let history = health.ws_connections()[0].history();
+        assert_eq!(history[0].close_code, Some(1001));
+        assert_eq!(history[0].close_reason.as_deref(), Some("going away"));
+    }
+}
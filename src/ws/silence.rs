@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::api::health::HealthState;
+use crate::config::{WS_RESUBSCRIBE_COOLDOWN_SECS, WS_SILENCE_ALERT_SECS};
+use crate::state::market_store::{MarketStore, Silence};
+use crate::types::ControlMsg;
+use crate::ws::capture::{CaptureRecord, FrameCapture};
+
+/// Watches per-token feed activity. Tokens that never received a `book` snapshot
+/// are resubscribed; tokens silent while their market has an open window mark the
+/// market stale (the detector force-closes its windows) and are resubscribed too,
+/// as are both legs of any market with a book marked corrupt.
+pub struct SilenceMonitor {
+    store: Arc<MarketStore>,
+    control_tx: mpsc::Sender<ControlMsg>,
+    health: Arc<HealthState>,
+    /// asset_id → last automatic resubscribe, for the cooldown.
+    last_resubscribe: HashMap<String, Instant>,
+    capture: Option<FrameCapture>,
+}
+
+impl SilenceMonitor {
+    pub fn new(store: Arc<MarketStore>, control_tx: mpsc::Sender<ControlMsg>, health: Arc<HealthState>) -> Self {
+        Self { store, control_tx, health, last_resubscribe: HashMap::new(), capture: None }
+    }
+
+    /// Records stale marks into the raw feed capture so replays see them.
+    pub fn with_capture(mut self, capture: FrameCapture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            let resubscribe = self.sweep(now_ns(), Instant::now());
+            if resubscribe.is_empty() {
+                continue;
+            }
+            info!("[SILENCE] resubscribing {} asset_ids", resubscribe.len());
+            self.health.resubscribes.fetch_add(resubscribe.len() as u64, Ordering::Relaxed);
+            if self.control_tx.send(ControlMsg::Resubscribe(resubscribe)).await.is_err() {
+                warn!("[SILENCE] control channel closed, stopping");
+                return;
+            }
+        }
+    }
+
+    /// One pass over token activity: marks silent markets stale, updates health
+    /// counters and returns the asset ids due a resubscribe (silent, unsnapshotted
+    /// or with a corrupt book).
+    fn sweep(&mut self, now_ns: u64, now: Instant) -> Vec<String> {
+        let threshold_ns = WS_SILENCE_ALERT_SECS * 1_000_000_000;
+        let cooldown = Duration::from_secs(WS_RESUBSCRIBE_COOLDOWN_SECS);
+        let (mut silent, mut unsnapshotted) = (0u64, 0u64);
+        let mut due = Vec::new();
+
+        for (asset_id, market_id, silence) in self.store.silent_tokens(now_ns, threshold_ns) {
+            match silence {
+                Silence::NoSnapshot => {
+                    unsnapshotted += 1;
+                    due.push(asset_id);
+                }
+                Silence::Silent => {
+                    silent += 1;
+                    if self.store.mark_stale(&market_id, now_ns) {
+                        if let Some(capture) = &self.capture {
+                            capture.record(CaptureRecord::Stale { t: now_ns, market_id: market_id.clone() });
+                        }
+                        warn!(
+                            "[SILENCE] {asset_id} on {market_id} silent for >{WS_SILENCE_ALERT_SECS}s with an open window; marking market stale"
+                        );
+                    }
+                    // Resnapshot both legs so the pair comes back consistent.
+                    due.extend(self.store.token_ids_for_market(&market_id).unwrap_or_default());
+                }
+            }
+        }
+
+        // A corrupt book is repaired by the snapshot a resubscribe triggers.
+        for asset_id in self.store.corrupt_tokens() {
+            if let Some((market_id, _, _)) = self.store.get_market_for_token(&asset_id) {
+                due.extend(self.store.token_ids_for_market(&market_id).unwrap_or_default());
+            }
+        }
+
+        self.last_resubscribe.retain(|_, at| now.duration_since(*at) < cooldown);
+        due.sort();
+        due.dedup();
+        due.retain(|id| !self.last_resubscribe.contains_key(id));
+        for id in &due {
+            self.last_resubscribe.insert(id.clone(), now);
+        }
+
+        self.health.silent_tokens.store(silent, Ordering::Relaxed);
+        self.health.unsnapshotted_tokens.store(unsnapshotted, Ordering::Relaxed);
+        self.health.stale_markets.store(self.store.stale_market_count() as u64, Ordering::Relaxed);
+        due
+    }
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Category, Market, Price};
+
+    #[test]
+    fn resubscribes_unsnapshotted_tokens_once_per_cooldown() {
+        let store = MarketStore::new();
+        store.add_market(Market {
+            id: "m1".to_string(),
+            question: "Test".to_string(),
+            category: Category::Other,
+            end_date_iso: None,
+            total_volume: None,
+            yes_token_id: "yes1".to_string(),
+            no_token_id: "no1".to_string(),
+        });
+        let (control_tx, _control_rx) = mpsc::channel(4);
+        let health = Arc::new(HealthState::new());
+        let mut monitor = SilenceMonitor::new(Arc::clone(&store), control_tx, Arc::clone(&health));
+
+        let silent_at = now_ns() + 2 * WS_SILENCE_ALERT_SECS * 1_000_000_000;
+        let now = Instant::now();
+        assert_eq!(monitor.sweep(silent_at, now), vec!["no1".to_string(), "yes1".to_string()]);
+        assert_eq!(health.unsnapshotted_tokens.load(Ordering::Relaxed), 2);
+        assert!(monitor.sweep(silent_at, now + Duration::from_secs(1)).is_empty(), "cooling down");
+
+        // A silent leg under an open window marks the market stale.
+        store.apply_book_snapshot("yes1", &[(Price::from_ticks(5000), 10.0)], &[]);
+        store.apply_book_snapshot("no1", &[(Price::from_ticks(4000), 10.0)], &[]);
+        store.note_window_opened("m1");
+        let silent_at = now_ns() + 2 * WS_SILENCE_ALERT_SECS * 1_000_000_000;
+        let after_cooldown = now + Duration::from_secs(WS_RESUBSCRIBE_COOLDOWN_SECS + 1);
+        assert_eq!(monitor.sweep(silent_at, after_cooldown).len(), 2);
+        assert!(store.is_stale("m1"));
+        assert_eq!(health.silent_tokens.load(Ordering::Relaxed), 2);
+        assert_eq!(health.stale_markets.load(Ordering::Relaxed), 1);
+    }
+}